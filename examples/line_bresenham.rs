@@ -1,7 +1,7 @@
 use fltk::prelude::{GroupExt, WidgetBase, WidgetExt};
 use nalgebra::Vector2;
 use tinyrenderer::{
-    common::{basetype::Viewport, color::Color},
+    common::{basetype::Viewport, color::Color, random},
     rasterizer::{pass::RenderPass, texture::Data},
 };
 
@@ -18,6 +18,8 @@ pub fn main() {
     win.draw(move |_| {
         pass.clear();
 
+        let mut rng = random::seeded_rng(random::DEFAULT_SEED);
+
         let x_center = WIN_WIDTH as f32 / 2.0;
         let y_center = WIN_HEIGHT as f32 / 2.0;
         let r_0 = WIN_WIDTH as f32 / 8.0;
@@ -29,7 +31,7 @@ pub fn main() {
             pass.draw_line(
                 &Vector2::new(x_center + r_0 * cos, y_center + r_0 * sin),
                 &Vector2::new(x_center + r_1 * cos, y_center + r_1 * sin),
-                &Color::new_rand(),
+                &Color::random(&mut rng),
             );
         }
 
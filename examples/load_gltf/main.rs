@@ -1,5 +1,5 @@
 use fltk::prelude::{GroupExt, WidgetBase, WidgetExt};
-use nalgebra::{Matrix4, Point3, Vector2, Vector3};
+use nalgebra::{Matrix3, Matrix4, Point3, Vector2, Vector3};
 use tinyrenderer::{
     common::{basetype::Viewport, color::Color},
     interpolate::Interpolate,
@@ -9,52 +9,68 @@ use tinyrenderer::{
         shader::{FsPayload, Shader, VsOutput},
         texture::{Data, EdgeBehavior, SamplingMethod, Texture},
     },
+    scene::{
+        self,
+        camera::{Projection, View},
+        light::{Lighting, PunctualLight},
+        mesh::Primitive,
+    },
 };
 
-mod loader;
-
 #[derive(Debug, Interpolate)]
 struct Varying {
+    pub position: Vector3<f32>,
+
     pub normal: Vector3<f32>,
 
     pub tex_coord: Vector2<f32>,
 }
 
 struct Program<'a> {
-    primitive: &'a loader::Primitive,
+    primitive: &'a Primitive,
 
     texture: Option<&'a Texture<Color>>,
 
-    matrix: &'a Matrix4<f32>,
+    model: Matrix4<f32>,
+
+    normal_matrix: Matrix3<f32>,
+
+    view_projection: &'a Matrix4<f32>,
 
-    light_dir: Vector3<f32>,
+    lighting: &'a Lighting,
 }
 
 impl<'a> Shader for Program<'a> {
     type Varying = Varying;
 
     fn vertex_shader(&self, index: usize) -> VsOutput<Self::Varying> {
-        let vertex = &self.primitive.vertices[index];
-        let position = self.matrix * &vertex.position;
+        let vertex = self.primitive.vertex(index);
+        let position = self.model * vertex.position;
         VsOutput {
-            position,
+            position: self.view_projection * position,
             varying: Self::Varying {
-                normal: vertex.normal.unwrap(),
-                tex_coord: vertex.tex_coord.unwrap(),
+                position: position.xyz(),
+                normal: self.normal_matrix * vertex.normal.unwrap_or_else(Vector3::zeros),
+                tex_coord: vertex.tex_coord.unwrap_or_else(Vector2::zeros),
             },
         }
     }
 
     fn fragment_shader(&self, payload: FsPayload<Self::Varying>) -> Color {
         let normal = payload.varying.normal.normalize();
-        let intensity = normal.dot(&self.light_dir).max(0.0);
+        let irradiance = self.lighting.irradiance(&payload.varying.position, &normal);
         let tex_coord = payload.varying.tex_coord;
-        if let Some(texture) = self.texture {
-            let color = texture.sample(&tex_coord, SamplingMethod::Bilinear, EdgeBehavior::Clamp);
-            color
+        let color = if let Some(texture) = self.texture {
+            texture.sample(&tex_coord, SamplingMethod::Bilinear, EdgeBehavior::Clamp)
         } else {
             Color::WHITE
-        }
+        };
+        Color::new(
+            color.r * irradiance.x,
+            color.g * irradiance.y,
+            color.b * irradiance.z,
+            color.a,
+        )
     }
 }
 
@@ -68,35 +84,64 @@ pub fn main() {
     let viewport = Viewport::new(WIN_WIDTH, WIN_HEIGHT);
     let mut pass = RenderPass::new(viewport);
 
-    let (document, buffers, images) = gltf::import("models/Avocado/glTF/Avocado.gltf").unwrap();
-    let texture_map = loader::load_textures(&document, &images);
-    let meshes = loader::load_meshs(&document, &buffers);
+    let mut scene = scene::gltf::load("models/Avocado/glTF/Avocado.gltf").unwrap();
+    // glTF asks for flat normals when a primitive has none.
+    for primitive in scene
+        .meshes
+        .iter_mut()
+        .flat_map(|mesh| &mut mesh.primitives)
+    {
+        if primitive
+            .vertices
+            .iter()
+            .any(|vertex| vertex.normal.is_none())
+        {
+            primitive.generate_flat_normals();
+        }
+    }
 
-    let perspective = Matrix4::new_perspective(1.0, 10.0_f32.to_radians(), 0.1, 1000.0);
-    let look_at = Matrix4::look_at_rh(
-        &Point3::new(-1.0, 0.0, -1.0),
-        &Point3::new(0.0, 0.0, 0.0),
-        &Vector3::new(0.0, 1.0, 0.0),
+    let projection = Projection::Perspective {
+        yfov: 10.0_f32.to_radians(),
+        aspect_ratio: None,
+        znear: 0.1,
+        zfar: Some(1000.0),
+    };
+    let view = View::look_at(
+        Point3::new(1.0, 0.0, 1.0),
+        Point3::origin(),
+        Vector3::y(),
+        &projection,
+        WIN_WIDTH as f32 / WIN_HEIGHT as f32,
     );
-    let matrix = perspective * look_at;
+    let matrix = view.view_projection();
+
+    // The asset's own lights, else one shining from the camera.
+    let mut lighting = scene.lighting(None, Vector3::repeat(0.1));
+    if lighting.lights.is_empty() {
+        lighting.lights.push(PunctualLight::directional(
+            Vector3::new(-1.0, 0.0, -1.0),
+            Vector3::repeat(0.9),
+        ));
+    }
 
     win.draw(move |_| {
         pass.clear();
 
-        println!("meshes: {:?}", meshes.len());
-        for mesh in &meshes {
+        println!("meshes: {:?}", scene.meshes.len());
+        for instance in scene.instances(None) {
+            let mesh = &scene.meshes[instance.mesh];
             println!("primitives: {:?}", mesh.primitives.len());
             for primitive in &mesh.primitives {
                 println!("vertices: {:?}", primitive.vertices.len());
-                let texture = primitive
-                    .texture_index
-                    .map(|index| texture_map.get(&index).unwrap());
+                let texture = primitive.texture_index.map(|index| &scene.textures[index]);
 
                 let program = Program {
                     primitive: &primitive,
                     texture,
-                    matrix: &matrix.clone(),
-                    light_dir: Vector3::new(0.0, 0.0, -1.0),
+                    model: instance.world,
+                    normal_matrix: instance.normal_matrix(),
+                    view_projection: &matrix,
+                    lighting: &lighting,
                 };
 
                 let mut pipeline = Pipeline {
@@ -106,7 +151,7 @@ pub fn main() {
                     depth_compare: DepthCompare::Less,
                 };
 
-                pass.draw::<Varying>(&mut pipeline, primitive.vertices.len());
+                pass.draw::<Varying>(&mut pipeline, primitive.vertex_count());
             }
         }
 
@@ -1,8 +1,11 @@
 use fltk::prelude::{GroupExt, WidgetBase, WidgetExt};
 use nalgebra::Vector2;
-use rand::Rng;
 use tinyrenderer::{
-    common::{basetype::Viewport, color::Color},
+    common::{
+        basetype::{Bbox2, Viewport},
+        color::Color,
+        random,
+    },
     rasterizer::{pass::RenderPass, texture::Data, triangle::travel_triangle_sweep_line},
 };
 
@@ -19,7 +22,7 @@ pub fn main() {
     win.draw(move |_| {
         pass.clear();
 
-        let mut rng = rand::thread_rng();
+        let mut rng = random::seeded_rng(random::DEFAULT_SEED);
 
         for i in 0..16 {
             let row = i / 4;
@@ -38,19 +41,9 @@ pub fn main() {
                 &Vector2::new(x_max as f32, y_max as f32),
                 &Color::WHITE,
             );
-            let p_0 = Vector2::new(
-                rng.gen_range(x_min..x_max) as f32,
-                rng.gen_range(y_min..y_max) as f32,
-            );
-            let p_1 = Vector2::new(
-                rng.gen_range(x_min..x_max) as f32,
-                rng.gen_range(y_min..y_max) as f32,
-            );
-            let p_2 = Vector2::new(
-                rng.gen_range(x_min..x_max) as f32,
-                rng.gen_range(y_min..y_max) as f32,
-            );
-            let color = Color::new_rand();
+            let cell = Bbox2::new(x_min as f32, x_max as f32, y_min as f32, y_max as f32);
+            let [p_0, p_1, p_2] = random::triangle_in_bbox2(&mut rng, &cell);
+            let color = Color::random(&mut rng);
             let draw = |p: Vector2<i32>| {
                 pass.draw_pixel(&p, &color);
             };
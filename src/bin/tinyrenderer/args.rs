@@ -0,0 +1,207 @@
+use nalgebra::{Point3, Vector3};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: tinyrenderer <INPUT> <OUTPUT> [OPTIONS]
+
+Render a glTF/GLB, OBJ, STL or PLY file to an image (.png, .tga, .ppm or .pam).
+
+Options:
+    --width <N>          Image width in pixels [default: 512]
+    --height <N>         Image height in pixels [default: 512]
+    --scene <INDEX>      Scene of the asset to render [default: the default scene]
+    --camera <CAMERA>    Render through a camera of the asset, by index or name
+    --eye <X,Y,Z>        Camera position, frames the whole model when omitted
+    --target <X,Y,Z>     Camera target [default: center of the model]
+    --up <X,Y,Z>         Camera up vector [default: 0,1,0]
+    --fov <DEGREES>      Vertical field of view [default: 45]
+    --light <X,Y,Z>      Direction towards the light [default: the asset's lights,
+                         else towards the camera]
+    --shading <MODE>     One of lambert, pbr, unlit, normal [default: lambert]
+    --depth <PATH>       Also write the normalized depth buffer to PATH
+    -h, --help           Print this help
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shading {
+    Lambert,
+    Pbr,
+    Unlit,
+    Normal,
+}
+
+#[derive(Debug)]
+pub struct Args {
+    pub input: PathBuf,
+
+    pub output: PathBuf,
+
+    pub width: u32,
+
+    pub height: u32,
+
+    pub scene: Option<usize>,
+
+    pub camera: Option<String>,
+
+    pub eye: Option<Point3<f32>>,
+
+    pub target: Option<Point3<f32>>,
+
+    pub up: Vector3<f32>,
+
+    pub fov: f32,
+
+    pub light: Option<Vector3<f32>>,
+
+    pub shading: Shading,
+
+    pub depth: Option<PathBuf>,
+}
+
+/**
+ * Parse the command line arguments, without the program name.
+ * `Ok(None)` means help was requested.
+ */
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Args>, String> {
+    let mut positionals: Vec<String> = Vec::new();
+    let mut width = 512;
+    let mut height = 512;
+    let mut scene = None;
+    let mut camera = None;
+    let mut eye = None;
+    let mut target = None;
+    let mut up = Vector3::new(0.0, 1.0, 0.0);
+    let mut fov = 45.0;
+    let mut light = None;
+    let mut shading = Shading::Lambert;
+    let mut depth = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            positionals.push(arg);
+            continue;
+        }
+
+        if arg == "-h" || arg == "--help" {
+            return Ok(None);
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for `{}`", arg))?;
+
+        match arg.as_str() {
+            "--width" => width = parse_size(&arg, &value)?,
+            "--height" => height = parse_size(&arg, &value)?,
+            "--scene" => {
+                scene = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid scene index `{}`", value))?,
+                )
+            }
+            "--camera" => camera = Some(value),
+            "--eye" => eye = Some(Point3::from(parse_vector3(&arg, &value)?)),
+            "--target" => target = Some(Point3::from(parse_vector3(&arg, &value)?)),
+            "--up" => up = parse_vector3(&arg, &value)?,
+            "--fov" => fov = parse_f32(&arg, &value)?,
+            "--light" => light = Some(parse_vector3(&arg, &value)?),
+            "--shading" => {
+                shading = match value.as_str() {
+                    "lambert" => Shading::Lambert,
+                    "pbr" => Shading::Pbr,
+                    "unlit" => Shading::Unlit,
+                    "normal" => Shading::Normal,
+                    _ => return Err(format!("unknown shading mode `{}`", value)),
+                }
+            }
+            "--depth" => depth = Some(PathBuf::from(value)),
+            _ => return Err(format!("unknown option `{}`", arg)),
+        }
+    }
+
+    let [input, output]: [String; 2] = positionals
+        .try_into()
+        .map_err(|_| String::from("expected exactly an input and an output path"))?;
+
+    if camera.is_some() && (eye.is_some() || target.is_some()) {
+        return Err(String::from(
+            "`--camera` can't be combined with `--eye` or `--target`",
+        ));
+    }
+
+    if !(fov > 0.0 && fov < 180.0) {
+        return Err(format!("`--fov` must be in (0, 180), got {}", fov));
+    }
+
+    Ok(Some(Args {
+        input: PathBuf::from(input),
+        output: PathBuf::from(output),
+        width,
+        height,
+        scene,
+        camera,
+        eye,
+        target,
+        up,
+        fov,
+        light,
+        shading,
+        depth,
+    }))
+}
+
+fn parse_f32(name: &str, value: &str) -> Result<f32, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid number `{}` for `{}`", value, name))
+}
+
+fn parse_size(name: &str, value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(format!("invalid size `{}` for `{}`", value, name)),
+    }
+}
+
+fn parse_vector3(name: &str, value: &str) -> Result<Vector3<f32>, String> {
+    let components = value
+        .split(',')
+        .map(|v| parse_f32(name, v))
+        .collect::<Result<Vec<f32>, String>>()?;
+    match components[..] {
+        [x, y, z] => Ok(Vector3::new(x, y, z)),
+        _ => Err(format!("expected `X,Y,Z` for `{}`, got `{}`", name, value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Result<Option<Args>, String> {
+        parse(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = args("duck.glb duck.png --width 64 --scene 1 --eye 1,2,3 --shading normal")
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed.input, PathBuf::from("duck.glb"));
+        assert_eq!(parsed.output, PathBuf::from("duck.png"));
+        assert_eq!((parsed.width, parsed.height), (64, 512));
+        assert_eq!(parsed.scene, Some(1));
+        assert_eq!(parsed.eye, Some(Point3::new(1.0, 2.0, 3.0)));
+        assert_eq!(parsed.shading, Shading::Normal);
+
+        assert!(args("duck.glb --help").unwrap().is_none());
+        assert!(args("duck.glb").is_err());
+        assert!(args("duck.glb duck.png --eye 1,2").is_err());
+        assert!(args("duck.glb duck.png --width 0").is_err());
+        assert!(args("duck.glb duck.png --camera 0 --eye 1,2,3").is_err());
+    }
+}
@@ -0,0 +1,364 @@
+use nalgebra::{Matrix3, Matrix4, Point3, Vector2, Vector3, Vector4};
+use std::{f32::consts::PI, path::Path, process::ExitCode};
+use tinyrenderer::{
+    common::{
+        basetype::{Bbox3, Viewport},
+        color::Color,
+    },
+    interpolate::Interpolate,
+    rasterizer::{
+        pass::RenderPass,
+        shader::{FsPayload, Shader, VsOutput},
+        texture::{EdgeBehavior, SamplingMethod, Texture},
+    },
+    scene::{
+        self,
+        camera::{Projection, View},
+        frustum::Frustum,
+        light::{Lighting, PunctualLight},
+        material::{Material, TextureInfo},
+        mesh::Primitive,
+        pbr::PbrProgram,
+        render::draw_scene_culled,
+        Scene,
+    },
+};
+
+mod args;
+
+use args::{Args, Shading};
+
+const AMBIENT: f32 = 0.1;
+
+#[derive(Debug, Interpolate)]
+struct Varying {
+    pub position: Vector3<f32>,
+
+    pub normal: Vector3<f32>,
+
+    pub color: Vector4<f32>,
+
+    /**
+     * Of the set the base color texture uses.
+     */
+    pub tex_coord: Vector2<f32>,
+}
+
+struct Program<'a> {
+    primitive: &'a Primitive,
+
+    /**
+     * The base color texture of the material, into `textures`.
+     */
+    base_color_texture: Option<TextureInfo>,
+
+    textures: &'a [Texture<Color>],
+
+    model: Matrix4<f32>,
+
+    normal_matrix: Matrix3<f32>,
+
+    view_projection: &'a Matrix4<f32>,
+
+    lighting: &'a Lighting,
+
+    shading: Shading,
+}
+
+impl<'a> Shader for Program<'a> {
+    type Varying = Varying;
+
+    fn vertex_shader(&self, index: usize) -> VsOutput<Self::Varying> {
+        let vertex = self.primitive.vertex(index);
+        let color = vertex.color.unwrap_or(Color::WHITE);
+        let position = self.model * vertex.position;
+        let tex_coord = self
+            .base_color_texture
+            .and_then(|info| vertex.tex_coord_set(info.tex_coord));
+        VsOutput {
+            position: self.view_projection * position,
+            varying: Self::Varying {
+                position: position.xyz(),
+                normal: self.normal_matrix * vertex.normal.unwrap_or_else(Vector3::zeros),
+                color: Vector4::new(color.r, color.g, color.b, color.a),
+                tex_coord: tex_coord.unwrap_or_else(Vector2::zeros),
+            },
+        }
+    }
+
+    fn fragment_shader(&self, payload: FsPayload<Self::Varying>) -> Color {
+        let varying = payload.varying;
+        let normal = varying.normal.try_normalize(f32::EPSILON);
+
+        if self.shading == Shading::Normal {
+            let n = normal.unwrap_or_else(Vector3::zeros) * 0.5 + Vector3::repeat(0.5);
+            return Color::new(n.x, n.y, n.z, 1.0);
+        }
+
+        let c = varying.color;
+        let mut color = Color::new(c.x, c.y, c.z, c.w);
+        let texture = self
+            .base_color_texture
+            .and_then(|info| Some((info, self.textures.get(info.texture)?)));
+        if let Some((info, texture)) = texture {
+            let texel = texture.sample(
+                &info.map(&varying.tex_coord),
+                SamplingMethod::Bilinear,
+                EdgeBehavior::Wrap,
+            );
+            color = Color::new(
+                color.r * texel.r,
+                color.g * texel.g,
+                color.b * texel.b,
+                color.a * texel.a,
+            );
+        }
+
+        match (self.shading, normal) {
+            (Shading::Lambert, Some(normal)) => {
+                let irradiance = self.lighting.irradiance(&varying.position, &normal);
+                Color::new(
+                    color.r * irradiance.x,
+                    color.g * irradiance.y,
+                    color.b * irradiance.z,
+                    color.a,
+                )
+            }
+            _ => color,
+        }
+    }
+}
+
+/**
+ * Look from `--eye` at `--target`, framing the whole model for whichever is omitted.
+ */
+fn compute_view(args: &Args, bounds: &Bbox3<f32>) -> View {
+    let aspect = args.width as f32 / args.height as f32;
+    let fovy = args.fov.to_radians();
+    let direction = Vector3::new(1.0, 1.0, 1.0);
+    if args.eye.is_none() && args.target.is_none() {
+        return View::framing(bounds, &direction, fovy, aspect);
+    }
+
+    let center = Point3::new(
+        (bounds.l + bounds.r) / 2.0,
+        (bounds.b + bounds.t) / 2.0,
+        (bounds.n + bounds.f) / 2.0,
+    );
+    let radius = (Vector3::new(
+        bounds.r - bounds.l,
+        bounds.t - bounds.b,
+        bounds.f - bounds.n,
+    )
+    .norm()
+        / 2.0)
+        .max(f32::EPSILON);
+
+    let target = args.target.unwrap_or(center);
+    let eye = args.eye.unwrap_or_else(|| {
+        // Fit the bounding sphere into the narrower of the two field of views.
+        let fovx = 2.0 * ((fovy / 2.0).tan() * aspect).atan();
+        let distance = radius / (fovy.min(fovx) / 2.0).sin();
+        target + direction.normalize() * distance
+    });
+
+    let distance = (eye - center).norm();
+    let far = distance + radius;
+    let near = (distance - radius).max(far * 0.001);
+
+    let projection = Projection::Perspective {
+        yfov: fovy,
+        aspect_ratio: None,
+        znear: near,
+        zfar: Some(far),
+    };
+    View::look_at(eye, target, args.up, &projection, aspect)
+}
+
+/**
+ * Load `path` with the loader matching its extension, glTF being the default.
+ * Primitives without normals get flat ones for glTF, as its specification requires,
+ * and smooth ones for the other formats, which mostly hold scanned or CAD surfaces.
+ */
+fn load_scene(path: &Path) -> Result<Scene, String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let (scene, smooth) = match extension.as_deref() {
+        Some("obj") => (scene::obj::load(path).map_err(|err| err.to_string()), true),
+        Some("stl") => (
+            scene::stl::load(path)
+                .map(Scene::from_mesh)
+                .map_err(|err| err.to_string()),
+            true,
+        ),
+        Some("ply") => (
+            scene::ply::load(path)
+                .map(Scene::from_mesh)
+                .map_err(|err| err.to_string()),
+            true,
+        ),
+        _ => (
+            scene::gltf::load(path).map_err(|err| err.to_string()),
+            false,
+        ),
+    };
+    let mut scene = scene?;
+    for primitive in scene
+        .meshes
+        .iter_mut()
+        .flat_map(|mesh| &mut mesh.primitives)
+    {
+        if primitive
+            .vertices
+            .iter()
+            .any(|vertex| vertex.normal.is_none())
+        {
+            if smooth {
+                primitive.generate_smooth_normals();
+            } else {
+                primitive.generate_flat_normals();
+            }
+        }
+    }
+    Ok(scene)
+}
+
+fn render(args: &Args) -> Result<(), String> {
+    let scene = load_scene(&args.input)
+        .map_err(|err| format!("failed to load {}: {}", args.input.display(), err))?;
+
+    if let Some(index) = args.scene {
+        if index >= scene.graphs.len() {
+            return Err(format!(
+                "scene {} out of range, {} has {} scenes",
+                index,
+                args.input.display(),
+                scene.graphs.len()
+            ));
+        }
+    }
+
+    let bounds = scene
+        .bounds(args.scene)
+        .ok_or_else(|| format!("no triangles to render in {}", args.input.display()))?;
+    let view = match &args.camera {
+        Some(name) => {
+            let index = name
+                .parse()
+                .ok()
+                .or_else(|| scene.find_camera(name))
+                .ok_or_else(|| format!("no camera `{}` in {}", name, args.input.display()))?;
+            scene
+                .view(
+                    Some(index),
+                    args.scene,
+                    args.width as f32 / args.height as f32,
+                )
+                .ok_or_else(|| format!("camera `{}` is not placed in the scene", name))?
+        }
+        None => compute_view(args, &bounds),
+    };
+    let view_projection = view.view_projection();
+    // The lights of the asset, else a light shining from the camera along its -Z axis.
+    let mut lighting = scene.lighting(args.scene, Vector3::repeat(AMBIENT));
+    if args.light.is_some() || lighting.lights.is_empty() {
+        let towards_light = args
+            .light
+            .unwrap_or_else(|| view.view.fixed_view::<1, 3>(2, 0).transpose());
+        // Lambertian BRDFs divide by π, scale so that head-on white stays white.
+        let scale = if args.shading == Shading::Pbr {
+            PI
+        } else {
+            1.0
+        };
+        lighting.lights = vec![PunctualLight::directional(
+            -towards_light,
+            Vector3::repeat((1.0 - AMBIENT) * scale),
+        )];
+    }
+
+    let mut pass = RenderPass::new(Viewport::new(args.width, args.height));
+    pass.clear();
+
+    let default_material = Material::default();
+    let frustum = Frustum::from_matrix(&view_projection);
+    if args.shading == Shading::Pbr {
+        draw_scene_culled(
+            &mut pass,
+            &scene,
+            args.scene,
+            view.reversed_z,
+            &frustum,
+            |instance, primitive| PbrProgram {
+                primitive,
+                material: primitive
+                    .material
+                    .and_then(|index| scene.materials.get(index))
+                    .unwrap_or(&default_material),
+                textures: &scene.textures,
+                model: instance.world,
+                normal_matrix: instance.normal_matrix(),
+                view_projection,
+                eye: view.eye.coords,
+                lighting: &lighting,
+            },
+        );
+    } else {
+        draw_scene_culled(
+            &mut pass,
+            &scene,
+            args.scene,
+            view.reversed_z,
+            &frustum,
+            |instance, primitive| Program {
+                primitive,
+                base_color_texture: primitive
+                    .material
+                    .and_then(|index| scene.materials.get(index))
+                    .and_then(|material| material.base_color_texture),
+                textures: &scene.textures,
+                model: instance.world,
+                normal_matrix: instance.normal_matrix(),
+                view_projection: &view_projection,
+                lighting: &lighting,
+                shading: args.shading,
+            },
+        );
+    }
+
+    pass.frame_texture
+        .save(&args.output)
+        .map_err(|err| format!("failed to write {}: {}", args.output.display(), err))?;
+
+    if let Some(path) = &args.depth {
+        pass.depth_texture
+            .save(path)
+            .map_err(|err| format!("failed to write {}: {}", path.display(), err))?;
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{}", args::USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, args::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match render(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
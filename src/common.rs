@@ -1,2 +1,3 @@
 pub mod basetype;
 pub mod color;
+pub mod random;
@@ -130,4 +130,45 @@ impl<T: Float> Bbox3<T> {
             None
         }
     }
+
+    /**
+     * The smallest box enclosing both boxes.
+     */
+    pub fn union(&self, other: &Bbox3<T>) -> Bbox3<T> {
+        Bbox3 {
+            l: self.l.min(other.l),
+            r: self.r.max(other.r),
+            b: self.b.min(other.b),
+            t: self.t.max(other.t),
+            n: self.n.min(other.n),
+            f: self.f.max(other.f),
+        }
+    }
+
+    pub fn center(&self) -> Vector3<T> {
+        let two = T::one() + T::one();
+        Vector3::new(
+            (self.l + self.r) / two,
+            (self.b + self.t) / two,
+            (self.n + self.f) / two,
+        )
+    }
+
+    pub fn surface_area(&self) -> T {
+        let (x, y, z) = (self.r - self.l, self.t - self.b, self.f - self.n);
+        (x * y + y * z + z * x) * (T::one() + T::one())
+    }
+
+    /**
+     * The eight corners, with x varying fastest, then y, then z.
+     */
+    pub fn corners(&self) -> [Vector3<T>; 8] {
+        std::array::from_fn(|i| {
+            Vector3::new(
+                if i & 1 == 0 { self.l } else { self.r },
+                if i & 2 == 0 { self.b } else { self.t },
+                if i & 4 == 0 { self.n } else { self.f },
+            )
+        })
+    }
 }
@@ -29,8 +29,11 @@ impl Color {
         Self { r, g, b, a }
     }
 
-    pub fn new_rand() -> Self {
-        let mut rng = rand::thread_rng();
+    /**
+     * An opaque color with random channels, drawn from `rng`.
+     * Use a seeded generator (see `common::random`) for reproducible output.
+     */
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
         Self {
             r: rng.gen_range(0.0..1.0),
             g: rng.gen_range(0.0..1.0),
@@ -71,3 +74,38 @@ impl Add for Color {
         }
     }
 }
+
+/**
+ * Decode an sRGB encoded channel to linear.
+ */
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/**
+ * Encode a linear channel to sRGB.
+ */
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srgb_round_trip() {
+        for c in [0.0, 0.002, 0.2, 0.5, 1.0] {
+            assert!((linear_to_srgb(srgb_to_linear(c)) - c).abs() < 1e-5);
+        }
+        assert!((srgb_to_linear(0.5) - 0.214).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::common::basetype::{Bbox2, Bbox3};
+use nalgebra::{Vector2, Vector3};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+pub type SeededRng = ChaCha8Rng;
+
+/**
+ * The seed used by the demo scenes.
+ */
+pub const DEFAULT_SEED: u64 = 0x7153_0f7e;
+
+/**
+ * A random number generator producing the same sequence for the same seed on every platform.
+ */
+pub fn seeded_rng(seed: u64) -> SeededRng {
+    ChaCha8Rng::seed_from_u64(seed)
+}
+
+fn gen_between<R: Rng + ?Sized>(rng: &mut R, min: f32, max: f32) -> f32 {
+    if min < max {
+        rng.gen_range(min..max)
+    } else {
+        min
+    }
+}
+
+pub fn point_in_bbox2<R: Rng + ?Sized>(rng: &mut R, bbox: &Bbox2<f32>) -> Vector2<f32> {
+    Vector2::new(
+        gen_between(rng, bbox.l, bbox.r),
+        gen_between(rng, bbox.b, bbox.t),
+    )
+}
+
+pub fn point_in_bbox3<R: Rng + ?Sized>(rng: &mut R, bbox: &Bbox3<f32>) -> Vector3<f32> {
+    Vector3::new(
+        gen_between(rng, bbox.l, bbox.r),
+        gen_between(rng, bbox.b, bbox.t),
+        gen_between(rng, bbox.n, bbox.f),
+    )
+}
+
+/**
+ * Three independent random points within `bbox`.
+ */
+pub fn triangle_in_bbox2<R: Rng + ?Sized>(rng: &mut R, bbox: &Bbox2<f32>) -> [Vector2<f32>; 3] {
+    [
+        point_in_bbox2(rng, bbox),
+        point_in_bbox2(rng, bbox),
+        point_in_bbox2(rng, bbox),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_triangles() {
+        let bbox = Bbox2::new(10.0, 20.0, -5.0, 5.0);
+        let a = triangle_in_bbox2(&mut seeded_rng(DEFAULT_SEED), &bbox);
+        let b = triangle_in_bbox2(&mut seeded_rng(DEFAULT_SEED), &bbox);
+        assert_eq!(a, b);
+        for p in a {
+            assert!(p.x >= 10.0 && p.x < 20.0 && p.y >= -5.0 && p.y < 5.0);
+        }
+
+        let flat = Bbox3::new(0.0, 1.0, 2.0, 2.0, 0.0, 1.0);
+        assert_eq!(point_in_bbox3(&mut seeded_rng(1), &flat).y, 2.0);
+    }
+}
@@ -1,4 +1,6 @@
 pub mod common;
+pub mod metrics;
 pub mod rasterizer;
+pub mod scene;
 
 pub use interpolate;
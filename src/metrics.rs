@@ -0,0 +1,515 @@
+use crate::{
+    common::color::{srgb_to_linear, Color},
+    rasterizer::texture::{EdgeBehavior, SamplingMethod, Texture},
+};
+use nalgebra::{Vector2, Vector3};
+
+/**
+ * Per-channel statistic of the difference between two images.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelError {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    pub a: f64,
+}
+
+impl ChannelError {
+    /**
+     * Mean over the color channels, alpha is ignored.
+     */
+    pub fn rgb_mean(&self) -> f64 {
+        (self.r + self.g + self.b) / 3.0
+    }
+}
+
+fn assert_same_size<T, U>(a: &Texture<T>, b: &Texture<U>) {
+    assert!(
+        a.width == b.width && a.height == b.height,
+        "image size mismatch: {}x{} and {}x{}",
+        a.width,
+        a.height,
+        b.width,
+        b.height
+    );
+}
+
+fn map_texels<F: Fn(&Color, &Color) -> Color>(
+    a: &Texture<Color>,
+    b: &Texture<Color>,
+    f: F,
+) -> Texture<Color> {
+    assert_same_size(a, b);
+    let mut texture = Texture::new(a.width, a.height);
+    for (texel, (a, b)) in texture.data.iter_mut().zip(a.data.iter().zip(&b.data)) {
+        *texel = f(a, b);
+    }
+    texture
+}
+
+fn mean_per_channel(texture: &Texture<Color>) -> ChannelError {
+    let n = texture.data.len() as f64;
+    let mut sum = ChannelError {
+        r: 0.0,
+        g: 0.0,
+        b: 0.0,
+        a: 0.0,
+    };
+    for c in &texture.data {
+        sum.r += c.r as f64;
+        sum.g += c.g as f64;
+        sum.b += c.b as f64;
+        sum.a += c.a as f64;
+    }
+    ChannelError {
+        r: sum.r / n,
+        g: sum.g / n,
+        b: sum.b / n,
+        a: sum.a / n,
+    }
+}
+
+/**
+ * Per-texel, per-channel `|a - b|`.
+ */
+pub fn absolute_error(a: &Texture<Color>, b: &Texture<Color>) -> Texture<Color> {
+    map_texels(a, b, |a, b| Color {
+        r: (a.r - b.r).abs(),
+        g: (a.g - b.g).abs(),
+        b: (a.b - b.b).abs(),
+        a: (a.a - b.a).abs(),
+    })
+}
+
+/**
+ * Per-texel, per-channel `(a - b)^2`.
+ */
+pub fn squared_error(a: &Texture<Color>, b: &Texture<Color>) -> Texture<Color> {
+    map_texels(a, b, |a, b| Color {
+        r: (a.r - b.r).powi(2),
+        g: (a.g - b.g).powi(2),
+        b: (a.b - b.b).powi(2),
+        a: (a.a - b.a).powi(2),
+    })
+}
+
+pub fn mean_absolute_error(a: &Texture<Color>, b: &Texture<Color>) -> ChannelError {
+    mean_per_channel(&absolute_error(a, b))
+}
+
+pub fn mean_squared_error(a: &Texture<Color>, b: &Texture<Color>) -> ChannelError {
+    mean_per_channel(&squared_error(a, b))
+}
+
+/**
+ * Mean squared error over the color channels, with values in [0, 1].
+ */
+pub fn mse(a: &Texture<Color>, b: &Texture<Color>) -> f64 {
+    mean_squared_error(a, b).rgb_mean()
+}
+
+/**
+ * Peak signal-to-noise ratio in dB, infinite for identical images.
+ */
+pub fn psnr(a: &Texture<Color>, b: &Texture<Color>) -> f64 {
+    let mse = mse(a, b);
+    if mse == 0.0 {
+        f64::INFINITY
+    } else {
+        -10.0 * mse.log10()
+    }
+}
+
+/**
+ * Count the texels where any color channel differs by more than `threshold`.
+ */
+pub fn count_over_threshold(a: &Texture<Color>, b: &Texture<Color>, threshold: f32) -> usize {
+    absolute_error(a, b)
+        .data
+        .iter()
+        .filter(|e| e.r.max(e.g).max(e.b) > threshold)
+        .count()
+}
+
+fn luminance(color: &Color) -> f32 {
+    0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b
+}
+
+/**
+ * Starts of the windows along an axis of `size` texels, the last one moved back to end
+ * on the edge so that every texel is covered.
+ */
+fn window_starts(size: u32, window: u32, stride: u32) -> Vec<u32> {
+    let last = size.saturating_sub(window);
+    let mut starts: Vec<u32> = (0..=last).step_by(stride as usize).collect();
+    if starts.last() != Some(&last) {
+        starts.push(last);
+    }
+    starts
+}
+
+/**
+ * Mean structural similarity of the luminance,
+ * computed over 8x8 windows with a stride of 4. Empty images are identical.
+ */
+pub fn ssim(a: &Texture<Color>, b: &Texture<Color>) -> f64 {
+    const WINDOW: u32 = 8;
+    const STRIDE: u32 = 4;
+    const C_1: f64 = 0.01 * 0.01;
+    const C_2: f64 = 0.03 * 0.03;
+
+    assert_same_size(a, b);
+    if a.data.is_empty() {
+        return 1.0;
+    }
+
+    let mut total = 0.0;
+    let mut count = 0;
+    for &y_0 in &window_starts(a.height, WINDOW, STRIDE) {
+        for &x_0 in &window_starts(a.width, WINDOW, STRIDE) {
+            let mut samples = Vec::new();
+            for y in y_0..(y_0 + WINDOW).min(a.height) {
+                for x in x_0..(x_0 + WINDOW).min(a.width) {
+                    let p = Vector2::new(x, y);
+                    samples.push((
+                        luminance(&a.get_texel(&p)) as f64,
+                        luminance(&b.get_texel(&p)) as f64,
+                    ));
+                }
+            }
+
+            let n = samples.len() as f64;
+            let mean_a = samples.iter().map(|s| s.0).sum::<f64>() / n;
+            let mean_b = samples.iter().map(|s| s.1).sum::<f64>() / n;
+            let mut var_a = 0.0;
+            let mut var_b = 0.0;
+            let mut cov = 0.0;
+            for (v_a, v_b) in &samples {
+                var_a += (v_a - mean_a).powi(2);
+                var_b += (v_b - mean_b).powi(2);
+                cov += (v_a - mean_a) * (v_b - mean_b);
+            }
+            var_a /= n;
+            var_b /= n;
+            cov /= n;
+
+            total += ((2.0 * mean_a * mean_b + C_1) * (2.0 * cov + C_2))
+                / ((mean_a * mean_a + mean_b * mean_b + C_1) * (var_a + var_b + C_2));
+            count += 1;
+        }
+    }
+    total / count as f64
+}
+
+/**
+ * sRGB color to CIE L*a*b* under the D65 white point.
+ */
+fn srgb_to_lab(color: &Color) -> Vector3<f32> {
+    let r = srgb_to_linear(color.r);
+    let g = srgb_to_linear(color.g);
+    let b = srgb_to_linear(color.b);
+
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.950_47;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.088_83;
+
+    let f = |t: f32| {
+        if t > 216.0 / 24389.0 {
+            t.cbrt()
+        } else {
+            (24389.0 / 27.0 * t + 16.0) / 116.0
+        }
+    };
+    let (f_x, f_y, f_z) = (f(x), f(y), f(z));
+
+    Vector3::new(116.0 * f_y - 16.0, 500.0 * (f_x - f_y), 200.0 * (f_y - f_z))
+}
+
+/**
+ * Hybrid distance of two L*a*b* colors, the color metric used by FLIP.
+ */
+fn hyab(a: &Vector3<f32>, b: &Vector3<f32>) -> f32 {
+    (a.x - b.x).abs() + ((a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
+
+fn gaussian_kernel(sigma: f32) -> Vec<f32> {
+    let radius = (3.0 * sigma).ceil().max(1.0) as i32;
+    let kernel: Vec<f32> = (-radius..=radius)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f32 = kernel.iter().sum();
+    kernel.into_iter().map(|k| k / sum).collect()
+}
+
+/**
+ * Separable Gaussian blur of one channel, clamping at the edges.
+ */
+fn blur(values: &[f32], width: u32, height: u32, sigma: f32) -> Vec<f32> {
+    let kernel = gaussian_kernel(sigma);
+    let radius = (kernel.len() / 2) as i32;
+    let (w, h) = (width as i32, height as i32);
+
+    let mut horizontal = vec![0.0; values.len()];
+    for y in 0..h {
+        for x in 0..w {
+            horizontal[(x + y * w) as usize] = kernel
+                .iter()
+                .enumerate()
+                .map(|(i, k)| {
+                    let sx = (x + i as i32 - radius).clamp(0, w - 1);
+                    k * values[(sx + y * w) as usize]
+                })
+                .sum();
+        }
+    }
+
+    let mut result = vec![0.0; values.len()];
+    for y in 0..h {
+        for x in 0..w {
+            result[(x + y * w) as usize] = kernel
+                .iter()
+                .enumerate()
+                .map(|(i, k)| {
+                    let sy = (y + i as i32 - radius).clamp(0, h - 1);
+                    k * horizontal[(x + sy * w) as usize]
+                })
+                .sum();
+        }
+    }
+    result
+}
+
+/**
+ * Sobel gradient magnitude, clamping at the edges.
+ */
+fn edges(values: &[f32], width: u32, height: u32) -> Vec<f32> {
+    let (w, h) = (width as i32, height as i32);
+    let at = |x: i32, y: i32| values[(x.clamp(0, w - 1) + y.clamp(0, h - 1) * w) as usize];
+
+    let mut result = vec![0.0; values.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let g_x = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                - at(x - 1, y - 1)
+                - 2.0 * at(x - 1, y)
+                - at(x - 1, y + 1);
+            let g_y = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                - at(x - 1, y - 1)
+                - 2.0 * at(x, y - 1)
+                - at(x + 1, y - 1);
+            result[(x + y * w) as usize] = (g_x * g_x + g_y * g_y).sqrt() / 4.0;
+        }
+    }
+    result
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FlipOptions {
+    /**
+     * Number of pixels per degree of visual angle for the assumed viewing setup.
+     */
+    pub pixels_per_degree: f32,
+}
+
+impl Default for FlipOptions {
+    fn default() -> Self {
+        // A 0.7 m wide 4K monitor seen from 0.7 m.
+        Self {
+            pixels_per_degree: 67.0,
+        }
+    }
+}
+
+/**
+ * A simplified FLIP-like perceptual difference map with values in [0, 1].
+ *
+ * Both images are converted to L*a*b* and blurred, achromatic channels less than
+ * chromatic ones, to mimic the contrast sensitivity of the eye.
+ * The HyAB distance of the filtered colors gives the color error,
+ * which is amplified where the luminance edges of the images differ.
+ */
+pub fn flip(a: &Texture<Color>, b: &Texture<Color>, options: FlipOptions) -> Texture<f32> {
+    assert_same_size(a, b);
+    let (width, height) = (a.width, a.height);
+
+    let filter = |texture: &Texture<Color>| {
+        let lab: Vec<Vector3<f32>> = texture.data.iter().map(srgb_to_lab).collect();
+        let channel = |i: usize| lab.iter().map(|v| v[i]).collect::<Vec<f32>>();
+        let sigma_l = (0.0125 * options.pixels_per_degree).max(0.5);
+        let sigma_ab = (0.04 * options.pixels_per_degree).max(0.5);
+        let l = blur(&channel(0), width, height, sigma_l);
+        let a = blur(&channel(1), width, height, sigma_ab);
+        let b = blur(&channel(2), width, height, sigma_ab);
+        let luma: Vec<f32> = lab.iter().map(|v| v.x / 100.0).collect();
+        let filtered: Vec<Vector3<f32>> = (0..lab.len())
+            .map(|i| Vector3::new(l[i], a[i], b[i]))
+            .collect();
+        (filtered, edges(&luma, width, height))
+    };
+
+    let (lab_a, edges_a) = filter(a);
+    let (lab_b, edges_b) = filter(b);
+
+    // The largest distance between the primaries normalizes the color error.
+    let max_distance = hyab(&srgb_to_lab(&Color::GREEN), &srgb_to_lab(&Color::BLUE));
+
+    let mut texture = Texture::new(width, height);
+    for (i, texel) in texture.data.iter_mut().enumerate() {
+        let color_error = (hyab(&lab_a[i], &lab_b[i]) / max_distance)
+            .min(1.0)
+            .powf(0.7);
+        let feature_error = (edges_a[i] - edges_b[i]).abs().min(1.0).sqrt();
+        *texel = color_error.powf(1.0 - feature_error);
+    }
+    texture
+}
+
+/**
+ * Mean of a FLIP-like difference map.
+ */
+pub fn mean_flip(a: &Texture<Color>, b: &Texture<Color>, options: FlipOptions) -> f64 {
+    let map = flip(a, b, options);
+    map.data.iter().map(|v| *v as f64).sum::<f64>() / map.data.len() as f64
+}
+
+/**
+ * Visualize an error map with values in [0, 1],
+ * from black through purple, red and yellow to white.
+ */
+pub fn heatmap(errors: &Texture<f32>) -> Texture<Color> {
+    const STOPS: [Color; 5] = [
+        Color::new_const(0.0, 0.0, 0.0, 1.0),
+        Color::new_const(0.4, 0.0, 0.6, 1.0),
+        Color::new_const(0.9, 0.1, 0.1, 1.0),
+        Color::new_const(1.0, 0.85, 0.0, 1.0),
+        Color::new_const(1.0, 1.0, 1.0, 1.0),
+    ];
+
+    let mut texture = Texture::new(errors.width, errors.height);
+    for (texel, error) in texture.data.iter_mut().zip(&errors.data) {
+        let t = error.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+        let i = (t.floor() as usize).min(STOPS.len() - 2);
+        let f = t - i as f32;
+        *texel = STOPS[i] * (1.0 - f) + STOPS[i + 1] * f;
+    }
+    texture
+}
+
+/**
+ * Per-texel largest color channel difference, suitable for `heatmap`.
+ */
+pub fn max_channel_error(a: &Texture<Color>, b: &Texture<Color>) -> Texture<f32> {
+    let errors = absolute_error(a, b);
+    let mut texture = Texture::new(a.width, a.height);
+    for (texel, e) in texture.data.iter_mut().zip(&errors.data) {
+        *texel = e.r.max(e.g).max(e.b);
+    }
+    texture
+}
+
+/**
+ * Bilinearly resample `texture` to a new size,
+ * e.g. to compare a render with a reference screenshot of another resolution.
+ */
+pub fn resample(texture: &Texture<Color>, width: u32, height: u32) -> Texture<Color> {
+    let mut result = Texture::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let uv = Vector2::new(
+                (x as f32 + 0.5) / width as f32,
+                (y as f32 + 0.5) / height as f32,
+            );
+            let color = texture.sample(&uv, SamplingMethod::Bilinear, EdgeBehavior::Clamp);
+            result.set_texel(&Vector2::new(x, y), color);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(color: Color) -> Texture<Color> {
+        let mut texture = Texture::new(16, 16);
+        texture.data.fill(color);
+        texture
+    }
+
+    #[test]
+    fn test_error_metrics() {
+        let a = solid(Color::new(0.5, 0.5, 0.5, 1.0));
+        let b = solid(Color::new(0.6, 0.5, 0.5, 1.0));
+
+        let mae = mean_absolute_error(&a, &b);
+        assert!((mae.r - 0.1).abs() < 1e-6);
+        assert_eq!(mae.g, 0.0);
+        assert!((mse(&a, &b) - 0.01 / 3.0).abs() < 1e-6);
+        assert!((psnr(&a, &b) - 10.0 * 300.0_f64.log10()).abs() < 1e-3);
+        assert_eq!(psnr(&a, &a), f64::INFINITY);
+        assert_eq!(count_over_threshold(&a, &b, 0.05), 256);
+        assert_eq!(count_over_threshold(&a, &b, 0.2), 0);
+    }
+
+    #[test]
+    fn test_ssim_and_flip() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/models/Duck/screenshot/screenshot.png"
+        );
+        let screenshot = Texture::<Color>::load(path).unwrap();
+
+        assert!((ssim(&screenshot, &screenshot) - 1.0).abs() < 1e-9);
+        assert_eq!(
+            mean_flip(&screenshot, &screenshot, FlipOptions::default()),
+            0.0
+        );
+
+        let blurred = resample(
+            &resample(&screenshot, 32, 32),
+            screenshot.width,
+            screenshot.height,
+        );
+        let inverted = map_texels(&screenshot, &screenshot, |c, _| Color {
+            r: 1.0 - c.r,
+            g: 1.0 - c.g,
+            b: 1.0 - c.b,
+            a: c.a,
+        });
+        let options = FlipOptions::default();
+        assert!(ssim(&screenshot, &blurred) > ssim(&screenshot, &inverted));
+        assert!(
+            mean_flip(&screenshot, &blurred, options) < mean_flip(&screenshot, &inverted, options)
+        );
+    }
+
+    #[test]
+    fn test_ssim_coverage() {
+        // Only the last two columns of a 10 pixel wide image differ.
+        let a = Texture::<Color>::new(10, 10);
+        let mut b = Texture::<Color>::new(10, 10);
+        for y in 0..10 {
+            for x in 8..10 {
+                b.set_texel(&Vector2::new(x, y), Color::WHITE);
+            }
+        }
+        assert!(ssim(&a, &b) < 0.9);
+
+        let empty = Texture::<Color>::new(0, 0);
+        assert_eq!(ssim(&empty, &empty), 1.0);
+        assert_eq!(window_starts(10, 8, 4), [0, 2]);
+        assert_eq!(window_starts(16, 8, 4), [0, 4, 8]);
+        assert_eq!(window_starts(5, 8, 4), [0]);
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let mut errors = Texture::new(3, 1);
+        errors.data = vec![0.0, 0.5, 1.0];
+        let map = heatmap(&errors);
+        assert_eq!(map.data[0].r, 0.0);
+        assert_eq!(map.data[1].r, 0.9);
+        assert_eq!(map.data[2].b, 1.0);
+    }
+}
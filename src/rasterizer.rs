@@ -1,3 +1,4 @@
+pub mod image;
 pub mod line;
 pub mod pass;
 pub mod pipeline;
@@ -0,0 +1,592 @@
+use crate::{common::color::Color, rasterizer::texture::Texture};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /**
+     * Binary PPM (P6), RGB only.
+     */
+    Ppm,
+    /**
+     * Netpbm PAM (P7) with an alpha channel.
+     */
+    Pam,
+    /**
+     * Uncompressed 32-bit true-color TGA.
+     */
+    Tga,
+    Png,
+}
+
+impl ImageFormat {
+    /**
+     * Guess the format from the file extension of `path`.
+     */
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            "pam" => Some(ImageFormat::Pam),
+            "tga" => Some(ImageFormat::Tga),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    PngEncode(png::EncodingError),
+    PngDecode(png::DecodingError),
+    Malformed(String),
+    UnknownFormat(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(err) => write!(f, "io error: {}", err),
+            ImageError::PngEncode(err) => write!(f, "png encoding error: {}", err),
+            ImageError::PngDecode(err) => write!(f, "png decoding error: {}", err),
+            ImageError::Malformed(reason) => write!(f, "malformed image: {}", reason),
+            ImageError::UnknownFormat(path) => write!(f, "unknown image format: {}", path),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<io::Error> for ImageError {
+    fn from(err: io::Error) -> Self {
+        ImageError::Io(err)
+    }
+}
+
+impl From<png::EncodingError> for ImageError {
+    fn from(err: png::EncodingError) -> Self {
+        ImageError::PngEncode(err)
+    }
+}
+
+impl From<png::DecodingError> for ImageError {
+    fn from(err: png::DecodingError) -> Self {
+        ImageError::PngDecode(err)
+    }
+}
+
+fn unknown_format(path: &Path) -> ImageError {
+    ImageError::UnknownFormat(path.display().to_string())
+}
+
+fn from_rgba(width: u32, height: u32, rgba: &[u8]) -> Texture<Color> {
+    let mut texture = Texture::new(width, height);
+    for (texel, pixel) in texture.data.iter_mut().zip(rgba.chunks_exact(4)) {
+        *texel = Color::new(
+            pixel[0] as f32 / 255.0,
+            pixel[1] as f32 / 255.0,
+            pixel[2] as f32 / 255.0,
+            pixel[3] as f32 / 255.0,
+        );
+    }
+    texture
+}
+
+/**
+ * Read the next whitespace separated token of a netpbm header, skipping comments.
+ */
+fn read_header_token<R: BufRead>(reader: &mut R) -> Result<String, ImageError> {
+    let mut token = String::new();
+    let mut byte = [0u8];
+    loop {
+        if reader.read(&mut byte)? == 0 {
+            break;
+        }
+        match byte[0] {
+            b'#' if token.is_empty() => {
+                reader.read_line(&mut String::new())?;
+            }
+            b if b.is_ascii_whitespace() => {
+                if !token.is_empty() {
+                    break;
+                }
+            }
+            b => token.push(b as char),
+        }
+    }
+    if token.is_empty() {
+        return Err(ImageError::Malformed(String::from(
+            "unexpected end of header",
+        )));
+    }
+    Ok(token)
+}
+
+fn read_header_number<R: BufRead>(reader: &mut R) -> Result<u32, ImageError> {
+    let token = read_header_token(reader)?;
+    token
+        .parse()
+        .map_err(|_| ImageError::Malformed(format!("invalid header value `{}`", token)))
+}
+
+/**
+ * Read the `width * height * channels` bytes of an image. The buffer grows as bytes
+ * arrive, so a header claiming a huge image fails on the missing data instead of
+ * allocating it up front.
+ */
+fn read_pixels<R: Read>(
+    reader: &mut R,
+    width: u32,
+    height: u32,
+    channels: usize,
+) -> Result<Vec<u8>, ImageError> {
+    let size = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|size| size.checked_mul(channels))
+        .ok_or_else(|| ImageError::Malformed(format!("image too large: {}x{}", width, height)))?;
+    let mut pixels = Vec::new();
+    reader.take(size as u64).read_to_end(&mut pixels)?;
+    if pixels.len() < size {
+        return Err(ImageError::Io(io::ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(pixels)
+}
+
+fn expand_to_rgba(pixels: &[u8], channels: usize) -> Vec<u8> {
+    pixels
+        .chunks_exact(channels)
+        .flat_map(|pixel| match channels {
+            1 => [pixel[0], pixel[0], pixel[0], 255],
+            2 => [pixel[0], pixel[0], pixel[0], pixel[1]],
+            3 => [pixel[0], pixel[1], pixel[2], 255],
+            _ => [pixel[0], pixel[1], pixel[2], pixel[3]],
+        })
+        .collect()
+}
+
+impl Texture<Color> {
+    /**
+     * Read a texture from `path`, the format is decided by the file extension.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        let path = path.as_ref();
+        let format = ImageFormat::from_path(path).ok_or_else(|| unknown_format(path))?;
+        Self::read_from(BufReader::new(File::open(path)?), format)
+    }
+
+    /**
+     * Decode a texture from `reader`.
+     * Only 8-bit images are supported for the netpbm and TGA formats.
+     */
+    pub fn read_from<R: BufRead>(mut reader: R, format: ImageFormat) -> Result<Self, ImageError> {
+        match format {
+            ImageFormat::Ppm | ImageFormat::Pam => {
+                let magic = read_header_token(&mut reader)?;
+                let (width, height, channels) = match (format, magic.as_str()) {
+                    (ImageFormat::Ppm, "P6") => {
+                        let width = read_header_number(&mut reader)?;
+                        let height = read_header_number(&mut reader)?;
+                        let maxval = read_header_number(&mut reader)?;
+                        if maxval != 255 {
+                            return Err(ImageError::Malformed(format!(
+                                "unsupported maxval {}",
+                                maxval
+                            )));
+                        }
+                        (width, height, 3)
+                    }
+                    (ImageFormat::Pam, "P7") => {
+                        let (mut width, mut height, mut depth) = (0, 0, 0);
+                        loop {
+                            match read_header_token(&mut reader)?.as_str() {
+                                "WIDTH" => width = read_header_number(&mut reader)?,
+                                "HEIGHT" => height = read_header_number(&mut reader)?,
+                                "DEPTH" => depth = read_header_number(&mut reader)?,
+                                "MAXVAL" => {
+                                    if read_header_number(&mut reader)? != 255 {
+                                        return Err(ImageError::Malformed(String::from(
+                                            "unsupported maxval",
+                                        )));
+                                    }
+                                }
+                                "TUPLTYPE" => {
+                                    read_header_token(&mut reader)?;
+                                }
+                                "ENDHDR" => break,
+                                token => {
+                                    return Err(ImageError::Malformed(format!(
+                                        "unexpected header token `{}`",
+                                        token
+                                    )))
+                                }
+                            }
+                        }
+                        if !(1..=4).contains(&depth) {
+                            return Err(ImageError::Malformed(format!(
+                                "unsupported depth {}",
+                                depth
+                            )));
+                        }
+                        (width, height, depth as usize)
+                    }
+                    _ => {
+                        return Err(ImageError::Malformed(format!(
+                            "unexpected magic number `{}`",
+                            magic
+                        )))
+                    }
+                };
+                let pixels = read_pixels(&mut reader, width, height, channels)?;
+                Ok(from_rgba(width, height, &expand_to_rgba(&pixels, channels)))
+            }
+
+            ImageFormat::Tga => {
+                let mut header = [0u8; 18];
+                reader.read_exact(&mut header)?;
+                let width = u16::from_le_bytes([header[12], header[13]]) as u32;
+                let height = u16::from_le_bytes([header[14], header[15]]) as u32;
+                let channels = match (header[2], header[16]) {
+                    (2, 24) => 3,
+                    (2, 32) => 4,
+                    (3, 8) => 1,
+                    (kind, bits) => {
+                        return Err(ImageError::Malformed(format!(
+                            "unsupported TGA image type {} with {} bits per pixel",
+                            kind, bits
+                        )))
+                    }
+                };
+                io::copy(&mut (&mut reader).take(header[0] as u64), &mut io::sink())?;
+
+                let mut pixels = read_pixels(&mut reader, width, height, channels)?;
+                if channels >= 3 {
+                    for pixel in pixels.chunks_exact_mut(channels) {
+                        pixel.swap(0, 2);
+                    }
+                }
+                // Bottom-left origin unless the top-left bit is set.
+                if header[17] & 0x20 == 0 {
+                    let row = width as usize * channels;
+                    pixels = pixels.chunks_exact(row).rev().flatten().copied().collect();
+                }
+                Ok(from_rgba(width, height, &expand_to_rgba(&pixels, channels)))
+            }
+
+            ImageFormat::Png => {
+                let mut decoder = png::Decoder::new(reader);
+                decoder.set_transformations(png::Transformations::normalize_to_color8());
+                let mut png_reader = decoder.read_info()?;
+                let mut buf = vec![0; png_reader.output_buffer_size()];
+                let info = png_reader.next_frame(&mut buf)?;
+                let channels = match info.color_type {
+                    png::ColorType::Grayscale => 1,
+                    png::ColorType::GrayscaleAlpha => 2,
+                    png::ColorType::Rgb => 3,
+                    png::ColorType::Rgba => 4,
+                    png::ColorType::Indexed => {
+                        return Err(ImageError::Malformed(String::from(
+                            "indexed colors were not expanded",
+                        )))
+                    }
+                };
+                let pixels = &buf[..info.buffer_size()];
+                Ok(from_rgba(
+                    info.width,
+                    info.height,
+                    &expand_to_rgba(pixels, channels),
+                ))
+            }
+        }
+    }
+
+    /**
+     * Write the texture to `path`, the format is decided by the file extension.
+     */
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageError> {
+        let path = path.as_ref();
+        let format = ImageFormat::from_path(path).ok_or_else(|| unknown_format(path))?;
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer, format)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /**
+     * Encode the texture into `writer`.
+     * The first row of the texture is the top row of the image.
+     */
+    pub fn write_to<W: Write>(&self, mut writer: W, format: ImageFormat) -> Result<(), ImageError> {
+        let rgba = self.to_vec_u8();
+
+        match format {
+            ImageFormat::Ppm => {
+                write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+                for pixel in rgba.chunks_exact(4) {
+                    writer.write_all(&pixel[0..3])?;
+                }
+            }
+
+            ImageFormat::Pam => {
+                write!(
+                    writer,
+                    "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+                    self.width, self.height
+                )?;
+                writer.write_all(&rgba)?;
+            }
+
+            ImageFormat::Tga => {
+                let size = |dimension: u32| {
+                    u16::try_from(dimension).map_err(|_| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!(
+                                "TGA images are at most {} pixels wide and high, got {}x{}",
+                                u16::MAX,
+                                self.width,
+                                self.height
+                            ),
+                        )
+                    })
+                };
+                let (width, height) = (size(self.width)?, size(self.height)?);
+                let mut header = [0u8; 18];
+                // Uncompressed true-color image.
+                header[2] = 2;
+                header[12..14].copy_from_slice(&width.to_le_bytes());
+                header[14..16].copy_from_slice(&height.to_le_bytes());
+                header[16] = 32;
+                // 8 alpha bits, top-left origin.
+                header[17] = 0x08 | 0x20;
+                writer.write_all(&header)?;
+                for pixel in rgba.chunks_exact(4) {
+                    writer.write_all(&[pixel[2], pixel[1], pixel[0], pixel[3]])?;
+                }
+            }
+
+            ImageFormat::Png => {
+                let mut encoder = png::Encoder::new(writer, self.width, self.height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                let mut png_writer = encoder.write_header()?;
+                png_writer.write_image_data(&rgba)?;
+                png_writer.finish()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Texture<Option<f32>> {
+    /**
+     * Normalize the written depth values to a grayscale image.
+     * The nearest depth maps to white, the farthest to black,
+     * texels that were never written are black as well.
+     */
+    pub fn to_grayscale(&self) -> Texture<Color> {
+        let mut min = f32::INFINITY;
+        let mut max = f32::NEG_INFINITY;
+        for depth in self.data.iter().flatten() {
+            min = min.min(*depth);
+            max = max.max(*depth);
+        }
+        let range = if max > min { max - min } else { 1.0 };
+
+        let mut texture = Texture::new(self.width, self.height);
+        for (texel, depth) in texture.data.iter_mut().zip(&self.data) {
+            if let Some(depth) = depth {
+                let v = 1.0 - (depth - min) / range;
+                *texel = Color::new(v, v, v, 1.0);
+            }
+        }
+        texture
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageError> {
+        self.to_grayscale().save(path)
+    }
+
+    pub fn write_to<W: Write>(&self, writer: W, format: ImageFormat) -> Result<(), ImageError> {
+        self.to_grayscale().write_to(writer, format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector2;
+
+    fn checker() -> Texture<Color> {
+        let mut texture = Texture::new(2, 2);
+        texture.set_texel(&Vector2::new(0, 0), Color::RED);
+        texture.set_texel(&Vector2::new(1, 0), Color::GREEN);
+        texture.set_texel(&Vector2::new(0, 1), Color::BLUE);
+        texture.set_texel(&Vector2::new(1, 1), Color::WHITE);
+        texture
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let mut bytes = Vec::new();
+        checker().write_to(&mut bytes, ImageFormat::Ppm).unwrap();
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(&bytes[..header.len()], header);
+        assert_eq!(
+            &bytes[header.len()..],
+            &[255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn test_write_tga() {
+        let mut bytes = Vec::new();
+        checker().write_to(&mut bytes, ImageFormat::Tga).unwrap();
+        assert_eq!(bytes.len(), 18 + 2 * 2 * 4);
+        assert_eq!(bytes[2], 2);
+        assert_eq!(bytes[17], 0x28);
+        // The first pixel is red, stored as BGRA.
+        assert_eq!(&bytes[18..22], &[0, 0, 255, 255]);
+
+        // Sizes are 16 bits, larger images must not be truncated into a corrupt file.
+        let wide: Texture<Color> = Texture::new(u16::MAX as u32 + 1, 1);
+        let mut bytes = Vec::new();
+        match wide.write_to(&mut bytes, ImageFormat::Tga) {
+            Err(ImageError::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::InvalidInput),
+            other => panic!(
+                "expected an invalid input error, got {:?}",
+                other.map(|_| ())
+            ),
+        }
+        assert!(bytes.is_empty());
+        let mut bytes = Vec::new();
+        let largest: Texture<Color> = Texture::new(u16::MAX as u32, 1);
+        largest.write_to(&mut bytes, ImageFormat::Tga).unwrap();
+        assert_eq!(&bytes[12..16], &[255, 255, 1, 0]);
+    }
+
+    #[test]
+    fn test_write_png() {
+        let mut bytes = Vec::new();
+        checker().write_to(&mut bytes, ImageFormat::Png).unwrap();
+
+        let decoder = png::Decoder::new(bytes.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (2, 2));
+        assert_eq!(&buf[..info.buffer_size()], checker().to_vec_u8().as_slice());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for format in [
+            ImageFormat::Ppm,
+            ImageFormat::Pam,
+            ImageFormat::Tga,
+            ImageFormat::Png,
+        ] {
+            let mut bytes = Vec::new();
+            checker().write_to(&mut bytes, format).unwrap();
+            let texture = Texture::<Color>::read_from(bytes.as_slice(), format).unwrap();
+            assert_eq!((texture.width, texture.height), (2, 2));
+            assert_eq!(texture.to_vec_u8(), checker().to_vec_u8(), "{:?}", format);
+        }
+    }
+
+    fn read(bytes: &[u8], format: ImageFormat) -> Result<Vec<u8>, ImageError> {
+        Texture::<Color>::read_from(bytes, format).map(|texture| texture.to_vec_u8())
+    }
+
+    #[test]
+    fn test_read_ppm() {
+        let bytes = b"P6\n# a comment\n2 1 255\n\xff\x00\x00\x00\x00\xff";
+        let pixels = read(bytes, ImageFormat::Ppm).unwrap();
+        assert_eq!(pixels, [255, 0, 0, 255, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_read_pam() {
+        let header =
+            "P7\nWIDTH 2\nHEIGHT 1\nDEPTH 2\nMAXVAL 255\nTUPLTYPE GRAYSCALE_ALPHA\nENDHDR\n";
+        let bytes = [header.as_bytes(), &[10, 20, 30, 40]].concat();
+        let pixels = read(&bytes, ImageFormat::Pam).unwrap();
+        assert_eq!(pixels, [10, 10, 10, 20, 30, 30, 30, 40]);
+    }
+
+    #[test]
+    fn test_read_tga() {
+        // 2x2 grayscale with a one byte image id, stored bottom row first.
+        let mut bytes = vec![0u8; 18];
+        bytes[0] = 1;
+        bytes[2] = 3;
+        bytes[12] = 2;
+        bytes[14] = 2;
+        bytes[16] = 8;
+        bytes.extend_from_slice(&[99, 1, 2, 3, 4]);
+        let pixels = read(&bytes, ImageFormat::Tga).unwrap();
+        let gray = |v: u8| [v, v, v, 255];
+        assert_eq!(pixels, [gray(3), gray(4), gray(1), gray(2)].concat());
+
+        // 24 bits BGR, top row first.
+        let mut bytes = vec![0u8; 18];
+        bytes[2] = 2;
+        bytes[12] = 1;
+        bytes[14] = 1;
+        bytes[16] = 24;
+        bytes[17] = 0x20;
+        bytes.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(read(&bytes, ImageFormat::Tga).unwrap(), [3, 2, 1, 255]);
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let malformed =
+            |bytes: &[u8], format| matches!(read(bytes, format), Err(ImageError::Malformed(_)));
+        let truncated = |bytes: &[u8], format| match read(bytes, format) {
+            Err(ImageError::Io(err)) => err.kind() == io::ErrorKind::UnexpectedEof,
+            _ => false,
+        };
+        assert!(malformed(b"P5\n1 1\n255\n\x00", ImageFormat::Ppm));
+        assert!(malformed(b"P6\n1 1\n65535\n\x00", ImageFormat::Ppm));
+        assert!(malformed(b"P6\n1", ImageFormat::Ppm));
+        assert!(malformed(
+            b"P7\nWIDTH 1\nHEIGHT 1\nDEPTH 5\nENDHDR\n",
+            ImageFormat::Pam
+        ));
+        assert!(malformed(
+            &[0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 0, 8, 0],
+            ImageFormat::Tga
+        ));
+        assert!(truncated(b"P6\n2 2\n255\n\x00\x00\x00", ImageFormat::Ppm));
+
+        // Huge sizes in a tiny file fail on the missing data, without allocating it,
+        // or on their size when it overflows.
+        assert!(truncated(b"P6\n100000 100000\n255\n\x00", ImageFormat::Ppm));
+        assert!(malformed(
+            b"P6\n4000000000 4000000000\n255\n",
+            ImageFormat::Ppm
+        ));
+        let mut bytes = vec![0u8; 18];
+        bytes[2] = 2;
+        bytes[12..16].copy_from_slice(&[255, 255, 255, 255]);
+        bytes[16] = 32;
+        assert!(truncated(&bytes, ImageFormat::Tga));
+    }
+
+    #[test]
+    fn test_depth_to_grayscale() {
+        let mut depth = Texture::<Option<f32>>::new(3, 1);
+        depth.set_texel(&Vector2::new(0, 0), Some(-0.5));
+        depth.set_texel(&Vector2::new(1, 0), Some(0.5));
+        let gray = depth.to_grayscale();
+        assert_eq!(gray.get_texel(&Vector2::new(0, 0)).r, 1.0);
+        assert_eq!(gray.get_texel(&Vector2::new(1, 0)).r, 0.0);
+        assert_eq!(gray.get_texel(&Vector2::new(2, 0)).r, 0.0);
+    }
+}
@@ -14,12 +14,41 @@ use crate::{
 use interpolate::Interpolate;
 use nalgebra::{Vector2, Vector3, Vector4};
 
+/**
+ * What covers a pixel of the id attachment.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelId {
+    /**
+     * The object id of the draw, see `RenderPass::draw_object`.
+     */
+    pub object: u32,
+
+    /**
+     * Index of the triangle in its draw, see `FsPayload::primitive_id`.
+     */
+    pub primitive: u32,
+
+    pub depth: f32,
+}
+
 pub struct RenderPass {
     pub viewport: Viewport,
 
     pub frame_texture: Texture<Color>,
 
     pub depth_texture: Texture<Option<f32>>,
+
+    /**
+     * Written alongside the frame texture when present, laid out the same way.
+     */
+    pub id_texture: Option<Texture<Option<PixelId>>>,
+
+    /**
+     * Whether depths are in [0, 1] with the near plane at 1, as reversed Z projections
+     * output, rather than in [-1, 1] with the near plane at -1.
+     */
+    pub reversed_z: bool,
 }
 
 impl RenderPass {
@@ -28,12 +57,49 @@ impl RenderPass {
             viewport,
             frame_texture: Texture::new(viewport.width, viewport.height),
             depth_texture: Texture::new(viewport.width, viewport.height),
+            id_texture: None,
+            reversed_z: false,
         }
     }
 
+    /**
+     * The same pass, also recording which object and triangle covers each pixel.
+     */
+    pub fn with_id_texture(mut self) -> Self {
+        self.id_texture = Some(Texture::new(self.viewport.width, self.viewport.height));
+        self
+    }
+
+    /**
+     * The same pass, for reversed Z projections, see `View::with_reversed_z`.
+     * Draw with `DepthCompare::Greater` to keep the nearest fragments.
+     */
+    pub fn with_reversed_z(mut self) -> Self {
+        self.reversed_z = true;
+        self
+    }
+
     pub fn clear(&mut self) {
         self.frame_texture.data.fill(Color::WHITE);
         self.depth_texture.data.fill(None);
+        if let Some(id_texture) = &mut self.id_texture {
+            id_texture.data.fill(None);
+        }
+    }
+
+    /**
+     * The object id, primitive id and depth of the fragment nearest by the depth
+     * comparison of the draws at `(x, y)` of the frame texture, whose first row is the
+     * top, with or without depth writes. `None` for a background pixel, outside of the
+     * viewport or without an id texture.
+     */
+    pub fn pick(&self, x: u32, y: u32) -> Option<(u32, u32, f32)> {
+        let id_texture = self.id_texture.as_ref()?;
+        if x >= id_texture.width || y >= id_texture.height {
+            return None;
+        }
+        let id = id_texture.get_texel(&Vector2::new(x, y))?;
+        Some((id.object, id.primitive, id.depth))
     }
 
     pub fn draw_pixel(&mut self, p: &Vector2<i32>, color: &Color) {
@@ -61,10 +127,26 @@ impl RenderPass {
         }
     }
 
+    /**
+     * Draw with an object id of 0, see `draw_object`.
+     */
     pub fn draw<'a, V: std::fmt::Debug + Interpolate>(
         &mut self,
         pipeline: &mut Pipeline<'a, V>,
         vertex_count: usize,
+    ) {
+        self.draw_object(pipeline, vertex_count, 0);
+    }
+
+    /**
+     * Like `draw`, recording `object_id` in the id texture if any. Ids are depth tested
+     * on their own depth, so they follow the nearest fragment even without depth writes.
+     */
+    pub fn draw_object<'a, V: std::fmt::Debug + Interpolate>(
+        &mut self,
+        pipeline: &mut Pipeline<'a, V>,
+        vertex_count: usize,
+        object_id: u32,
     ) {
         let mut vs_outputs: Vec<VsOutput<V>> = (0..vertex_count)
             .map(|index| {
@@ -74,11 +156,17 @@ impl RenderPass {
             })
             .collect();
 
+        let depth_range = if self.reversed_z {
+            (0.0, 1.0)
+        } else {
+            (-1.0, 1.0)
+        };
         for i in (0..vertex_count).step_by(3) {
             if is_triangle_outside_clipspace_sketchy(
                 &vs_outputs[i].position,
                 &vs_outputs[i + 1].position,
                 &vs_outputs[i + 2].position,
+                depth_range,
             ) {
                 continue;
             }
@@ -91,7 +179,9 @@ impl RenderPass {
             let v_1 = &vs_outputs[i + 1];
             let v_2 = &vs_outputs[i + 2];
 
-            let fs_payloads = collect_triangle(v_0, v_1, v_2, &self.viewport);
+            let primitive_id = (i / 3) as u32;
+            let fs_payloads =
+                collect_triangle(v_0, v_1, v_2, primitive_id, &self.viewport, self.reversed_z);
 
             for payload in fs_payloads {
                 let position = payload.position;
@@ -114,8 +204,24 @@ impl RenderPass {
                     );
                 }
 
+                let id = PixelId {
+                    object: object_id,
+                    primitive: payload.primitive_id,
+                    depth: payload.position.z,
+                };
                 let position = position.xy().map(|v| v as i32);
                 self.draw_pixel(&position, &pipeline.program.fragment_shader(payload));
+                if let Some(id_texture) = &mut self.id_texture {
+                    // Flipped like the frame texture in `draw_pixel`.
+                    let y = self.viewport.height - 1 - position.y as u32;
+                    let texel = Vector2::new(position.x as u32, y);
+                    let nearer = id_texture
+                        .get_texel(&texel)
+                        .is_none_or(|prev| pipeline.depth_compare.test(id.depth, prev.depth));
+                    if nearer {
+                        id_texture.set_texel(&texel, Some(id));
+                    }
+                }
             }
         }
     }
@@ -125,13 +231,14 @@ fn is_triangle_outside_clipspace_sketchy(
     p_0: &Vector4<f32>,
     p_1: &Vector4<f32>,
     p_2: &Vector4<f32>,
+    (min_z, max_z): (f32, f32),
 ) -> bool {
     let bbox = Bbox3::from_vector3(&vec![
         &Vector3::new(p_0.x, p_0.y, p_0.z),
         &Vector3::new(p_1.x, p_1.y, p_1.z),
         &Vector3::new(p_2.x, p_2.y, p_2.z),
     ]);
-    let clipspace = Bbox3::new(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0);
+    let clipspace = Bbox3::new(-1.0, 1.0, -1.0, 1.0, min_z, max_z);
     bbox.intersect(&clipspace).is_none()
 }
 
@@ -147,7 +254,9 @@ fn collect_triangle<V: Interpolate>(
     v_0: &VsOutput<V>,
     v_1: &VsOutput<V>,
     v_2: &VsOutput<V>,
+    primitive_id: u32,
     viewport: &Viewport,
+    reversed_z: bool,
 ) -> Vec<FsPayload<V>> {
     let mut fs_payloads = Vec::new();
 
@@ -160,7 +269,7 @@ fn collect_triangle<V: Interpolate>(
         );
         let position = Vector4::new(p.x as f32, p.y as f32, z, 1.0);
 
-        if !is_position_inside_viewport(&position, viewport) {
+        if !is_position_inside_viewport(&position, viewport, reversed_z) {
             return;
         }
 
@@ -171,6 +280,7 @@ fn collect_triangle<V: Interpolate>(
             position,
             varying,
             bary_coord,
+            primitive_id,
         });
     };
 
@@ -185,11 +295,104 @@ fn collect_triangle<V: Interpolate>(
     fs_payloads
 }
 
-fn is_position_inside_viewport(p: &Vector4<f32>, viewport: &Viewport) -> bool {
+/**
+ * Whether `p` is on the viewport and between the near plane, included, and the far
+ * plane, excluded, which are at depth 1 and 0 when `reversed_z`.
+ */
+fn is_position_inside_viewport(p: &Vector4<f32>, viewport: &Viewport, reversed_z: bool) -> bool {
+    let depth_inside = if reversed_z {
+        p.z > 0.0 && p.z <= 1.0
+    } else {
+        p.z >= -1.0 && p.z < 1.0
+    };
     p.x >= 0.0
         && p.x < viewport.width as f32
         && p.y >= 0.0
         && p.y < viewport.height as f32
-        && p.z >= -1.0
-        && p.z < 1.0
+        && depth_inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::{pipeline::CullMode, shader::Shader};
+
+    struct DepthProgram {
+        depth: f32,
+    }
+
+    impl Shader for DepthProgram {
+        type Varying = ();
+
+        fn vertex_shader(&self, index: usize) -> VsOutput<Self::Varying> {
+            let corners = [(-1.0, -1.0), (3.0, -1.0), (-1.0, 3.0)];
+            let (x, y) = corners[index];
+            VsOutput {
+                position: Vector4::new(x, y, self.depth, 1.0),
+                varying: (),
+            }
+        }
+
+        fn fragment_shader(&self, _: FsPayload<Self::Varying>) -> Color {
+            Color::RED
+        }
+    }
+
+    #[test]
+    fn test_depth_range() {
+        // Covering the viewport at a depth on either plane.
+        let drawn = |depth: f32, depth_compare: DepthCompare, reversed_z: bool| {
+            let mut pass = RenderPass::new(Viewport::new(4, 4));
+            pass.reversed_z = reversed_z;
+            pass.clear();
+            let program = DepthProgram { depth };
+            let mut pipeline = Pipeline {
+                program: &program,
+                cull_mode: CullMode::None,
+                depth_write_enable: true,
+                depth_compare,
+            };
+            pass.draw(&mut pipeline, 3);
+            pass.depth_texture.data.iter().any(Option::is_some)
+        };
+        assert!(drawn(-1.0, DepthCompare::Less, false));
+        assert!(!drawn(1.0, DepthCompare::Less, false));
+        // The comparison does not move the planes.
+        assert!(drawn(-1.0, DepthCompare::Greater, false));
+        assert!(!drawn(1.0, DepthCompare::Greater, false));
+
+        assert!(drawn(1.0, DepthCompare::Greater, true));
+        assert!(!drawn(0.0, DepthCompare::Greater, true));
+        assert!(!drawn(-0.5, DepthCompare::Greater, true));
+    }
+
+    #[test]
+    fn test_pick_without_depth_writes() {
+        let mut pass = RenderPass::new(Viewport::new(4, 4)).with_id_texture();
+        pass.clear();
+        // The near object first, then the far one.
+        for (object_id, depth) in [(1, -0.5), (2, 0.5)] {
+            let program = DepthProgram { depth };
+            let mut pipeline = Pipeline {
+                program: &program,
+                cull_mode: CullMode::None,
+                depth_write_enable: false,
+                depth_compare: DepthCompare::Less,
+            };
+            pass.draw_object(&mut pipeline, 3, object_id);
+        }
+        assert!(pass.depth_texture.data.iter().all(Option::is_none));
+        assert_eq!(pass.pick(1, 1), Some((1, 0, -0.5)));
+
+        // Plain draws record an object id of 0.
+        let program = DepthProgram { depth: -0.75 };
+        let mut pipeline = Pipeline {
+            program: &program,
+            cull_mode: CullMode::None,
+            depth_write_enable: false,
+            depth_compare: DepthCompare::Less,
+        };
+        pass.draw(&mut pipeline, 3);
+        assert_eq!(pass.pick(1, 1), Some((0, 0, -0.75)));
+    }
 }
@@ -16,6 +16,11 @@ pub struct FsPayload<V: Interpolate> {
     pub varying: V,
 
     pub bary_coord: Vector3<f32>,
+
+    /**
+     * Index of the triangle in its draw call.
+     */
+    pub primitive_id: u32,
 }
 
 #[allow(unused_variables)]
@@ -0,0 +1,269 @@
+pub mod animation;
+pub mod bvh;
+pub mod camera;
+pub mod draco;
+pub mod frustum;
+pub mod geometry;
+pub mod gltf;
+pub mod light;
+pub mod material;
+pub mod mesh;
+pub mod morph;
+pub mod node;
+pub mod obj;
+pub mod pbr;
+pub mod ply;
+pub mod ray;
+pub mod render;
+pub mod simplify;
+pub mod skin;
+pub mod stl;
+pub mod tangent;
+
+use crate::{
+    common::{basetype::Bbox3, color::Color},
+    rasterizer::texture::Texture,
+    scene::{
+        animation::Animation,
+        camera::{Camera, View},
+        light::{Light, Lighting, PunctualLight},
+        material::Material,
+        mesh::Mesh,
+        node::{Instance, Node, SceneGraph},
+        skin::Skin,
+    },
+};
+use nalgebra::{Matrix4, Vector3};
+
+#[derive(Debug)]
+pub struct Scene {
+    pub meshes: Vec<Mesh>,
+
+    /**
+     * Textures referenced by `Primitive::texture_index` and materials.
+     */
+    pub textures: Vec<Texture<Color>>,
+
+    /**
+     * Materials referenced by `Primitive::material`.
+     */
+    pub materials: Vec<Material>,
+
+    /**
+     * Cameras referenced by `Node::camera`.
+     */
+    pub cameras: Vec<Camera>,
+
+    /**
+     * Lights referenced by `Node::light`.
+     */
+    pub lights: Vec<Light>,
+
+    /**
+     * Skins referenced by `Node::skin`.
+     */
+    pub skins: Vec<Skin>,
+
+    pub animations: Vec<Animation>,
+
+    pub nodes: Vec<Node>,
+
+    pub graphs: Vec<SceneGraph>,
+
+    /**
+     * The graph to show when none is chosen explicitly.
+     */
+    pub default_graph: Option<usize>,
+}
+
+impl Scene {
+    /**
+     * A scene of `mesh` alone at the origin, as read from mesh-only formats like STL or PLY.
+     */
+    pub fn from_mesh(mesh: Mesh) -> Self {
+        Self {
+            meshes: vec![mesh],
+            textures: Vec::new(),
+            materials: Vec::new(),
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            skins: Vec::new(),
+            animations: Vec::new(),
+            nodes: Vec::new(),
+            graphs: Vec::new(),
+            default_graph: None,
+        }
+    }
+
+    /**
+     * The root nodes of `graph`, or of the default graph when `None`.
+     * Without any graph, every node without a parent is a root.
+     */
+    pub fn roots(&self, graph: Option<usize>) -> Vec<usize> {
+        match graph.or(self.default_graph) {
+            Some(index) => self.graphs[index].roots.clone(),
+            None if !self.graphs.is_empty() => self.graphs[0].roots.clone(),
+            None => {
+                let mut is_child = vec![false; self.nodes.len()];
+                for node in &self.nodes {
+                    for child in &node.children {
+                        is_child[*child] = true;
+                    }
+                }
+                (0..self.nodes.len()).filter(|i| !is_child[*i]).collect()
+            }
+        }
+    }
+
+    /**
+     * World matrix of every node reachable from the roots of `graph`,
+     * `None` for nodes outside of it.
+     */
+    pub fn world_transforms(&self, graph: Option<usize>) -> Vec<Option<Matrix4<f32>>> {
+        let mut worlds = vec![None; self.nodes.len()];
+        let mut stack: Vec<(usize, Matrix4<f32>)> = self
+            .roots(graph)
+            .into_iter()
+            .map(|root| (root, Matrix4::identity()))
+            .collect();
+
+        while let Some((index, parent)) = stack.pop() {
+            if worlds[index].is_some() {
+                continue;
+            }
+            let node = &self.nodes[index];
+            let world = parent * node.transform.matrix();
+            worlds[index] = Some(world);
+            stack.extend(node.children.iter().map(|child| (*child, world)));
+        }
+
+        worlds
+    }
+
+    /**
+     * Every mesh placed by the nodes of `graph`.
+     * An asset without nodes places each mesh once at the origin.
+     */
+    pub fn instances(&self, graph: Option<usize>) -> Vec<Instance> {
+        if self.nodes.is_empty() {
+            return (0..self.meshes.len())
+                .map(|mesh| Instance {
+                    node: None,
+                    mesh,
+                    world: Matrix4::identity(),
+                })
+                .collect();
+        }
+
+        self.world_transforms(graph)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(node, world)| {
+                Some(Instance {
+                    node: Some(node),
+                    mesh: self.nodes[node].mesh?,
+                    world: world?,
+                })
+            })
+            .collect()
+    }
+
+    /**
+     * World space bounds of all instances of `graph`.
+     */
+    pub fn bounds(&self, graph: Option<usize>) -> Option<Bbox3<f32>> {
+        let positions: Vec<Vector3<f32>> = self
+            .instances(graph)
+            .iter()
+            .flat_map(|instance| {
+                self.meshes[instance.mesh]
+                    .primitives
+                    .iter()
+                    .flat_map(|primitive| &primitive.vertices)
+                    .map(move |vertex| (instance.world * vertex.position).xyz())
+            })
+            .collect();
+        if positions.is_empty() {
+            return None;
+        }
+        Some(Bbox3::from_vector3(&positions.iter().collect()))
+    }
+
+    /**
+     * Index of the first camera called `name`.
+     */
+    pub fn find_camera(&self, name: &str) -> Option<usize> {
+        self.cameras
+            .iter()
+            .position(|camera| camera.name.as_deref() == Some(name))
+    }
+
+    /**
+     * The view through `camera`, placed by the first node of `graph` referencing it.
+     * When `None`, the first camera placed in `graph` is used, and without any the view
+     * frames the bounds of `graph` from the +X+Y+Z diagonal.
+     *
+     * `None` when the camera isn't placed in `graph`, or when there is nothing to frame.
+     */
+    pub fn view(
+        &self,
+        camera: Option<usize>,
+        graph: Option<usize>,
+        viewport_aspect: f32,
+    ) -> Option<View> {
+        let worlds = self.world_transforms(graph);
+        let placed = self
+            .nodes
+            .iter()
+            .zip(&worlds)
+            .filter_map(|(node, world)| Some((node.camera?, (*world)?)))
+            .find(|(index, _)| camera.is_none_or(|camera| camera == *index));
+
+        match (placed, camera) {
+            (Some((index, world)), _) => Some(View::from_node(
+                &world,
+                &self.cameras[index].projection,
+                viewport_aspect,
+            )),
+            (None, Some(_)) => None,
+            (None, None) => Some(View::framing(
+                &self.bounds(graph)?,
+                &Vector3::new(1.0, 1.0, 1.0),
+                45.0_f32.to_radians(),
+                viewport_aspect,
+            )),
+        }
+    }
+
+    /**
+     * Every light placed by the nodes of `graph`, in world space.
+     */
+    pub fn lighting(&self, graph: Option<usize>, ambient: Vector3<f32>) -> Lighting {
+        let lights = self
+            .nodes
+            .iter()
+            .zip(self.world_transforms(graph))
+            .filter_map(|(node, world)| {
+                Some(PunctualLight::new(&self.lights[node.light?], &world?))
+            })
+            .collect();
+        Lighting { ambient, lights }
+    }
+
+    /**
+     * Pose the nodes with `animation` at `time` seconds.
+     */
+    pub fn animate(&mut self, animation: usize, time: f32) {
+        self.animations[animation].apply(time, &mut self.nodes);
+    }
+
+    /**
+     * The morph target weights of `instance`, its node's or else its mesh's.
+     */
+    pub fn morph_weights(&self, instance: &Instance) -> &[f32] {
+        instance
+            .node
+            .and_then(|node| self.nodes[node].weights.as_deref())
+            .unwrap_or(&self.meshes[instance.mesh].weights)
+    }
+}
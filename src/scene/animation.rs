@@ -0,0 +1,299 @@
+use crate::scene::node::{Node, Transform};
+use nalgebra::{Quaternion, UnitQuaternion, Vector3};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Linear,
+
+    /**
+     * Holds each keyframe until the next one.
+     */
+    Step,
+
+    /**
+     * Hermite spline, every keyframe stores an in-tangent, a value and an out-tangent.
+     */
+    CubicSpline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Property {
+    Translation,
+
+    /**
+     * Quaternions stored as `x, y, z, w`.
+     */
+    Rotation,
+
+    Scale,
+
+    /**
+     * Morph target weights, as many per keyframe as the mesh has targets.
+     */
+    Weights,
+}
+
+/**
+ * Keyframes of one property.
+ */
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    pub interpolation: Interpolation,
+
+    /**
+     * Increasing keyframe times in seconds.
+     */
+    pub inputs: Vec<f32>,
+
+    /**
+     * The flattened keyframe values, three times as many for cubic splines.
+     */
+    pub outputs: Vec<f32>,
+}
+
+impl Sampler {
+    /**
+     * The number of components of every value.
+     */
+    pub fn components(&self) -> usize {
+        let per_keyframe = match self.interpolation {
+            Interpolation::CubicSpline => 3,
+            _ => 1,
+        };
+        self.outputs
+            .len()
+            .checked_div(self.inputs.len() * per_keyframe)
+            .unwrap_or(0)
+    }
+
+    /**
+     * The value at `time`, clamped to the first and last keyframes.
+     * Rotations are interpolated spherically and come out normalized.
+     */
+    pub fn sample(&self, time: f32, property: Property) -> Vec<f32> {
+        let n = self.components();
+        if n == 0 {
+            return Vec::new();
+        }
+        let cubic = self.interpolation == Interpolation::CubicSpline;
+        // The value of keyframe `k`, skipping the in-tangent of cubic splines.
+        let value = |k: usize, part: usize| -> &[f32] {
+            let start = if cubic { (3 * k + part) * n } else { k * n };
+            &self.outputs[start..start + n]
+        };
+
+        let last = self.inputs.len() - 1;
+        let next = self.inputs.partition_point(|input| *input <= time);
+        if next == 0 || next > last {
+            let k = if next == 0 { 0 } else { last };
+            return normalized(value(k, 1).to_vec(), property);
+        }
+
+        let k = next - 1;
+        let dt = self.inputs[next] - self.inputs[k];
+        let s = if dt > 0.0 {
+            (time - self.inputs[k]) / dt
+        } else {
+            0.0
+        };
+
+        let result = match self.interpolation {
+            Interpolation::Step => value(k, 1).to_vec(),
+            Interpolation::Linear if property == Property::Rotation => {
+                let quaternion = |v: &[f32]| {
+                    UnitQuaternion::from_quaternion(Quaternion::new(v[3], v[0], v[1], v[2]))
+                };
+                let q = quaternion(value(k, 1)).slerp(&quaternion(value(next, 1)), s);
+                vec![q.i, q.j, q.k, q.w]
+            }
+            Interpolation::Linear => value(k, 1)
+                .iter()
+                .zip(value(next, 1))
+                .map(|(a, b)| a + (b - a) * s)
+                .collect(),
+            Interpolation::CubicSpline => {
+                let (s2, s3) = (s * s, s * s * s);
+                let (v0, b0) = (value(k, 1), value(k, 2));
+                let (a1, v1) = (value(next, 0), value(next, 1));
+                (0..n)
+                    .map(|i| {
+                        (2.0 * s3 - 3.0 * s2 + 1.0) * v0[i]
+                            + dt * (s3 - 2.0 * s2 + s) * b0[i]
+                            + (-2.0 * s3 + 3.0 * s2) * v1[i]
+                            + dt * (s3 - s2) * a1[i]
+                    })
+                    .collect()
+            }
+        };
+        normalized(result, property)
+    }
+}
+
+fn normalized(mut value: Vec<f32>, property: Property) -> Vec<f32> {
+    if property == Property::Rotation {
+        let norm = value.iter().map(|c| c * c).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            value.iter_mut().for_each(|c| *c /= norm);
+        }
+    }
+    value
+}
+
+/**
+ * Animates one property of a node.
+ */
+#[derive(Debug, Clone)]
+pub struct Channel {
+    pub node: usize,
+
+    pub property: Property,
+
+    pub sampler: Sampler,
+}
+
+#[derive(Debug, Clone)]
+pub struct Animation {
+    pub name: Option<String>,
+
+    pub channels: Vec<Channel>,
+}
+
+impl Animation {
+    /**
+     * Time of the last keyframe in seconds.
+     */
+    pub fn duration(&self) -> f32 {
+        self.channels
+            .iter()
+            .filter_map(|channel| channel.sampler.inputs.last())
+            .fold(0.0, |duration, time| duration.max(*time))
+    }
+
+    /**
+     * Pose `nodes` at `time`. Animated nodes get a TRS transform, keeping the components
+     * that aren't animated; a matrix transform is decomposed first.
+     */
+    pub fn apply(&self, time: f32, nodes: &mut [Node]) {
+        for channel in &self.channels {
+            let Some(node) = nodes.get_mut(channel.node) else {
+                continue;
+            };
+            let value = channel.sampler.sample(time, channel.property);
+            if channel.property == Property::Weights {
+                node.weights = Some(value);
+                continue;
+            }
+            if value.len() < 3 {
+                continue;
+            }
+
+            let (mut translation, mut rotation, mut scale) = node.transform.decompose();
+            match channel.property {
+                Property::Translation => translation = Vector3::new(value[0], value[1], value[2]),
+                Property::Rotation if value.len() == 4 => {
+                    rotation = UnitQuaternion::new_unchecked(Quaternion::new(
+                        value[3], value[0], value[1], value[2],
+                    ))
+                }
+                Property::Scale => scale = Vector3::new(value[0], value[1], value[2]),
+                _ => continue,
+            }
+            node.transform = Transform::Trs {
+                translation,
+                rotation,
+                scale,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sampler(interpolation: Interpolation, outputs: Vec<f32>) -> Sampler {
+        Sampler {
+            interpolation,
+            inputs: vec![0.0, 1.0, 3.0],
+            outputs,
+        }
+    }
+
+    #[test]
+    fn test_sample_scalar() {
+        let linear = sampler(Interpolation::Linear, vec![0.0, 2.0, 6.0]);
+        assert_eq!(linear.components(), 1);
+        assert_eq!(linear.sample(-1.0, Property::Weights), vec![0.0]);
+        assert_eq!(linear.sample(0.5, Property::Weights), vec![1.0]);
+        assert_eq!(linear.sample(2.0, Property::Weights), vec![4.0]);
+        assert_eq!(linear.sample(5.0, Property::Weights), vec![6.0]);
+
+        let step = sampler(Interpolation::Step, vec![0.0, 2.0, 6.0]);
+        assert_eq!(step.sample(0.99, Property::Weights), vec![0.0]);
+        assert_eq!(step.sample(1.0, Property::Weights), vec![2.0]);
+
+        // In-tangent, value and out-tangent per keyframe. Zero tangents ease in and out.
+        let cubic = sampler(
+            Interpolation::CubicSpline,
+            vec![0.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 6.0, 0.0],
+        );
+        assert_eq!(cubic.components(), 1);
+        assert_eq!(cubic.sample(1.0, Property::Weights), vec![2.0]);
+        assert!((cubic.sample(0.5, Property::Weights)[0] - 1.0).abs() < 1e-6);
+        assert!(cubic.sample(0.25, Property::Weights)[0] < 0.5);
+        // Matching tangents reproduce a straight line.
+        let line = Sampler {
+            interpolation: Interpolation::CubicSpline,
+            inputs: vec![0.0, 2.0],
+            outputs: vec![1.0, 0.0, 1.0, 1.0, 2.0, 1.0],
+        };
+        assert!((line.sample(0.5, Property::Weights)[0] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_animate_node() {
+        let half_turn = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), std::f32::consts::PI);
+        let animation = Animation {
+            name: None,
+            channels: vec![
+                Channel {
+                    node: 0,
+                    property: Property::Rotation,
+                    sampler: Sampler {
+                        interpolation: Interpolation::Linear,
+                        inputs: vec![0.0, 2.0],
+                        outputs: vec![0.0, 0.0, 0.0, 1.0, half_turn.i, half_turn.j, 0.0, 0.0],
+                    },
+                },
+                Channel {
+                    node: 0,
+                    property: Property::Translation,
+                    sampler: Sampler {
+                        interpolation: Interpolation::Step,
+                        inputs: vec![0.0],
+                        outputs: vec![1.0, 2.0, 3.0],
+                    },
+                },
+            ],
+        };
+        assert_eq!(animation.duration(), 2.0);
+
+        let mut nodes = vec![Node {
+            name: None,
+            transform: Transform::Matrix(nalgebra::Matrix4::new_scaling(2.0)),
+            mesh: None,
+            camera: None,
+            light: None,
+            skin: None,
+            weights: None,
+            children: Vec::new(),
+        }];
+        animation.apply(1.0, &mut nodes);
+
+        // A quarter turn around Y, keeping the scale of the matrix.
+        let matrix = nodes[0].transform.matrix();
+        let x = matrix.transform_vector(&Vector3::x());
+        assert!((x - Vector3::new(0.0, 0.0, -2.0)).norm() < 1e-5, "{:?}", x);
+        assert_eq!(matrix.column(3).xyz(), Vector3::new(1.0, 2.0, 3.0));
+    }
+}
@@ -0,0 +1,822 @@
+use crate::{
+    common::basetype::Bbox3,
+    scene::{
+        frustum::{Containment, Frustum},
+        geometry::transform_bounds,
+        mesh::Primitive,
+        node::Instance,
+        ray::Ray,
+        Scene,
+    },
+};
+use nalgebra::{Matrix4, Vector3};
+
+/**
+ * Number of buckets along an axis when evaluating split candidates.
+ */
+const SAH_BINS: usize = 16;
+
+/**
+ * Cost of visiting a node relative to testing one item.
+ */
+const TRAVERSAL_COST: f32 = 1.0;
+
+/**
+ * Leaves with more items are split even when the heuristic prefers not to.
+ */
+const MAX_LEAF_SIZE: usize = 8;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BvhNode {
+    pub bounds: Bbox3<f32>,
+
+    /**
+     * Index of the first of the two consecutive children of an inner node,
+     * or of the first item in `Bvh::items` of a leaf.
+     */
+    pub first: usize,
+
+    /**
+     * Number of items of a leaf, zero for inner nodes.
+     */
+    pub count: usize,
+}
+
+impl BvhNode {
+    pub fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+/**
+ * A binary tree of boxes over items known by their index and bounds, built with the
+ * surface area heuristic. Children always follow their parent in `nodes`.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Bvh {
+    /**
+     * The root first, empty without items.
+     */
+    pub nodes: Vec<BvhNode>,
+
+    /**
+     * Item indices, each leaf owning a contiguous range.
+     */
+    pub items: Vec<usize>,
+}
+
+impl Bvh {
+    /**
+     * Build over the items `0..bounds.len()`.
+     */
+    pub fn build(bounds: &[Bbox3<f32>]) -> Self {
+        let mut bvh = Self {
+            nodes: Vec::new(),
+            items: (0..bounds.len()).collect(),
+        };
+        if bounds.is_empty() {
+            return bvh;
+        }
+        let centers: Vec<Vector3<f32>> = bounds.iter().map(Bbox3::center).collect();
+
+        bvh.nodes.push(BvhNode {
+            bounds: bounds[0],
+            first: 0,
+            count: bounds.len(),
+        });
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let BvhNode { first, count, .. } = bvh.nodes[index];
+            let items = &mut bvh.items[first..first + count];
+            let node_bounds = union_of(items.iter().map(|&item| &bounds[item])).unwrap();
+            bvh.nodes[index].bounds = node_bounds;
+
+            let Some(left_count) = split(items, bounds, &centers, &node_bounds) else {
+                continue;
+            };
+            let children = bvh.nodes.len();
+            bvh.nodes[index].first = children;
+            bvh.nodes[index].count = 0;
+            for (first, count) in [
+                (first, left_count),
+                (first + left_count, count - left_count),
+            ] {
+                bvh.nodes.push(BvhNode {
+                    bounds: node_bounds,
+                    first,
+                    count,
+                });
+            }
+            stack.extend([children, children + 1]);
+        }
+        bvh
+    }
+
+    /**
+     * Recompute the node bounds for items that moved, keeping the tree. Queries stay
+     * correct, but get slower the further items move from where they were built.
+     */
+    pub fn refit(&mut self, bounds: &[Bbox3<f32>]) {
+        // Children follow their parents, so visiting backwards updates them first.
+        for index in (0..self.nodes.len()).rev() {
+            let BvhNode { first, count, .. } = self.nodes[index];
+            self.nodes[index].bounds = if count > 0 {
+                union_of(self.items[first..first + count].iter().map(|&i| &bounds[i]))
+            } else {
+                union_of(self.nodes[first..first + 2].iter().map(|node| &node.bounds))
+            }
+            .unwrap();
+        }
+    }
+
+    /**
+     * Items whose bounds the ray crosses before `max_t`.
+     */
+    pub fn intersect_ray(&self, ray: &Ray, max_t: f32) -> Vec<usize> {
+        let mut items = Vec::new();
+        let mut stack: Vec<usize> = if self.nodes.is_empty() {
+            Vec::new()
+        } else {
+            vec![0]
+        };
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !matches!(ray.intersect_bbox(&node.bounds), Some((near, _)) if near <= max_t) {
+                continue;
+            }
+            if node.is_leaf() {
+                items.extend_from_slice(&self.items[node.first..node.first + node.count]);
+            } else {
+                stack.extend([node.first, node.first + 1]);
+            }
+        }
+        items
+    }
+
+    /**
+     * The closest item hit before `max_t` and its distance. `intersect` tests an item
+     * against the ray and returns the distance of a hit closer than the given one,
+     * nearer nodes are visited first so that farther ones can be skipped.
+     */
+    pub fn nearest_hit<F>(&self, ray: &Ray, max_t: f32, mut intersect: F) -> Option<(usize, f32)>
+    where
+        F: FnMut(usize, f32) -> Option<f32>,
+    {
+        let root = self.nodes.first()?;
+        let mut nearest = None;
+        let mut max_t = max_t;
+        let mut stack = Vec::new();
+        if let Some((near, _)) = ray.intersect_bbox(&root.bounds) {
+            stack.push((0, near));
+        }
+        while let Some((index, near)) = stack.pop() {
+            if near > max_t {
+                continue;
+            }
+            let node = &self.nodes[index];
+            if node.is_leaf() {
+                for &item in &self.items[node.first..node.first + node.count] {
+                    if let Some(t) = intersect(item, max_t).filter(|t| *t <= max_t) {
+                        max_t = t;
+                        nearest = Some((item, t));
+                    }
+                }
+                continue;
+            }
+
+            let mut children: Vec<(usize, f32)> = [node.first, node.first + 1]
+                .into_iter()
+                .filter_map(|child| {
+                    let (near, _) = ray.intersect_bbox(&self.nodes[child].bounds)?;
+                    (near <= max_t).then_some((child, near))
+                })
+                .collect();
+            // Push the farther child first, so the nearer one is popped next.
+            children.sort_by(|a, b| b.1.total_cmp(&a.1));
+            stack.extend(children);
+        }
+        nearest
+    }
+
+    /**
+     * Items of the leaves whose bounds may be visible in `frustum`, a few of which may
+     * be outside. Nodes entirely inside are taken without testing their descendants.
+     */
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<usize> {
+        let mut items = Vec::new();
+        let mut stack: Vec<(usize, bool)> = if self.nodes.is_empty() {
+            Vec::new()
+        } else {
+            vec![(0, false)]
+        };
+        while let Some((index, inside)) = stack.pop() {
+            let node = &self.nodes[index];
+            let inside = inside
+                || match frustum.bbox_containment(&node.bounds) {
+                    Containment::Outside => continue,
+                    Containment::Intersecting => false,
+                    Containment::Inside => true,
+                };
+            if node.is_leaf() {
+                items.extend_from_slice(&self.items[node.first..node.first + node.count]);
+            } else {
+                stack.extend([(node.first, inside), (node.first + 1, inside)]);
+            }
+        }
+        items
+    }
+}
+
+fn union_of<'a>(mut boxes: impl Iterator<Item = &'a Bbox3<f32>>) -> Option<Bbox3<f32>> {
+    let first = *boxes.next()?;
+    Some(boxes.fold(first, |union, bounds| union.union(bounds)))
+}
+
+/**
+ * Reorder `items` so that the first ones form the left child of the cheapest split and
+ * return their number, or `None` when a leaf is cheaper.
+ */
+fn split(
+    items: &mut [usize],
+    bounds: &[Bbox3<f32>],
+    centers: &[Vector3<f32>],
+    node_bounds: &Bbox3<f32>,
+) -> Option<usize> {
+    if items.len() <= 1 {
+        return None;
+    }
+    let center_bounds = Bbox3::from_vector3(&items.iter().map(|&i| &centers[i]).collect());
+    let low = Vector3::new(center_bounds.l, center_bounds.b, center_bounds.n);
+    let extent = Vector3::new(center_bounds.r, center_bounds.t, center_bounds.f) - low;
+    let bin = |axis: usize, item: usize| {
+        let offset = (centers[item][axis] - low[axis]) / extent[axis];
+        ((offset * SAH_BINS as f32) as usize).min(SAH_BINS - 1)
+    };
+
+    // The best (cost, axis, number of left bins).
+    let mut best: Option<(f32, usize, usize)> = None;
+    for axis in (0..3).filter(|&axis| extent[axis] > 0.0) {
+        let mut bins: [(usize, Option<Bbox3<f32>>); SAH_BINS] = [(0, None); SAH_BINS];
+        for &item in items.iter() {
+            let (count, union) = &mut bins[bin(axis, item)];
+            *count += 1;
+            *union = Some(union.map_or(bounds[item], |union| union.union(&bounds[item])));
+        }
+
+        // Sweep from the right for the cost of every right side, then from the left.
+        let area_count = |(count, union): (usize, Option<Bbox3<f32>>)| {
+            union.map_or(0.0, |union| union.surface_area()) * count as f32
+        };
+        let mut right_costs = [0.0; SAH_BINS];
+        let mut right = (0, None);
+        for i in (1..SAH_BINS).rev() {
+            right = merge(right, bins[i]);
+            right_costs[i] = area_count(right);
+        }
+        let mut left = (0, None);
+        for i in 1..SAH_BINS {
+            left = merge(left, bins[i - 1]);
+            let cost = area_count(left) + right_costs[i];
+            if left.0 > 0 && left.0 < items.len() && best.is_none_or(|(best, ..)| cost < best) {
+                best = Some((cost, axis, i));
+            }
+        }
+    }
+
+    let area = node_bounds.surface_area();
+    let leaf_cost = items.len() as f32;
+    match best {
+        Some((cost, axis, left_bins))
+            if items.len() > MAX_LEAF_SIZE
+                || area <= 0.0
+                || TRAVERSAL_COST + cost / area < leaf_cost =>
+        {
+            Some(partition(items, |item| bin(axis, item) < left_bins))
+        }
+        // All centers coincide, halve large leaves anyway.
+        None if items.len() > MAX_LEAF_SIZE => Some(items.len() / 2),
+        _ => None,
+    }
+}
+
+fn merge(
+    (count, union): (usize, Option<Bbox3<f32>>),
+    (other_count, other): (usize, Option<Bbox3<f32>>),
+) -> (usize, Option<Bbox3<f32>>) {
+    let union = match (union, other) {
+        (Some(union), Some(other)) => Some(union.union(&other)),
+        (union, other) => union.or(other),
+    };
+    (count + other_count, union)
+}
+
+/**
+ * Move the items satisfying `left` to the front and return their number.
+ */
+fn partition(items: &mut [usize], left: impl Fn(usize) -> bool) -> usize {
+    let mut count = 0;
+    for i in 0..items.len() {
+        if left(items[i]) {
+            items.swap(i, count);
+            count += 1;
+        }
+    }
+    count
+}
+
+/**
+ * A ray hit on a triangle.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TriangleHit {
+    /**
+     * Index of the triangle, the indices `3 * triangle..3 * triangle + 3` of the primitive.
+     */
+    pub triangle: usize,
+
+    pub t: f32,
+
+    /**
+     * Weights of the three corners of the triangle, as with `compute_barycentric_coordinate`.
+     */
+    pub bary_coord: Vector3<f32>,
+}
+
+/**
+ * A hierarchy over the triangles of a primitive, in the space of its vertices.
+ */
+#[derive(Debug, Clone)]
+pub struct TriangleBvh {
+    pub positions: Vec<Vector3<f32>>,
+
+    pub triangles: Vec<[usize; 3]>,
+
+    pub bvh: Bvh,
+}
+
+impl TriangleBvh {
+    pub fn new(primitive: &Primitive) -> Self {
+        let mut triangles = Self {
+            positions: Vec::new(),
+            triangles: primitive
+                .indices
+                .chunks_exact(3)
+                .map(|triangle| [0, 1, 2].map(|i| triangle[i] as usize))
+                .collect(),
+            bvh: Bvh::default(),
+        };
+        triangles.set_positions(primitive);
+        triangles.bvh = Bvh::build(&triangles.triangle_bounds());
+        triangles
+    }
+
+    /**
+     * Follow vertices that moved, such as after morphing or skinning `primitive`,
+     * whose vertex count and indices must not have changed.
+     */
+    pub fn refit(&mut self, primitive: &Primitive) {
+        self.set_positions(primitive);
+        self.bvh.refit(&self.triangle_bounds());
+    }
+
+    fn set_positions(&mut self, primitive: &Primitive) {
+        self.positions = primitive
+            .vertices
+            .iter()
+            .map(|vertex| vertex.position.xyz())
+            .collect();
+    }
+
+    fn triangle_bounds(&self) -> Vec<Bbox3<f32>> {
+        self.triangles
+            .iter()
+            .map(|triangle| {
+                Bbox3::from_vector3(&triangle.iter().map(|&i| &self.positions[i]).collect())
+            })
+            .collect()
+    }
+
+    /**
+     * Bounds of all triangles, `None` without any.
+     */
+    pub fn bounds(&self) -> Option<Bbox3<f32>> {
+        self.bvh.nodes.first().map(|root| root.bounds)
+    }
+
+    fn intersect(&self, ray: &Ray, triangle: usize) -> Option<TriangleHit> {
+        let [p_0, p_1, p_2] = self.triangles[triangle].map(|i| self.positions[i]);
+        let (t, bary_coord) = ray.intersect_triangle(&p_0, &p_1, &p_2)?;
+        Some(TriangleHit {
+            triangle,
+            t,
+            bary_coord,
+        })
+    }
+
+    /**
+     * Every triangle the ray hits, nearest first.
+     */
+    pub fn intersect_ray(&self, ray: &Ray) -> Vec<TriangleHit> {
+        let mut hits: Vec<TriangleHit> = self
+            .bvh
+            .intersect_ray(ray, f32::INFINITY)
+            .into_iter()
+            .filter_map(|triangle| self.intersect(ray, triangle))
+            .collect();
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+        hits
+    }
+
+    pub fn nearest_hit(&self, ray: &Ray, max_t: f32) -> Option<TriangleHit> {
+        let mut nearest = None;
+        self.bvh.nearest_hit(ray, max_t, |triangle, max_t| {
+            let hit = self.intersect(ray, triangle).filter(|hit| hit.t <= max_t)?;
+            nearest = Some(hit);
+            Some(hit.t)
+        });
+        nearest
+    }
+
+    /**
+     * Triangles that may be visible in `frustum`, including some outside of it.
+     */
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<usize> {
+        self.bvh.query_frustum(frustum)
+    }
+}
+
+/**
+ * A ray hit on a triangle of a scene.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct SceneHit {
+    pub instance: Instance,
+
+    /**
+     * Index of the primitive in the mesh of the instance.
+     */
+    pub primitive: usize,
+
+    /**
+     * The hit in the space of the mesh, whose distance is also the one along the world ray.
+     */
+    pub hit: TriangleHit,
+}
+
+/**
+ * A hierarchy over the instances of a scene graph, with one over the triangles of
+ * each primitive in the space of its mesh.
+ */
+#[derive(Debug, Clone)]
+pub struct SceneBvh {
+    pub graph: Option<usize>,
+
+    pub instances: Vec<Instance>,
+
+    /**
+     * The triangles of every primitive of every mesh.
+     */
+    pub meshes: Vec<Vec<TriangleBvh>>,
+
+    /**
+     * Over the world bounds of `instances`.
+     */
+    pub bvh: Bvh,
+}
+
+impl SceneBvh {
+    pub fn new(scene: &Scene, graph: Option<usize>) -> Self {
+        let meshes = scene
+            .meshes
+            .iter()
+            .map(|mesh| mesh.primitives.iter().map(TriangleBvh::new).collect())
+            .collect();
+        let mut bvh = Self {
+            graph,
+            instances: scene.instances(graph),
+            meshes,
+            bvh: Bvh::default(),
+        };
+        bvh.bvh = Bvh::build(&bvh.instance_bounds());
+        bvh
+    }
+
+    /**
+     * Follow nodes that moved, such as after applying an animation, and primitives
+     * refitted in `meshes`. The nodes placing meshes must be the same as when built.
+     */
+    pub fn refit(&mut self, scene: &Scene) {
+        let instances = scene.instances(self.graph);
+        assert_eq!(
+            instances.len(),
+            self.instances.len(),
+            "the instances of the scene changed"
+        );
+        self.instances = instances;
+        self.bvh.refit(&self.instance_bounds());
+    }
+
+    fn instance_bounds(&self) -> Vec<Bbox3<f32>> {
+        self.instances
+            .iter()
+            .map(|instance| {
+                let local = union_of(
+                    self.meshes[instance.mesh]
+                        .iter()
+                        .filter_map(|triangles| triangles.bvh.nodes.first())
+                        .map(|root| &root.bounds),
+                );
+                match local {
+                    Some(local) => transform_bounds(&local, &instance.world),
+                    // Nothing to hit, but the hierarchy needs a place for it.
+                    None => {
+                        let origin = instance.world.column(3).xyz();
+                        Bbox3::from_vector3(&vec![&origin])
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn local_ray(ray: &Ray, world: &Matrix4<f32>) -> Option<Ray> {
+        Some(ray.transform(&world.try_inverse()?))
+    }
+
+    /**
+     * Every triangle the ray hits, nearest first.
+     */
+    pub fn intersect_ray(&self, ray: &Ray) -> Vec<SceneHit> {
+        let mut hits = Vec::new();
+        for index in self.bvh.intersect_ray(ray, f32::INFINITY) {
+            let instance = self.instances[index];
+            let Some(local) = Self::local_ray(ray, &instance.world) else {
+                continue;
+            };
+            for (primitive, triangles) in self.meshes[instance.mesh].iter().enumerate() {
+                hits.extend(
+                    triangles
+                        .intersect_ray(&local)
+                        .into_iter()
+                        .map(|hit| SceneHit {
+                            instance,
+                            primitive,
+                            hit,
+                        }),
+                );
+            }
+        }
+        hits.sort_by(|a, b| a.hit.t.total_cmp(&b.hit.t));
+        hits
+    }
+
+    /**
+     * The closest triangle the ray hits, for picking.
+     */
+    pub fn nearest_hit(&self, ray: &Ray) -> Option<SceneHit> {
+        let mut nearest = None;
+        self.bvh.nearest_hit(ray, f32::INFINITY, |index, max_t| {
+            let instance = self.instances[index];
+            let local = Self::local_ray(ray, &instance.world)?;
+            let mut max_t = max_t;
+            let mut found = None;
+            for (primitive, triangles) in self.meshes[instance.mesh].iter().enumerate() {
+                if let Some(hit) = triangles.nearest_hit(&local, max_t) {
+                    max_t = hit.t;
+                    found = Some(SceneHit {
+                        instance,
+                        primitive,
+                        hit,
+                    });
+                }
+            }
+            nearest = found.or(nearest);
+            Some(found?.hit.t)
+        });
+        nearest
+    }
+
+    /**
+     * Instances that may be visible in `frustum`, including some outside of it.
+     */
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<Instance> {
+        self.bvh
+            .query_frustum(frustum)
+            .into_iter()
+            .map(|index| self.instances[index])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::random::{point_in_bbox3, seeded_rng},
+        scene::{
+            mesh::{Mesh, Vertex},
+            node::{Node, SceneGraph, Transform},
+        },
+    };
+    use nalgebra::{Point3, UnitQuaternion};
+
+    /**
+     * Scattered small triangles in the cube `[-1, 1]^3`.
+     */
+    fn scattered(count: usize) -> Primitive {
+        let mut rng = seeded_rng(7);
+        let cube = Bbox3::new(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0);
+        let small = Bbox3::new(-0.1, 0.1, -0.1, 0.1, -0.1, 0.1);
+        let mut vertices = Vec::new();
+        for _ in 0..count {
+            let center = point_in_bbox3(&mut rng, &cube);
+            for _ in 0..3 {
+                vertices.push(Vertex::new(center + point_in_bbox3(&mut rng, &small)));
+            }
+        }
+        Primitive {
+            indices: (0..vertices.len() as u32).collect(),
+            vertices,
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        }
+    }
+
+    fn brute_force_nearest(triangles: &TriangleBvh, ray: &Ray) -> Option<TriangleHit> {
+        (0..triangles.triangles.len())
+            .filter_map(|triangle| triangles.intersect(ray, triangle))
+            .min_by(|a, b| a.t.total_cmp(&b.t))
+    }
+
+    #[test]
+    fn test_build() {
+        let triangles = TriangleBvh::new(&scattered(500));
+        let bvh = &triangles.bvh;
+        let mut items = bvh.items.clone();
+        items.sort();
+        assert_eq!(items, (0..500).collect::<Vec<_>>());
+
+        // Every node encloses its children and items, leaves stay small.
+        let bounds = triangles.triangle_bounds();
+        let encloses = |outer: &Bbox3<f32>, inner: &Bbox3<f32>| {
+            outer.l <= inner.l
+                && inner.r <= outer.r
+                && outer.b <= inner.b
+                && inner.t <= outer.t
+                && outer.n <= inner.n
+                && inner.f <= outer.f
+        };
+        for node in &bvh.nodes {
+            if node.is_leaf() {
+                assert!(node.count <= MAX_LEAF_SIZE);
+                for &item in &bvh.items[node.first..node.first + node.count] {
+                    assert!(encloses(&node.bounds, &bounds[item]));
+                }
+            } else {
+                for child in &bvh.nodes[node.first..node.first + 2] {
+                    assert!(encloses(&node.bounds, &child.bounds));
+                }
+            }
+        }
+        assert!(bvh.nodes.len() > 500 / MAX_LEAF_SIZE);
+
+        assert!(Bvh::build(&[]).nodes.is_empty());
+        let point = Bbox3::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        assert_eq!(Bvh::build(&[point; 20]).items.len(), 20);
+    }
+
+    #[test]
+    fn test_ray_queries() {
+        let primitive = scattered(300);
+        let mut triangles = TriangleBvh::new(&primitive);
+        let mut rng = seeded_rng(11);
+        let around = Bbox3::new(-3.0, 3.0, -3.0, 3.0, -3.0, 3.0);
+        let target = Bbox3::new(-0.5, 0.5, -0.5, 0.5, -0.5, 0.5);
+        let rays: Vec<Ray> = (0..200)
+            .map(|_| {
+                let origin = point_in_bbox3(&mut rng, &around);
+                let direction = point_in_bbox3(&mut rng, &target) - origin;
+                Ray::new(origin.into(), direction)
+            })
+            .collect();
+
+        let check = |triangles: &TriangleBvh| {
+            let mut hit_count = 0;
+            for ray in &rays {
+                let expected = brute_force_nearest(triangles, ray);
+                assert_eq!(triangles.nearest_hit(ray, f32::INFINITY), expected);
+                let all = triangles.intersect_ray(ray);
+                assert_eq!(all.first().copied(), expected);
+                hit_count += all.len();
+            }
+            assert!(hit_count > 20);
+        };
+        check(&triangles);
+
+        // Move every vertex, as an animation would.
+        let mut moved = primitive.clone();
+        for vertex in &mut moved.vertices {
+            vertex.position.x = vertex.position.x * 0.5 + vertex.position.y;
+        }
+        triangles.refit(&moved);
+        check(&triangles);
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), -Vector3::z());
+        assert_eq!(triangles.nearest_hit(&ray, 1.0), None);
+    }
+
+    #[test]
+    fn test_frustum_query() {
+        let triangles = TriangleBvh::new(&scattered(400));
+        // The box x, y in [-0.5, 0.5], z in [-1, 1] seen from above.
+        let projection = Matrix4::new_orthographic(-0.5, 0.5, -0.5, 0.5, -1.0, 1.0);
+        let frustum = Frustum::from_matrix(&projection);
+
+        let found = triangles.query_frustum(&frustum);
+        let bounds = triangles.triangle_bounds();
+        for (triangle, bounds) in bounds.iter().enumerate() {
+            if frustum.intersects_bbox(bounds) {
+                assert!(found.contains(&triangle));
+            }
+        }
+        assert!(!found.is_empty() && found.len() < 400);
+    }
+
+    #[test]
+    fn test_scene() {
+        let quad = Primitive {
+            vertices: [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                .map(|(x, y)| Vertex::new(Vector3::new(x, y, 0.0)))
+                .to_vec(),
+            indices: vec![0, 1, 2, 0, 2, 3],
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        };
+        let node = |z: f32| Node {
+            name: None,
+            transform: Transform::Trs {
+                translation: Vector3::new(0.0, 0.0, z),
+                rotation: UnitQuaternion::identity(),
+                scale: Vector3::repeat(0.5),
+            },
+            mesh: Some(0),
+            camera: None,
+            light: None,
+            skin: None,
+            weights: None,
+            children: Vec::new(),
+        };
+        let mut scene = Scene {
+            meshes: vec![Mesh {
+                name: None,
+                primitives: vec![quad],
+                weights: Vec::new(),
+            }],
+            textures: Vec::new(),
+            materials: Vec::new(),
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            skins: Vec::new(),
+            animations: Vec::new(),
+            nodes: vec![node(-1.0), node(-3.0)],
+            graphs: vec![SceneGraph {
+                name: None,
+                roots: vec![0, 1],
+            }],
+            default_graph: Some(0),
+        };
+        let mut bvh = SceneBvh::new(&scene, None);
+
+        let ray = Ray::new(Point3::new(0.2, 0.1, 0.0), -Vector3::z());
+        let hit = bvh.nearest_hit(&ray).unwrap();
+        assert_eq!(hit.instance.node, Some(0));
+        assert_eq!(hit.primitive, 0);
+        assert!((hit.hit.t - 1.0).abs() < 1e-6);
+        let nodes: Vec<_> = bvh
+            .intersect_ray(&ray)
+            .iter()
+            .map(|hit| hit.instance.node)
+            .collect();
+        assert_eq!(nodes, [Some(0), Some(1)]);
+        // The quads are half size, this ray passes beside them.
+        assert!(bvh
+            .nearest_hit(&Ray::new(Point3::new(0.7, 0.0, 0.0), -Vector3::z()))
+            .is_none());
+
+        // Move the first quad aside and behind the second one.
+        scene.nodes[0].transform = node(-5.0).transform;
+        bvh.refit(&scene);
+        let hit = bvh.nearest_hit(&ray).unwrap();
+        assert_eq!(hit.instance.node, Some(1));
+        assert!((hit.hit.t - 3.0).abs() < 1e-6);
+
+        let frustum =
+            Frustum::from_matrix(&Matrix4::new_orthographic(-1.0, 1.0, -1.0, 1.0, 2.0, 4.0));
+        let visible: Vec<_> = bvh
+            .query_frustum(&frustum)
+            .iter()
+            .map(|instance| instance.node)
+            .collect();
+        assert_eq!(visible, [Some(1)]);
+    }
+}
@@ -0,0 +1,727 @@
+use crate::{
+    common::basetype::{Bbox3, Viewport},
+    scene::ray::Ray,
+};
+use nalgebra::{Isometry3, Matrix4, Point3, Translation3, Unit, UnitQuaternion, Vector2, Vector3};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Projection {
+    Perspective {
+        /**
+         * Vertical field of view in radians.
+         */
+        yfov: f32,
+
+        /**
+         * Width over height of the field of view, the viewport's when `None`.
+         */
+        aspect_ratio: Option<f32>,
+
+        znear: f32,
+
+        /**
+         * Infinite when `None`.
+         */
+        zfar: Option<f32>,
+    },
+
+    Orthographic {
+        /**
+         * Half of the horizontal extent.
+         */
+        xmag: f32,
+
+        /**
+         * Half of the vertical extent.
+         */
+        ymag: f32,
+
+        znear: f32,
+
+        zfar: f32,
+    },
+}
+
+impl Projection {
+    /**
+     * The projection matrix for a viewport of `viewport_aspect` (width over height).
+     *
+     * When the authored aspect ratio differs from the viewport, the field of view is
+     * widened along one axis so that the authored view stays fully visible undistorted.
+     */
+    pub fn matrix(&self, viewport_aspect: f32) -> Matrix4<f32> {
+        match *self {
+            Projection::Perspective {
+                yfov,
+                aspect_ratio,
+                znear,
+                zfar,
+            } => {
+                let camera_aspect = aspect_ratio.unwrap_or(viewport_aspect);
+                let mut tan_half_y = (yfov / 2.0).tan();
+                if viewport_aspect < camera_aspect {
+                    // Keep the horizontal field of view instead.
+                    tan_half_y *= camera_aspect / viewport_aspect;
+                }
+
+                let mut m = Matrix4::zeros();
+                m[(0, 0)] = 1.0 / (viewport_aspect * tan_half_y);
+                m[(1, 1)] = 1.0 / tan_half_y;
+                m[(3, 2)] = -1.0;
+                match zfar {
+                    Some(zfar) => {
+                        m[(2, 2)] = (zfar + znear) / (znear - zfar);
+                        m[(2, 3)] = 2.0 * zfar * znear / (znear - zfar);
+                    }
+                    None => {
+                        m[(2, 2)] = -1.0;
+                        m[(2, 3)] = -2.0 * znear;
+                    }
+                }
+                m
+            }
+
+            Projection::Orthographic {
+                xmag,
+                ymag,
+                znear,
+                zfar,
+            } => {
+                let (xmag, ymag) = if viewport_aspect * ymag >= xmag {
+                    (ymag * viewport_aspect, ymag)
+                } else {
+                    (xmag, xmag / viewport_aspect)
+                };
+
+                let mut m = Matrix4::identity();
+                m[(0, 0)] = 1.0 / xmag;
+                m[(1, 1)] = 1.0 / ymag;
+                m[(2, 2)] = 2.0 / (znear - zfar);
+                m[(2, 3)] = (zfar + znear) / (znear - zfar);
+                m
+            }
+        }
+    }
+
+    /**
+     * Like `matrix`, but mapping the near plane to a depth of 1 and the far plane, or
+     * infinity, to 0. Floats are densest near 0, where perspective crowds distant
+     * depths, so far surfaces stay apart where `matrix` would merge them. Draw into a
+     * `RenderPass::with_reversed_z` pass with `DepthCompare::Greater`.
+     */
+    pub fn reversed_z_matrix(&self, viewport_aspect: f32) -> Matrix4<f32> {
+        reverse_z(&self.matrix(viewport_aspect))
+    }
+}
+
+/**
+ * Remap the depth `z / w` of `projection` from [-1, 1] to [1, 0]. The depth row of an
+ * infinite perspective becomes `(0, 0, 0, znear)` exactly.
+ */
+fn reverse_z(projection: &Matrix4<f32>) -> Matrix4<f32> {
+    let mut m = *projection;
+    let row = (projection.row(3) - projection.row(2)) / 2.0;
+    m.set_row(2, &row);
+    m
+}
+
+#[derive(Debug, Clone)]
+pub struct Camera {
+    pub name: Option<String>,
+
+    pub projection: Projection,
+}
+
+/**
+ * A camera placed in the world, ready for drawing.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct View {
+    pub eye: Point3<f32>,
+
+    pub view: Matrix4<f32>,
+
+    pub projection: Matrix4<f32>,
+
+    /**
+     * Whether `projection` is reversed Z, see `Projection::reversed_z_matrix`.
+     */
+    pub reversed_z: bool,
+}
+
+impl View {
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        self.projection * self.view
+    }
+
+    /**
+     * The same view with the depth of its projection reversed, see
+     * `Projection::reversed_z_matrix`. A reversed view is returned as is.
+     */
+    pub fn with_reversed_z(self) -> Self {
+        if self.reversed_z {
+            return self;
+        }
+        Self {
+            projection: reverse_z(&self.projection),
+            reversed_z: true,
+            ..self
+        }
+    }
+
+    /**
+     * The direction the view looks at, its -Z axis in world space.
+     */
+    pub fn forward(&self) -> Vector3<f32> {
+        -Vector3::new(self.view[(2, 0)], self.view[(2, 1)], self.view[(2, 2)]).normalize()
+    }
+
+    /**
+     * The ray through `pixel`, in image coordinates whose origin is the top left corner
+     * of `viewport` with pixel centers at half units. The ray starts at the eye, or for
+     * orthographic views in the plane of the eye, and has a unit direction.
+     * `None` when the view projection is not invertible.
+     */
+    pub fn ray_from_pixel(&self, pixel: &Vector2<f32>, viewport: &Viewport) -> Option<Ray> {
+        let inverse = self.view_projection().try_inverse()?;
+        let ndc = Vector2::new(
+            2.0 * pixel.x / viewport.width as f32 - 1.0,
+            1.0 - 2.0 * pixel.y / viewport.height as f32,
+        );
+        // Depths of 1/4 and 1/2 are finite for infinite and reversed projections alike.
+        let unproject = |z: f32| {
+            let p = inverse * ndc.push(z).push(1.0);
+            p.xyz() / p.w
+        };
+        let (a, b) = (unproject(0.25), unproject(0.5));
+        let forward = self.forward();
+        let mut direction = (b - a).try_normalize(0.0)?;
+        if direction.dot(&forward) < 0.0 {
+            direction = -direction;
+        }
+
+        let perspective = self.projection[(3, 2)] != 0.0;
+        let origin = if perspective {
+            self.eye
+        } else {
+            Point3::from(a - direction * (a - self.eye.coords).dot(&direction))
+        };
+        Some(Ray::new(origin, direction))
+    }
+
+    /**
+     * Look at `target` from `eye` through `projection`.
+     */
+    pub fn look_at(
+        eye: Point3<f32>,
+        target: Point3<f32>,
+        up: Vector3<f32>,
+        projection: &Projection,
+        viewport_aspect: f32,
+    ) -> Self {
+        Self {
+            eye,
+            view: Matrix4::look_at_rh(&eye, &target, &up),
+            projection: projection.matrix(viewport_aspect),
+            reversed_z: false,
+        }
+    }
+
+    /**
+     * The view of a camera node whose world matrix is `world`.
+     * glTF cameras look down their local -Z axis with +Y up.
+     */
+    pub fn from_node(world: &Matrix4<f32>, projection: &Projection, viewport_aspect: f32) -> Self {
+        Self {
+            eye: Point3::from(world.fixed_view::<3, 1>(0, 3).into_owned()),
+            view: world.try_inverse().unwrap_or_else(Matrix4::identity),
+            projection: projection.matrix(viewport_aspect),
+            reversed_z: false,
+        }
+    }
+
+    /**
+     * Look at the center of `bounds` from `direction`, close enough for the bounding
+     * sphere to fill the narrower field of view, with near and far planes hugging it.
+     */
+    pub fn framing(
+        bounds: &Bbox3<f32>,
+        direction: &Vector3<f32>,
+        yfov: f32,
+        viewport_aspect: f32,
+    ) -> Self {
+        let center = Point3::new(
+            (bounds.l + bounds.r) / 2.0,
+            (bounds.b + bounds.t) / 2.0,
+            (bounds.n + bounds.f) / 2.0,
+        );
+        let radius = (Vector3::new(
+            bounds.r - bounds.l,
+            bounds.t - bounds.b,
+            bounds.f - bounds.n,
+        )
+        .norm()
+            / 2.0)
+            .max(f32::EPSILON);
+
+        let direction = direction
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(Vector3::z());
+        let xfov = 2.0 * ((yfov / 2.0).tan() * viewport_aspect).atan();
+        let distance = radius / (yfov.min(xfov) / 2.0).sin();
+        let far = distance + radius;
+        let up = if direction.cross(&Vector3::y()).norm() > f32::EPSILON {
+            Vector3::y()
+        } else {
+            Vector3::z()
+        };
+
+        Self::look_at(
+            center + direction * distance,
+            center,
+            up,
+            &Projection::Perspective {
+                yfov,
+                aspect_ratio: None,
+                znear: (distance - radius).max(far * 0.001),
+                zfar: Some(far),
+            },
+            viewport_aspect,
+        )
+    }
+}
+
+/**
+ * Input deltas of one frame, from whichever device drives the camera.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CameraInput {
+    /**
+     * Yaw and pitch in radians. Positive values turn the camera right and up.
+     */
+    pub rotate: Vector2<f32>,
+
+    /**
+     * Movement along the right and up axes of the camera, in units of the distance
+     * to the target for orbit controllers and in world units for fly controllers.
+     */
+    pub pan: Vector2<f32>,
+
+    /**
+     * Orbit controllers scale their distance by `exp(-zoom)`,
+     * fly controllers move forward by `zoom`.
+     */
+    pub zoom: f32,
+
+    /**
+     * Movement along the right, up and forward axes in world units,
+     * ignored by orbit controllers.
+     */
+    pub movement: Vector3<f32>,
+}
+
+/**
+ * Turns input deltas into a camera placement, shared by interactive and headless tools.
+ */
+pub trait CameraController {
+    fn update(&mut self, input: &CameraInput);
+
+    fn eye(&self) -> Point3<f32>;
+
+    /**
+     * Rotation from camera space, looking down -Z with +Y up, to world space.
+     */
+    fn orientation(&self) -> UnitQuaternion<f32>;
+
+    fn view_matrix(&self) -> Matrix4<f32> {
+        Isometry3::from_parts(Translation3::from(self.eye().coords), self.orientation())
+            .inverse()
+            .to_homogeneous()
+    }
+
+    fn view(&self, projection: &Projection, viewport_aspect: f32) -> View {
+        View {
+            eye: self.eye(),
+            view: self.view_matrix(),
+            projection: projection.matrix(viewport_aspect),
+            reversed_z: false,
+        }
+    }
+}
+
+/**
+ * Largest cosine between the view direction and the up axis, keeping cameras off the
+ * poles where yaw is undefined.
+ */
+const MAX_POLE_COSINE: f32 = 0.999;
+
+/**
+ * Circles `target` at `distance`, turntable style around `up` or with an arcball.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitController {
+    pub target: Point3<f32>,
+
+    pub distance: f32,
+
+    pub orientation: UnitQuaternion<f32>,
+
+    pub up: Vector3<f32>,
+
+    /**
+     * Zooming stops this close to the target.
+     */
+    pub min_distance: f32,
+}
+
+impl OrbitController {
+    pub fn look_at(eye: Point3<f32>, target: Point3<f32>, up: Vector3<f32>) -> Self {
+        let offset = eye - target;
+        Self {
+            target,
+            distance: offset.norm(),
+            orientation: UnitQuaternion::face_towards(&offset, &up),
+            up: up.normalize(),
+            min_distance: offset.norm() * 1e-3,
+        }
+    }
+
+    /**
+     * Rotate as if dragging the point under `from` to `to` on a sphere around the target,
+     * both in normalized device coordinates.
+     */
+    pub fn arcball(&mut self, from: &Vector2<f32>, to: &Vector2<f32>) {
+        let on_sphere = |p: &Vector2<f32>| {
+            let length_squared = p.norm_squared();
+            if length_squared <= 1.0 {
+                p.push((1.0 - length_squared).sqrt())
+            } else {
+                p.normalize().push(0.0)
+            }
+        };
+        // Turning the scene one way turns the camera around it the other way.
+        if let Some(rotation) = UnitQuaternion::rotation_between(&on_sphere(to), &on_sphere(from)) {
+            self.orientation *= rotation;
+        }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn update(&mut self, input: &CameraInput) {
+        let yaw = UnitQuaternion::from_axis_angle(&Unit::new_normalize(self.up), input.rotate.x);
+        let pitch = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), -input.rotate.y);
+        let orientation = yaw * self.orientation;
+        let pitched = orientation * pitch;
+        self.orientation = if (pitched * Vector3::z()).dot(&self.up).abs() < MAX_POLE_COSINE {
+            pitched
+        } else {
+            orientation
+        };
+
+        self.target += self.orientation * input.pan.push(0.0) * self.distance;
+        self.distance = (self.distance * (-input.zoom).exp()).max(self.min_distance);
+    }
+
+    fn eye(&self) -> Point3<f32> {
+        self.target + self.orientation * Vector3::z() * self.distance
+    }
+
+    fn orientation(&self) -> UnitQuaternion<f32> {
+        self.orientation
+    }
+}
+
+/**
+ * Moves freely like a first person camera, with +Y up.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlyController {
+    pub position: Point3<f32>,
+
+    /**
+     * Turn around +Y in radians, zero looking down -Z.
+     */
+    pub yaw: f32,
+
+    /**
+     * Look up from the horizon in radians.
+     */
+    pub pitch: f32,
+}
+
+impl FlyController {
+    pub fn look_at(eye: Point3<f32>, target: Point3<f32>) -> Self {
+        let direction = (target - eye)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(-Vector3::z());
+        Self {
+            position: eye,
+            yaw: (-direction.x).atan2(-direction.z),
+            pitch: direction.y.clamp(-MAX_POLE_COSINE, MAX_POLE_COSINE).asin(),
+        }
+    }
+}
+
+impl CameraController for FlyController {
+    fn update(&mut self, input: &CameraInput) {
+        let max_pitch = MAX_POLE_COSINE.asin();
+        self.yaw -= input.rotate.x;
+        self.pitch = (self.pitch + input.rotate.y).clamp(-max_pitch, max_pitch);
+
+        let local = Vector3::new(
+            input.movement.x + input.pan.x,
+            input.movement.y + input.pan.y,
+            -(input.movement.z + input.zoom),
+        );
+        self.position += self.orientation() * local;
+    }
+
+    fn eye(&self) -> Point3<f32> {
+        self.position
+    }
+
+    fn orientation(&self) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.yaw)
+            * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), self.pitch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector4;
+
+    #[test]
+    fn test_perspective_matrix() {
+        let finite = Projection::Perspective {
+            yfov: 1.0,
+            aspect_ratio: None,
+            znear: 0.1,
+            zfar: Some(100.0),
+        };
+        let expected = Matrix4::new_perspective(1.5, 1.0, 0.1, 100.0);
+        assert!((finite.matrix(1.5) - expected).norm() < 1e-5);
+
+        // Points on the far plane of an infinite projection approach a depth of 1.
+        let infinite = Projection::Perspective {
+            yfov: 1.0,
+            aspect_ratio: None,
+            znear: 0.1,
+            zfar: None,
+        };
+        let p = infinite.matrix(1.0) * Vector4::new(0.0, 0.0, -1e6, 1.0);
+        assert!((p.z / p.w - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_aspect_ratio_fit() {
+        let projection = Projection::Perspective {
+            yfov: 1.0,
+            aspect_ratio: Some(2.0),
+            znear: 0.1,
+            zfar: Some(100.0),
+        };
+        // A wider viewport keeps the vertical field of view.
+        let wide = projection.matrix(3.0);
+        assert!((wide[(1, 1)] - 1.0 / 0.5_f32.tan()).abs() < 1e-5);
+        // A narrower viewport keeps the horizontal field of view.
+        let narrow = projection.matrix(1.0);
+        let authored = projection.matrix(2.0);
+        assert!((narrow[(0, 0)] - authored[(0, 0)]).abs() < 1e-5);
+
+        let orthographic = Projection::Orthographic {
+            xmag: 2.0,
+            ymag: 1.0,
+            znear: 0.0,
+            zfar: 10.0,
+        };
+        let m = orthographic.matrix(1.0);
+        assert_eq!((m[(0, 0)], m[(1, 1)]), (0.5, 0.5));
+    }
+
+    #[test]
+    fn test_framing() {
+        let bounds = Bbox3::new(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0);
+        let view = View::framing(&bounds, &Vector3::new(0.0, 0.0, 1.0), 1.0, 2.0);
+        let matrix = view.view_projection();
+
+        // The corners of the box stay inside of the clip volume.
+        for x in [-1.0, 1.0] {
+            for y in [-1.0, 1.0] {
+                for z in [-1.0, 1.0] {
+                    let p = matrix * Vector4::new(x, y, z, 1.0);
+                    let ndc = p.xyz() / p.w;
+                    assert!(ndc.iter().all(|c| c.abs() <= 1.0 + 1e-4), "{:?}", ndc);
+                }
+            }
+        }
+        assert!(view.eye.z > 1.0);
+    }
+
+    #[test]
+    fn test_reversed_z() {
+        let finite = Projection::Perspective {
+            yfov: 1.0,
+            aspect_ratio: None,
+            znear: 0.1,
+            zfar: Some(100.0),
+        };
+        let depth = |m: Matrix4<f32>, z: f32| {
+            let p = m * Vector4::new(0.0, 0.0, z, 1.0);
+            p.z / p.w
+        };
+        let m = finite.reversed_z_matrix(1.0);
+        assert!((depth(m, -0.1) - 1.0).abs() < 1e-5);
+        assert!(depth(m, -100.0).abs() < 1e-6);
+        assert!(depth(m, -1.0) > depth(m, -2.0));
+
+        let infinite = Projection::Perspective {
+            yfov: 1.0,
+            aspect_ratio: None,
+            znear: 0.01,
+            zfar: None,
+        };
+        let m = infinite.reversed_z_matrix(1.0);
+        assert_eq!(depth(m, -0.01), 1.0);
+        assert!(depth(m, -1e6) < 1e-7);
+        // Surfaces a thousand units away and half a unit apart.
+        let (near, far) = (-1000.0, -1000.5);
+        assert!(depth(m, near) > depth(m, far));
+        let usual = infinite.matrix(1.0);
+        assert_eq!(depth(usual, near), depth(usual, far));
+
+        let orthographic = Projection::Orthographic {
+            xmag: 1.0,
+            ymag: 1.0,
+            znear: 1.0,
+            zfar: 3.0,
+        };
+        let m = orthographic.reversed_z_matrix(1.0);
+        assert_eq!((depth(m, -1.0), depth(m, -3.0)), (1.0, 0.0));
+    }
+
+    #[test]
+    fn test_ray_from_pixel() {
+        let viewport = Viewport::new(200, 100);
+        let eye = Point3::new(1.0, 2.0, 5.0);
+        let target = Point3::new(0.0, 0.0, 0.0);
+        let perspective = Projection::Perspective {
+            yfov: 1.0,
+            aspect_ratio: None,
+            znear: 0.1,
+            zfar: None,
+        };
+        let orthographic = Projection::Orthographic {
+            xmag: 2.0,
+            ymag: 1.0,
+            znear: 0.1,
+            zfar: 10.0,
+        };
+        for projection in [perspective, orthographic] {
+            for view in [
+                View::look_at(eye, target, Vector3::y(), &projection, 2.0),
+                View::look_at(eye, target, Vector3::y(), &projection, 2.0).with_reversed_z(),
+            ] {
+                let center = view
+                    .ray_from_pixel(&Vector2::new(100.0, 50.0), &viewport)
+                    .unwrap();
+                let to_target = (target - eye).normalize();
+                assert!((center.direction - to_target).norm() < 1e-4);
+
+                // A ray through the pixel a point projects to passes through the point.
+                let point = Point3::new(0.5, -0.3, 0.2);
+                let clip = view.view_projection() * point.to_homogeneous();
+                let ndc = clip.xy() / clip.w;
+                let pixel = Vector2::new((ndc.x + 1.0) * 100.0, (1.0 - ndc.y) * 50.0);
+                let ray = view.ray_from_pixel(&pixel, &viewport).unwrap();
+                let t = (point - ray.origin).dot(&ray.direction);
+                assert!(t > 0.0);
+                assert!((ray.at(t) - point).norm() < 1e-4, "{:?}", ray);
+            }
+        }
+    }
+
+    fn assert_matrix_eq(a: &Matrix4<f32>, b: &Matrix4<f32>) {
+        assert!((a - b).norm() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_orbit_controller() {
+        let eye = Point3::new(0.0, 1.0, 4.0);
+        let target = Point3::new(0.0, 1.0, 0.0);
+        let mut orbit = OrbitController::look_at(eye, target, Vector3::y());
+        assert_matrix_eq(
+            &orbit.view_matrix(),
+            &Matrix4::look_at_rh(&eye, &target, &Vector3::y()),
+        );
+
+        // A quarter turn right moves the camera from +Z to +X.
+        orbit.update(&CameraInput {
+            rotate: Vector2::new(std::f32::consts::FRAC_PI_2, 0.0),
+            ..CameraInput::default()
+        });
+        assert!((orbit.eye() - Point3::new(4.0, 1.0, 0.0)).norm() < 1e-5);
+
+        // Pitching up stops short of the pole.
+        orbit.update(&CameraInput {
+            rotate: Vector2::new(0.0, 3.0),
+            ..CameraInput::default()
+        });
+        assert!(orbit.eye().y > 1.0 && orbit.eye().y < 5.0);
+        orbit.update(&CameraInput {
+            rotate: Vector2::new(0.0, 1.5),
+            ..CameraInput::default()
+        });
+        let to_eye = (orbit.eye() - orbit.target).normalize();
+        assert!(to_eye.y < MAX_POLE_COSINE + 1e-6);
+
+        let mut orbit = OrbitController::look_at(eye, target, Vector3::y());
+        orbit.update(&CameraInput {
+            pan: Vector2::new(0.25, 0.0),
+            zoom: 2.0_f32.ln(),
+            ..CameraInput::default()
+        });
+        assert!((orbit.target - Point3::new(1.0, 1.0, 0.0)).norm() < 1e-5);
+        assert!((orbit.distance - 2.0).abs() < 1e-5);
+
+        // Dragging the center of the arcball to the right turns the camera left.
+        let mut orbit = OrbitController::look_at(eye, target, Vector3::y());
+        orbit.arcball(&Vector2::zeros(), &Vector2::new(0.5, 0.0));
+        assert!(orbit.eye().x < 0.0);
+        assert!((orbit.eye() - orbit.target).norm() - 4.0 < 1e-5);
+    }
+
+    #[test]
+    fn test_fly_controller() {
+        let eye = Point3::new(1.0, 2.0, 3.0);
+        let target = Point3::new(-1.0, 0.0, 1.0);
+        let mut fly = FlyController::look_at(eye, target);
+        assert_matrix_eq(
+            &fly.view_matrix(),
+            &Matrix4::look_at_rh(&eye, &target, &Vector3::y()),
+        );
+
+        let distance = (target - eye).norm();
+        fly.update(&CameraInput {
+            movement: Vector3::new(0.0, 0.0, distance),
+            ..CameraInput::default()
+        });
+        assert!((fly.position - target).norm() < 1e-5);
+
+        fly.update(&CameraInput {
+            rotate: Vector2::new(0.0, 10.0),
+            ..CameraInput::default()
+        });
+        assert!(fly.pitch < std::f32::consts::FRAC_PI_2);
+        let view = fly.view(
+            &Projection::Perspective {
+                yfov: 1.0,
+                aspect_ratio: None,
+                znear: 0.1,
+                zfar: None,
+            },
+            1.0,
+        );
+        assert!(view.forward().y > 0.99);
+    }
+}
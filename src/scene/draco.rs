@@ -0,0 +1,2124 @@
+use std::{collections::HashMap, fmt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DracoError {
+    Truncated,
+    Unsupported(&'static str),
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for DracoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DracoError::Truncated => write!(f, "truncated Draco data"),
+            DracoError::Unsupported(what) => write!(f, "unsupported Draco {}", what),
+            DracoError::Corrupt(what) => write!(f, "corrupt Draco {}", what),
+        }
+    }
+}
+
+impl std::error::Error for DracoError {}
+
+type Result<T> = std::result::Result<T, DracoError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+    Position,
+    Normal,
+    Color,
+    TexCoord,
+    Generic,
+}
+
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub attribute_type: AttributeType,
+
+    /**
+     * The id glTF uses to refer to the attribute.
+     */
+    pub unique_id: u32,
+
+    pub components: usize,
+
+    /**
+     * `components` values per point, dequantized.
+     */
+    pub values: Vec<f32>,
+}
+
+impl Attribute {
+    pub fn value(&self, point: usize) -> &[f32] {
+        &self.values[point * self.components..(point + 1) * self.components]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DracoMesh {
+    /**
+     * Three points per triangle.
+     */
+    pub indices: Vec<u32>,
+
+    pub point_count: usize,
+
+    pub attributes: Vec<Attribute>,
+}
+
+impl DracoMesh {
+    pub fn attribute(&self, unique_id: u32) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.unique_id == unique_id)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(DracoError::Truncated)?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.data[self.position..]
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn i8(&mut self) -> Result<i8> {
+        Ok(self.u8()? as i8)
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32> {
+        Ok(f32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte < 0x80 {
+                return Ok(value);
+            }
+        }
+        Err(DracoError::Corrupt("varint"))
+    }
+
+    fn varint_usize(&mut self) -> Result<usize> {
+        usize::try_from(self.varint()?).map_err(|_| DracoError::Corrupt("count"))
+    }
+}
+
+/**
+ * Least significant bit first reader, reading zeros past the end.
+ */
+struct BitReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    fn bits(&mut self, count: u32) -> u32 {
+        let mut value = 0;
+        for i in 0..count {
+            let bit = self
+                .data
+                .get(self.offset >> 3)
+                .map_or(0, |byte| (*byte >> (self.offset & 7)) & 1);
+            self.offset += 1;
+            value |= (bit as u32) << i;
+        }
+        value
+    }
+
+    fn bytes_read(&self) -> usize {
+        self.offset.div_ceil(8)
+    }
+}
+
+/**
+ * Initial `(offset, state)` of an rANS stream, which is read backwards.
+ */
+fn ans_start(data: &[u8], l_base: u32) -> Result<(usize, u32)> {
+    let last = *data.last().ok_or(DracoError::Corrupt("rANS data"))?;
+    let (length, mask) = match last >> 6 {
+        0 => (1, 0x3f),
+        1 => (2, 0x3fff),
+        2 => (3, 0x3f_ffff),
+        _ => (4, 0x3fff_ffff),
+    };
+    let offset = data
+        .len()
+        .checked_sub(length)
+        .ok_or(DracoError::Corrupt("rANS data"))?;
+    let state = data[offset..]
+        .iter()
+        .enumerate()
+        .fold(0u32, |state, (i, byte)| state | (*byte as u32) << (8 * i));
+    Ok((offset, (state & mask) + l_base))
+}
+
+struct RansBitDecoder<'a> {
+    data: &'a [u8],
+    offset: usize,
+    state: u32,
+    probability_zero: u32,
+}
+
+impl<'a> RansBitDecoder<'a> {
+    const L_BASE: u32 = 4096;
+
+    fn new(reader: &mut Reader<'a>) -> Result<Self> {
+        let probability_zero = reader.u8()? as u32;
+        let size = reader.varint_usize()?;
+        let data = reader.bytes(size)?;
+        let (offset, state) = ans_start(data, Self::L_BASE)?;
+        if state >= Self::L_BASE * 256 {
+            return Err(DracoError::Corrupt("rANS data"));
+        }
+        Ok(Self {
+            data,
+            offset,
+            state,
+            probability_zero,
+        })
+    }
+
+    fn next(&mut self) -> bool {
+        let probability = 256 - self.probability_zero;
+        if self.state < Self::L_BASE && self.offset > 0 {
+            self.offset -= 1;
+            self.state = self.state * 256 + self.data[self.offset] as u32;
+        }
+        let (quotient, remainder) = (self.state / 256, self.state % 256);
+        let scaled = quotient * probability;
+        if remainder < probability {
+            self.state = scaled + remainder;
+            true
+        } else {
+            self.state -= scaled + probability;
+            false
+        }
+    }
+}
+
+struct RansSymbolDecoder<'a> {
+    probabilities: Vec<u32>,
+    cumulative: Vec<u32>,
+    lookup: Vec<u32>,
+    precision: u32,
+    l_base: u32,
+    data: &'a [u8],
+    offset: usize,
+    state: u32,
+}
+
+impl<'a> RansSymbolDecoder<'a> {
+    /**
+     * Read the probability table and the encoded data of symbols up to `max_bit_length` bits.
+     */
+    fn new(reader: &mut Reader<'a>, max_bit_length: u32) -> Result<Self> {
+        let precision = 1 << ((3 * max_bit_length) / 2).clamp(12, 20);
+        let l_base = precision * 4;
+
+        let count = reader.varint_usize()?;
+        let mut probabilities = Vec::new();
+        while probabilities.len() < count {
+            let byte = reader.u8()?;
+            let token = byte & 3;
+            if token == 3 {
+                let zeros = (byte >> 2) as usize + 1;
+                if probabilities.len() + zeros > count {
+                    return Err(DracoError::Corrupt("rANS probabilities"));
+                }
+                probabilities.resize(probabilities.len() + zeros, 0);
+            } else {
+                let mut probability = (byte >> 2) as u32;
+                for i in 0..token as u32 {
+                    probability |= (reader.u8()? as u32) << (8 * (i + 1) - 2);
+                }
+                probabilities.push(probability);
+            }
+        }
+
+        let mut cumulative = Vec::new();
+        let mut lookup = Vec::with_capacity(precision as usize);
+        for (symbol, probability) in probabilities.iter().enumerate() {
+            cumulative.push(lookup.len() as u32);
+            if lookup.len() + *probability as usize > precision as usize {
+                return Err(DracoError::Corrupt("rANS probabilities"));
+            }
+            lookup.resize(lookup.len() + *probability as usize, symbol as u32);
+        }
+        if lookup.len() != precision as usize {
+            return Err(DracoError::Corrupt("rANS probabilities"));
+        }
+
+        let size = reader.varint_usize()?;
+        let data = reader.bytes(size)?;
+        let (offset, state) = ans_start(data, l_base)?;
+        if state >= l_base * 256 {
+            return Err(DracoError::Corrupt("rANS data"));
+        }
+        Ok(Self {
+            probabilities,
+            cumulative,
+            lookup,
+            precision,
+            l_base,
+            data,
+            offset,
+            state,
+        })
+    }
+
+    fn next(&mut self) -> u32 {
+        while self.state < self.l_base && self.offset > 0 {
+            self.offset -= 1;
+            self.state = self.state * 256 + self.data[self.offset] as u32;
+        }
+        let (quotient, remainder) = (self.state / self.precision, self.state % self.precision);
+        let symbol = self.lookup[remainder as usize];
+        self.state = quotient * self.probabilities[symbol as usize] + remainder
+            - self.cumulative[symbol as usize];
+        symbol
+    }
+}
+
+/**
+ * Decode `count` entropy coded values of `components` each.
+ */
+fn decode_symbols(reader: &mut Reader, count: usize, components: usize) -> Result<Vec<u32>> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    let mut values = Vec::new();
+    match reader.u8()? {
+        0 => {
+            let mut tags = RansSymbolDecoder::new(reader, 5)?;
+            let mut bits = BitReader::new(reader.remaining());
+            while values.len() < count {
+                let bit_length = tags.next();
+                if bit_length > 32 {
+                    return Err(DracoError::Corrupt("symbol bit length"));
+                }
+                for _ in 0..components.max(1) {
+                    values.push(bits.bits(bit_length));
+                }
+            }
+            values.truncate(count);
+            reader.bytes(bits.bytes_read())?;
+        }
+        1 => {
+            let max_bit_length = reader.u8()? as u32;
+            if !(1..=18).contains(&max_bit_length) {
+                return Err(DracoError::Corrupt("symbol bit length"));
+            }
+            let mut decoder = RansSymbolDecoder::new(reader, max_bit_length)?;
+            values.extend((0..count).map(|_| decoder.next()));
+        }
+        _ => return Err(DracoError::Unsupported("symbol coding")),
+    }
+    Ok(values)
+}
+
+const INVALID: u32 = u32::MAX;
+
+fn next(corner: u32) -> u32 {
+    match corner {
+        INVALID => INVALID,
+        _ if corner % 3 == 2 => corner - 2,
+        _ => corner + 1,
+    }
+}
+
+fn previous(corner: u32) -> u32 {
+    match corner {
+        INVALID => INVALID,
+        _ if corner.is_multiple_of(3) => corner + 2,
+        _ => corner - 1,
+    }
+}
+
+fn valid(corner: u32) -> Result<u32> {
+    match corner {
+        INVALID => Err(DracoError::Corrupt("connectivity")),
+        _ => Ok(corner),
+    }
+}
+
+/**
+ * Connectivity queries shared by the mesh corner table and the attribute corner tables,
+ * where `INVALID` stands for a missing corner or vertex.
+ */
+trait Corners {
+    fn face_count(&self) -> usize;
+
+    fn vertex_count(&self) -> usize;
+
+    fn vertex(&self, corner: u32) -> u32;
+
+    fn opposite(&self, corner: u32) -> u32;
+
+    fn left_most_corner(&self, vertex: u32) -> u32;
+
+    fn swing_left(&self, corner: u32) -> u32 {
+        next(self.opposite(next(corner)))
+    }
+
+    fn swing_right(&self, corner: u32) -> u32 {
+        previous(self.opposite(previous(corner)))
+    }
+
+    fn left_corner(&self, corner: u32) -> u32 {
+        self.opposite(previous(corner))
+    }
+
+    fn right_corner(&self, corner: u32) -> u32 {
+        self.opposite(next(corner))
+    }
+
+    fn is_on_boundary(&self, vertex: u32) -> bool {
+        let corner = self.left_most_corner(vertex);
+        corner == INVALID || self.swing_left(corner) == INVALID
+    }
+}
+
+fn lookup(values: &[u32], index: u32) -> u32 {
+    values.get(index as usize).copied().unwrap_or(INVALID)
+}
+
+struct CornerTable {
+    corner_to_vertex: Vec<u32>,
+    opposites: Vec<u32>,
+    left_most_corners: Vec<u32>,
+}
+
+impl CornerTable {
+    fn new(face_count: usize) -> Self {
+        Self {
+            corner_to_vertex: vec![INVALID; 3 * face_count],
+            opposites: vec![INVALID; 3 * face_count],
+            left_most_corners: Vec::new(),
+        }
+    }
+
+    fn set_opposite(&mut self, a: u32, b: u32) {
+        self.opposites[a as usize] = b;
+        self.opposites[b as usize] = a;
+    }
+
+    fn add_vertex(&mut self) -> u32 {
+        self.left_most_corners.push(INVALID);
+        self.left_most_corners.len() as u32 - 1
+    }
+}
+
+impl Corners for CornerTable {
+    fn face_count(&self) -> usize {
+        self.corner_to_vertex.len() / 3
+    }
+
+    fn vertex_count(&self) -> usize {
+        self.left_most_corners.len()
+    }
+
+    fn vertex(&self, corner: u32) -> u32 {
+        lookup(&self.corner_to_vertex, corner)
+    }
+
+    fn opposite(&self, corner: u32) -> u32 {
+        lookup(&self.opposites, corner)
+    }
+
+    fn left_most_corner(&self, vertex: u32) -> u32 {
+        lookup(&self.left_most_corners, vertex)
+    }
+}
+
+/**
+ * The mesh connectivity cut along the seams of one attribute,
+ * splitting vertices with several values of the attribute.
+ */
+struct AttributeCornerTable {
+    corner_to_vertex: Vec<u32>,
+    opposites: Vec<u32>,
+    left_most_corners: Vec<u32>,
+    seam_vertices: Vec<bool>,
+}
+
+impl AttributeCornerTable {
+    fn new(table: &CornerTable, seams: &[u32]) -> Result<Self> {
+        let mut opposites = table.opposites.clone();
+        let mut seam_edges = vec![false; opposites.len()];
+        let mut seam_vertices = vec![false; table.vertex_count()];
+        for corner in seams {
+            for corner in [*corner, table.opposite(*corner)] {
+                if corner == INVALID {
+                    continue;
+                }
+                opposites[corner as usize] = INVALID;
+                seam_edges[corner as usize] = true;
+                for vertex in [table.vertex(next(corner)), table.vertex(previous(corner))] {
+                    *seam_vertices
+                        .get_mut(vertex as usize)
+                        .ok_or(DracoError::Corrupt("attribute seam"))? = true;
+                }
+            }
+        }
+
+        let mut attribute_table = Self {
+            corner_to_vertex: vec![INVALID; opposites.len()],
+            opposites,
+            left_most_corners: Vec::new(),
+            seam_vertices,
+        };
+        for vertex in 0..table.vertex_count() as u32 {
+            let corner = table.left_most_corner(vertex);
+            if corner == INVALID {
+                continue;
+            }
+            let mut first_corner = corner;
+            if attribute_table.seam_vertices[vertex as usize] {
+                let mut current = attribute_table.swing_left(first_corner);
+                while current != INVALID {
+                    if current == corner {
+                        return Err(DracoError::Corrupt("attribute seam"));
+                    }
+                    first_corner = current;
+                    current = attribute_table.swing_left(current);
+                }
+            }
+
+            let mut attribute_vertex = attribute_table.left_most_corners.len() as u32;
+            attribute_table.left_most_corners.push(first_corner);
+            attribute_table.corner_to_vertex[first_corner as usize] = attribute_vertex;
+            let mut current = table.swing_right(first_corner);
+            while current != INVALID && current != first_corner {
+                if seam_edges[next(current) as usize] {
+                    attribute_vertex = attribute_table.left_most_corners.len() as u32;
+                    attribute_table.left_most_corners.push(current);
+                }
+                attribute_table.corner_to_vertex[current as usize] = attribute_vertex;
+                current = table.swing_right(current);
+            }
+        }
+        Ok(attribute_table)
+    }
+}
+
+impl Corners for AttributeCornerTable {
+    fn face_count(&self) -> usize {
+        self.corner_to_vertex.len() / 3
+    }
+
+    fn vertex_count(&self) -> usize {
+        self.left_most_corners.len()
+    }
+
+    fn vertex(&self, corner: u32) -> u32 {
+        lookup(&self.corner_to_vertex, corner)
+    }
+
+    fn opposite(&self, corner: u32) -> u32 {
+        lookup(&self.opposites, corner)
+    }
+
+    fn left_most_corner(&self, vertex: u32) -> u32 {
+        lookup(&self.left_most_corners, vertex)
+    }
+}
+
+const SYMBOL_C: u32 = 0;
+const SYMBOL_S: u32 = 1;
+const SYMBOL_L: u32 = 3;
+const SYMBOL_R: u32 = 5;
+const SYMBOL_E: u32 = 7;
+
+const VALENCE_SYMBOLS: [u32; 5] = [SYMBOL_C, SYMBOL_S, SYMBOL_L, SYMBOL_R, SYMBOL_E];
+const MIN_VALENCE: u32 = 2;
+const MAX_VALENCE: u32 = 7;
+
+/**
+ * Source of the edgebreaker symbols.
+ */
+enum Traversal<'a> {
+    Standard(BitReader<'a>),
+
+    /**
+     * Symbols entropy coded in contexts chosen by the valence of the next vertex
+     * of the active corner.
+     */
+    Valence {
+        valences: Vec<u32>,
+        contexts: Vec<Vec<u32>>,
+        active: Option<usize>,
+        last: u32,
+    },
+}
+
+impl Traversal<'_> {
+    fn next_symbol(&mut self) -> Result<u32> {
+        match self {
+            Traversal::Standard(bits) => {
+                let symbol = bits.bits(1);
+                Ok(match symbol {
+                    SYMBOL_C => SYMBOL_C,
+                    _ => symbol | bits.bits(2) << 1,
+                })
+            }
+            Traversal::Valence {
+                contexts,
+                active,
+                last,
+                ..
+            } => {
+                *last = match active {
+                    Some(context) => contexts[*context]
+                        .pop()
+                        .and_then(|id| VALENCE_SYMBOLS.get(id as usize).copied())
+                        .ok_or(DracoError::Corrupt("valence symbols"))?,
+                    None => SYMBOL_E,
+                };
+                Ok(*last)
+            }
+        }
+    }
+
+    fn new_active_corner(&mut self, corner: u32, table: &CornerTable) {
+        let Traversal::Valence {
+            valences,
+            active,
+            last,
+            ..
+        } = self
+        else {
+            return;
+        };
+        let increments = match *last {
+            SYMBOL_C | SYMBOL_S => [0, 1, 1],
+            SYMBOL_R => [1, 1, 2],
+            SYMBOL_L => [1, 2, 1],
+            _ => [2, 2, 2],
+        };
+        let vertices = [corner, next(corner), previous(corner)].map(|c| table.vertex(c));
+        for (vertex, increment) in vertices.iter().zip(increments) {
+            valences[*vertex as usize] += increment;
+        }
+        let valence = valences[vertices[1] as usize].clamp(MIN_VALENCE, MAX_VALENCE);
+        *active = Some((valence - MIN_VALENCE) as usize);
+    }
+
+    fn merge_vertices(&mut self, dest: u32, source: u32) {
+        if let Traversal::Valence { valences, .. } = self {
+            valences[dest as usize] += valences[source as usize];
+        }
+    }
+}
+
+struct Connectivity {
+    table: CornerTable,
+    attribute_tables: Vec<AttributeCornerTable>,
+    corner_to_point: Vec<u32>,
+    point_count: usize,
+}
+
+fn decode_connectivity(reader: &mut Reader) -> Result<Connectivity> {
+    let traversal_type = reader.u8()?;
+    let encoded_vertex_count = reader.varint_usize()?;
+    let face_count = reader.varint_usize()?;
+    let attribute_data_count = reader.u8()? as usize;
+    let symbol_count = reader.varint_usize()?;
+    let split_symbol_count = reader.varint_usize()?;
+    let max_vertices = encoded_vertex_count.saturating_add(split_symbol_count);
+    if face_count > (u32::MAX / 3) as usize
+        || symbol_count > face_count
+        || max_vertices > 3 * face_count
+    {
+        return Err(DracoError::Corrupt("connectivity header"));
+    }
+
+    // (source symbol, split symbol, whether the split is at the next corner) in encoder order.
+    let split_count = reader.varint_usize()?;
+    let mut splits = Vec::new();
+    let mut last_source = 0u64;
+    for _ in 0..split_count {
+        let source = reader.varint()?.saturating_add(last_source);
+        let split = source
+            .checked_sub(reader.varint()?)
+            .ok_or(DracoError::Corrupt("topology split"))?;
+        splits.push((source, split, false));
+        last_source = source;
+    }
+    if split_count > 0 {
+        let mut bits = BitReader::new(reader.remaining());
+        for split in &mut splits {
+            split.2 = bits.bits(1) == 1;
+        }
+        reader.bytes(bits.bytes_read())?;
+    }
+
+    let (mut traversal, mut start_faces, mut seams) = match traversal_type {
+        0 => {
+            let size = reader.varint_usize()?;
+            let symbols = BitReader::new(reader.bytes(size)?);
+            let start_faces = RansBitDecoder::new(reader)?;
+            let seams = (0..attribute_data_count)
+                .map(|_| RansBitDecoder::new(reader))
+                .collect::<Result<Vec<_>>>()?;
+            (Traversal::Standard(symbols), start_faces, seams)
+        }
+        2 => {
+            let start_faces = RansBitDecoder::new(reader)?;
+            let seams = (0..attribute_data_count)
+                .map(|_| RansBitDecoder::new(reader))
+                .collect::<Result<Vec<_>>>()?;
+            let contexts = (MIN_VALENCE..=MAX_VALENCE)
+                .map(|_| {
+                    let count = reader.varint_usize()?;
+                    decode_symbols(reader, count, 1)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let traversal = Traversal::Valence {
+                valences: vec![0; max_vertices],
+                contexts,
+                active: None,
+                last: SYMBOL_E,
+            };
+            (traversal, start_faces, seams)
+        }
+        1 => return Err(DracoError::Unsupported("predictive traversal")),
+        _ => return Err(DracoError::Corrupt("traversal type")),
+    };
+
+    let corrupt = || DracoError::Corrupt("connectivity");
+    let mut table = CornerTable::new(face_count);
+    let mut is_hole = vec![true; max_vertices];
+    let mut stack: Vec<u32> = Vec::new();
+    let mut split_corners: HashMap<u64, u32> = HashMap::new();
+    for symbol_id in 0..symbol_count {
+        let corner = 3 * symbol_id as u32;
+        let mut check_split = false;
+        match traversal.next_symbol()? {
+            SYMBOL_C => {
+                let corner_a = *stack.last().ok_or_else(corrupt)?;
+                let vertex_x = table.vertex(next(corner_a));
+                let corner_b = valid(next(table.left_most_corner(vertex_x)))?;
+                if corner_a == corner_b
+                    || table.opposite(corner_a) != INVALID
+                    || table.opposite(corner_b) != INVALID
+                {
+                    return Err(corrupt());
+                }
+                table.set_opposite(corner_a, corner + 1);
+                table.set_opposite(corner_b, corner + 2);
+                let vertex_a = table.vertex(previous(corner_a));
+                let vertex_b = table.vertex(next(corner_b));
+                if vertex_x == vertex_a || vertex_x == vertex_b {
+                    return Err(corrupt());
+                }
+                table.corner_to_vertex[corner as usize..corner as usize + 3]
+                    .copy_from_slice(&[vertex_x, vertex_b, vertex_a]);
+                table.left_most_corners[vertex_a as usize] = corner + 2;
+                is_hole[vertex_x as usize] = false;
+                *stack.last_mut().unwrap() = corner;
+            }
+            symbol @ (SYMBOL_R | SYMBOL_L) => {
+                let corner_a = *stack.last().ok_or_else(corrupt)?;
+                if table.opposite(corner_a) != INVALID || table.vertex_count() >= max_vertices {
+                    return Err(corrupt());
+                }
+                let (opposite, left, right) = match symbol {
+                    SYMBOL_R => (corner + 2, corner + 1, corner),
+                    _ => (corner + 1, corner, corner + 2),
+                };
+                table.set_opposite(opposite, corner_a);
+                let new_vertex = table.add_vertex();
+                table.corner_to_vertex[opposite as usize] = new_vertex;
+                table.left_most_corners[new_vertex as usize] = opposite;
+                let vertex_r = table.vertex(previous(corner_a));
+                table.corner_to_vertex[right as usize] = vertex_r;
+                table.left_most_corners[vertex_r as usize] = right;
+                table.corner_to_vertex[left as usize] = table.vertex(next(corner_a));
+                *stack.last_mut().unwrap() = corner;
+                check_split = true;
+            }
+            SYMBOL_S => {
+                let corner_b = stack.pop().ok_or_else(corrupt)?;
+                if let Some(split_corner) = split_corners.get(&(symbol_id as u64)) {
+                    stack.push(*split_corner);
+                }
+                let corner_a = *stack.last().ok_or_else(corrupt)?;
+                if corner_a == corner_b
+                    || table.opposite(corner_a) != INVALID
+                    || table.opposite(corner_b) != INVALID
+                {
+                    return Err(corrupt());
+                }
+                table.set_opposite(corner_a, corner + 2);
+                table.set_opposite(corner_b, corner + 1);
+                let vertex_p = table.vertex(previous(corner_a));
+                table.corner_to_vertex[corner as usize] = vertex_p;
+                table.corner_to_vertex[corner as usize + 1] = table.vertex(next(corner_a));
+                let vertex_b_previous = table.vertex(previous(corner_b));
+                table.corner_to_vertex[corner as usize + 2] = vertex_b_previous;
+                table.left_most_corners[vertex_b_previous as usize] = corner + 2;
+
+                // Merge the vertex at the next corner of `corner_b` into `vertex_p`.
+                let mut corner_n = next(corner_b);
+                let vertex_n = table.vertex(corner_n);
+                traversal.merge_vertices(vertex_p, vertex_n);
+                table.left_most_corners[vertex_p as usize] =
+                    table.left_most_corners[vertex_n as usize];
+                let first = corner_n;
+                while corner_n != INVALID {
+                    table.corner_to_vertex[corner_n as usize] = vertex_p;
+                    corner_n = table.swing_left(corner_n);
+                    if corner_n == first {
+                        return Err(corrupt());
+                    }
+                }
+                table.left_most_corners[vertex_n as usize] = INVALID;
+                *stack.last_mut().unwrap() = corner;
+            }
+            SYMBOL_E => {
+                if table.vertex_count() + 3 > max_vertices {
+                    return Err(corrupt());
+                }
+                for i in 0..3 {
+                    let vertex = table.add_vertex();
+                    table.corner_to_vertex[(corner + i) as usize] = vertex;
+                    table.left_most_corners[vertex as usize] = corner + i;
+                }
+                stack.push(corner);
+                check_split = true;
+            }
+            _ => return Err(DracoError::Corrupt("edgebreaker symbol")),
+        }
+        traversal.new_active_corner(*stack.last().unwrap(), &table);
+
+        if check_split {
+            let encoder_id = (symbol_count - symbol_id - 1) as u64;
+            while let Some(&(source, split, at_next)) = splits.last() {
+                if source > encoder_id {
+                    return Err(DracoError::Corrupt("topology split"));
+                }
+                if source != encoder_id {
+                    break;
+                }
+                splits.pop();
+                let top = *stack.last().unwrap();
+                let split_corner = if at_next { next(top) } else { previous(top) };
+                let decoder_id = (symbol_count as u64)
+                    .checked_sub(split + 1)
+                    .ok_or(DracoError::Corrupt("topology split"))?;
+                split_corners.insert(decoder_id, split_corner);
+            }
+        }
+    }
+
+    // Close the interior start faces, whose corners the traversal left on the stack.
+    let mut face = symbol_count;
+    while let Some(corner) = stack.pop() {
+        if !start_faces.next() {
+            continue;
+        }
+        if face >= face_count {
+            return Err(corrupt());
+        }
+        let vertex_n = table.vertex(next(corner));
+        let corner_b = valid(next(table.left_most_corner(vertex_n)))?;
+        let vertex_x = table.vertex(next(corner_b));
+        let corner_c = valid(next(table.left_most_corner(vertex_x)))?;
+        let vertex_p = table.vertex(next(corner_c));
+        let new_corner = 3 * face as u32;
+        table.set_opposite(new_corner, corner);
+        table.set_opposite(new_corner + 1, corner_b);
+        table.set_opposite(new_corner + 2, corner_c);
+        for (i, vertex) in [vertex_x, vertex_p, vertex_n].into_iter().enumerate() {
+            table.corner_to_vertex[new_corner as usize + i] = vertex;
+            is_hole[vertex as usize] = false;
+        }
+        face += 1;
+    }
+    if face != face_count {
+        return Err(corrupt());
+    }
+
+    // Boundary edges are seams of every attribute, interior edges are flagged once.
+    let mut attribute_seams = vec![Vec::new(); attribute_data_count];
+    for corner in 0..3 * face_count as u32 {
+        let opposite = table.opposite(corner);
+        if opposite == INVALID {
+            attribute_seams
+                .iter_mut()
+                .for_each(|seams| seams.push(corner));
+        } else if opposite / 3 >= corner / 3 {
+            for (seams, decoder) in attribute_seams.iter_mut().zip(&mut seams) {
+                if decoder.next() {
+                    seams.push(corner);
+                }
+            }
+        }
+    }
+    let attribute_tables = attribute_seams
+        .iter()
+        .map(|seams| AttributeCornerTable::new(&table, seams))
+        .collect::<Result<Vec<_>>>()?;
+
+    let (corner_to_point, point_count) = assign_points(&table, &attribute_tables, &is_hole)?;
+    Ok(Connectivity {
+        table,
+        attribute_tables,
+        corner_to_point,
+        point_count,
+    })
+}
+
+/**
+ * Split every vertex into points where any attribute has a seam,
+ * returning the point of each corner and the number of points.
+ */
+fn assign_points(
+    table: &CornerTable,
+    attribute_tables: &[AttributeCornerTable],
+    is_hole: &[bool],
+) -> Result<(Vec<u32>, usize)> {
+    let mut corner_to_point = vec![INVALID; table.corner_to_vertex.len()];
+    let mut point_count = 0u32;
+    for vertex in 0..table.vertex_count() as u32 {
+        let corner = table.left_most_corner(vertex);
+        if corner == INVALID {
+            continue;
+        }
+
+        // Interior vertices start at a seam so that the walk around them splits once per seam.
+        let mut first = corner;
+        if !is_hole[vertex as usize] {
+            'tables: for attribute_table in attribute_tables {
+                if !attribute_table.seam_vertices[vertex as usize] {
+                    continue;
+                }
+                let attribute_vertex = attribute_table.vertex(corner);
+                let mut current = valid(table.swing_right(corner))?;
+                while current != corner {
+                    if attribute_table.vertex(current) != attribute_vertex {
+                        first = current;
+                        break 'tables;
+                    }
+                    current = valid(table.swing_right(current))?;
+                }
+            }
+        }
+
+        corner_to_point[first as usize] = point_count;
+        point_count += 1;
+        let mut previous_corner = first;
+        let mut current = table.swing_right(first);
+        while current != INVALID && current != first {
+            let is_split = attribute_tables.iter().any(|attribute_table| {
+                attribute_table.vertex(current) != attribute_table.vertex(previous_corner)
+            });
+            corner_to_point[current as usize] = if is_split {
+                point_count += 1;
+                point_count - 1
+            } else {
+                corner_to_point[previous_corner as usize]
+            };
+            previous_corner = current;
+            current = table.swing_right(current);
+        }
+    }
+    if corner_to_point.contains(&INVALID) {
+        return Err(DracoError::Corrupt("connectivity"));
+    }
+    Ok((corner_to_point, point_count as usize))
+}
+
+/**
+ * Integer octahedral normal coordinates with `bits` per component.
+ */
+#[derive(Debug, Clone, Copy)]
+struct Octahedron {
+    max_quantized: i32,
+    max_value: i32,
+    center: i32,
+}
+
+impl Octahedron {
+    fn new(bits: u32) -> Result<Self> {
+        if !(2..=30).contains(&bits) {
+            return Err(DracoError::Corrupt("normal quantization"));
+        }
+        let max_quantized = (1 << bits) - 1;
+        Ok(Self {
+            max_quantized,
+            max_value: max_quantized - 1,
+            center: (max_quantized - 1) / 2,
+        })
+    }
+
+    fn from_max_quantized(max_quantized: i32) -> Result<Self> {
+        if max_quantized <= 0 || max_quantized % 2 == 0 {
+            return Err(DracoError::Corrupt("normal quantization"));
+        }
+        Self::new(32 - max_quantized.leading_zeros())
+    }
+
+    /**
+     * Scale `vector` to an L1 norm of `center`.
+     */
+    fn canonicalize_vector(&self, vector: [i64; 3]) -> [i32; 3] {
+        let sum = vector[0].abs() + vector[1].abs() + vector[2].abs();
+        if sum == 0 {
+            return [self.center, 0, 0];
+        }
+        let center = self.center as i64;
+        let x = (vector[0] * center / sum) as i32;
+        let y = (vector[1] * center / sum) as i32;
+        let z = self.center - x.abs() - y.abs();
+        [x, y, if vector[2] < 0 { -z } else { z }]
+    }
+
+    fn vector_to_octahedral(&self, vector: [i32; 3]) -> [i32; 2] {
+        let (center, max) = (self.center, self.max_value);
+        let (s, t) = if vector[0] >= 0 {
+            (vector[1] + center, vector[2] + center)
+        } else {
+            (
+                if vector[1] < 0 {
+                    vector[2].abs()
+                } else {
+                    max - vector[2].abs()
+                },
+                if vector[2] < 0 {
+                    vector[1].abs()
+                } else {
+                    max - vector[1].abs()
+                },
+            )
+        };
+        self.canonicalize_octahedral(s, t)
+    }
+
+    /**
+     * Pick one representation of the coordinates duplicated along the border.
+     */
+    fn canonicalize_octahedral(&self, s: i32, t: i32) -> [i32; 2] {
+        let (center, max) = (self.center, self.max_value);
+        if (s == 0 && (t == 0 || t == max)) || (s == max && t == 0) {
+            [max, max]
+        } else if s == 0 && t > center {
+            [s, center - (t - center)]
+        } else if s == max && t < center {
+            [s, center + (center - t)]
+        } else if t == max && s < center {
+            [center + (center - s), t]
+        } else if t == 0 && s > center {
+            [center - (s - center), t]
+        } else {
+            [s, t]
+        }
+    }
+
+    fn in_diamond(&self, s: i32, t: i32) -> bool {
+        s.abs() + t.abs() <= self.center
+    }
+
+    /**
+     * Mirror centered coordinates between the inner diamond and the outer triangles.
+     */
+    fn invert_diamond(&self, s: i32, t: i32) -> [i32; 2] {
+        let (sign_s, sign_t) = if s >= 0 && t >= 0 {
+            (1, 1)
+        } else if s <= 0 && t <= 0 {
+            (-1, -1)
+        } else {
+            (s.signum(), t.signum())
+        };
+        let (corner_s, corner_t) = (sign_s * self.center, sign_t * self.center);
+        let (s, t) = (2 * s - corner_s, 2 * t - corner_t);
+        let (s, t) = if sign_s * sign_t >= 0 {
+            (-t, -s)
+        } else {
+            (t, s)
+        };
+        [(s + corner_s) / 2, (t + corner_t) / 2]
+    }
+
+    fn mod_max(&self, value: i32) -> i32 {
+        if value > self.center {
+            value - self.max_quantized
+        } else if value < -self.center {
+            value + self.max_quantized
+        } else {
+            value
+        }
+    }
+
+    fn to_unit_vector(self, s: i32, t: i32) -> [f32; 3] {
+        let mut y = s as f32 / self.max_value as f32 * 2.0 - 1.0;
+        let mut z = t as f32 / self.max_value as f32 * 2.0 - 1.0;
+        let x = 1.0 - y.abs() - z.abs();
+        let offset = (-x).max(0.0);
+        y += if y < 0.0 { offset } else { -offset };
+        z += if z < 0.0 { offset } else { -offset };
+        let norm = (x * x + y * y + z * z).sqrt();
+        if norm > 1e-3 {
+            [x / norm, y / norm, z / norm]
+        } else {
+            [0.0, 0.0, 0.0]
+        }
+    }
+}
+
+/**
+ * How a correction is combined with its prediction.
+ */
+#[derive(Debug, Clone, Copy)]
+enum Transform {
+    Delta,
+
+    /**
+     * Sums wrapped around into `[min, max]`.
+     */
+    Wrap {
+        min: i32,
+        max: i32,
+    },
+
+    /**
+     * Octahedral normal coordinates, corrected modulo the octahedron.
+     */
+    Octahedron(Octahedron),
+
+    /**
+     * Like `Octahedron`, with the prediction rotated to the bottom left quadrant first.
+     */
+    OctahedronCanonicalized(Octahedron),
+}
+
+impl Transform {
+    /**
+     * Read the transform data for a transform of `kind`.
+     */
+    fn read(kind: i8, reader: &mut Reader) -> Result<Self> {
+        Ok(match kind {
+            0 => Transform::Delta,
+            1 => {
+                let min = reader.i32()?;
+                let max = reader.i32()?;
+                if min > max {
+                    return Err(DracoError::Corrupt("wrap transform"));
+                }
+                Transform::Wrap { min, max }
+            }
+            2 => Transform::Octahedron(Octahedron::from_max_quantized(reader.i32()?)?),
+            3 => {
+                let max_quantized = reader.i32()?;
+                let _center = reader.i32()?;
+                Transform::OctahedronCanonicalized(Octahedron::from_max_quantized(max_quantized)?)
+            }
+            _ => return Err(DracoError::Unsupported("prediction transform")),
+        })
+    }
+
+    /**
+     * Whether corrections of a transform of `kind` are stored without zigzag coding.
+     */
+    fn corrections_positive(kind: i8) -> bool {
+        matches!(kind, 2 | 3)
+    }
+
+    fn octahedron(&self) -> Option<&Octahedron> {
+        match self {
+            Transform::Octahedron(octahedron) | Transform::OctahedronCanonicalized(octahedron) => {
+                Some(octahedron)
+            }
+            _ => None,
+        }
+    }
+
+    fn original(&self, predicted: &[i32], corrections: &[i32], out: &mut [i32]) {
+        match self {
+            Transform::Delta => {
+                for ((out, predicted), correction) in out.iter_mut().zip(predicted).zip(corrections)
+                {
+                    *out = predicted.wrapping_add(*correction);
+                }
+            }
+            Transform::Wrap { min, max } => {
+                let range = max.wrapping_sub(*min).wrapping_add(1);
+                for ((out, predicted), correction) in out.iter_mut().zip(predicted).zip(corrections)
+                {
+                    let value = predicted.clamp(min, max).wrapping_add(*correction);
+                    *out = if value > *max {
+                        value.wrapping_sub(range)
+                    } else if value < *min {
+                        value.wrapping_add(range)
+                    } else {
+                        value
+                    };
+                }
+            }
+            Transform::Octahedron(octahedron) => {
+                let center = octahedron.center;
+                let (s, t) = (predicted[0] - center, predicted[1] - center);
+                let in_diamond = octahedron.in_diamond(s, t);
+                let [s, t] = if in_diamond {
+                    [s, t]
+                } else {
+                    octahedron.invert_diamond(s, t)
+                };
+                let mut value = [
+                    octahedron.mod_max(s.wrapping_add(corrections[0])),
+                    octahedron.mod_max(t.wrapping_add(corrections[1])),
+                ];
+                if !in_diamond {
+                    value = octahedron.invert_diamond(value[0], value[1]);
+                }
+                out[0] = value[0] + center;
+                out[1] = value[1] + center;
+            }
+            Transform::OctahedronCanonicalized(octahedron) => {
+                let center = octahedron.center;
+                let (s, t) = (predicted[0] - center, predicted[1] - center);
+                let in_diamond = octahedron.in_diamond(s, t);
+                let [s, t] = if in_diamond {
+                    [s, t]
+                } else {
+                    octahedron.invert_diamond(s, t)
+                };
+                let bottom_left = (s == 0 && t == 0) || (s < 0 && t <= 0);
+                let rotations = match (s.signum(), t.signum()) {
+                    (0, 0) => 0,
+                    (0, 1) => 3,
+                    (0, _) => 1,
+                    (1, -1) => 1,
+                    (1, _) => 2,
+                    (_, 1) => 3,
+                    _ => 0,
+                };
+                let rotate = |[s, t]: [i32; 2], count: u32| match count % 4 {
+                    0 => [s, t],
+                    1 => [t, -s],
+                    2 => [-s, -t],
+                    _ => [-t, s],
+                };
+                let [s, t] = if bottom_left {
+                    [s, t]
+                } else {
+                    rotate([s, t], rotations)
+                };
+                let mut value = [
+                    octahedron.mod_max(s.wrapping_add(corrections[0])),
+                    octahedron.mod_max(t.wrapping_add(corrections[1])),
+                ];
+                if !bottom_left {
+                    value = rotate(value, 4 - rotations);
+                }
+                if !in_diamond {
+                    value = octahedron.invert_diamond(value[0], value[1]);
+                }
+                out[0] = value[0] + center;
+                out[1] = value[1] + center;
+            }
+        }
+    }
+}
+
+/**
+ * The order in which an attributes decoder visits the vertices of its table.
+ */
+struct Encoding {
+    vertex_to_entry: Vec<u32>,
+    entry_to_corner: Vec<u32>,
+}
+
+impl Encoding {
+    /**
+     * Depth first traversal, visiting vertices through the right corners of each face.
+     */
+    fn traverse(table: &dyn Corners) -> Self {
+        let mut encoding = Encoding {
+            vertex_to_entry: vec![INVALID; table.vertex_count()],
+            entry_to_corner: Vec::new(),
+        };
+        let mut visited_faces = vec![false; table.face_count()];
+        let is_visited = |visited_faces: &[bool], corner: u32| {
+            corner == INVALID || visited_faces[corner as usize / 3]
+        };
+
+        for face in 0..table.face_count() as u32 {
+            let corner = 3 * face;
+            if is_visited(&visited_faces, corner) {
+                continue;
+            }
+            for corner in [next(corner), previous(corner)] {
+                encoding.visit(table.vertex(corner), corner);
+            }
+
+            let mut stack = vec![corner];
+            while let Some(&top) = stack.last() {
+                if is_visited(&visited_faces, top) {
+                    stack.pop();
+                    continue;
+                }
+                let mut corner = top;
+                loop {
+                    visited_faces[corner as usize / 3] = true;
+                    let vertex = table.vertex(corner);
+                    if encoding.vertex_to_entry[vertex as usize] == INVALID {
+                        let on_boundary = table.is_on_boundary(vertex);
+                        encoding.visit(vertex, corner);
+                        if !on_boundary {
+                            corner = table.right_corner(corner);
+                            continue;
+                        }
+                    }
+                    let right = table.right_corner(corner);
+                    let left = table.left_corner(corner);
+                    match (
+                        is_visited(&visited_faces, right),
+                        is_visited(&visited_faces, left),
+                    ) {
+                        (true, true) => {
+                            stack.pop();
+                            break;
+                        }
+                        (true, false) => corner = left,
+                        (false, true) => corner = right,
+                        (false, false) => {
+                            *stack.last_mut().unwrap() = left;
+                            stack.push(right);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        encoding
+    }
+
+    fn visit(&mut self, vertex: u32, corner: u32) {
+        let entry = &mut self.vertex_to_entry[vertex as usize];
+        if *entry == INVALID {
+            *entry = self.entry_to_corner.len() as u32;
+            self.entry_to_corner.push(corner);
+        }
+    }
+
+    fn entry_count(&self) -> usize {
+        self.entry_to_corner.len()
+    }
+
+    fn entry(&self, table: &dyn Corners, corner: u32) -> u32 {
+        lookup(&self.vertex_to_entry, table.vertex(corner))
+    }
+}
+
+/**
+ * Quantized positions, for predictions based on the geometry.
+ */
+struct Positions {
+    values: Vec<i32>,
+    point_to_entry: Vec<u32>,
+}
+
+impl Positions {
+    fn at_corner(&self, corner_to_point: &[u32], corner: u32) -> Result<[i64; 3]> {
+        let entry = *corner_to_point
+            .get(corner as usize)
+            .and_then(|point| self.point_to_entry.get(*point as usize))
+            .ok_or(DracoError::Corrupt("connectivity"))? as usize;
+        let value = self
+            .values
+            .get(3 * entry..3 * entry + 3)
+            .ok_or(DracoError::Corrupt("positions"))?;
+        Ok([value[0] as i64, value[1] as i64, value[2] as i64])
+    }
+}
+
+const PREDICTION_NONE: i8 = -2;
+const PREDICTION_DIFFERENCE: i8 = 0;
+const PREDICTION_PARALLELOGRAM: i8 = 1;
+const PREDICTION_CONSTRAINED_MULTI_PARALLELOGRAM: i8 = 4;
+const PREDICTION_TEX_COORDS_PORTABLE: i8 = 5;
+const PREDICTION_GEOMETRIC_NORMAL: i8 = 6;
+
+/**
+ * Everything a prediction scheme needs to know about the mesh.
+ */
+struct PredictionContext<'a> {
+    table: &'a dyn Corners,
+    encoding: &'a Encoding,
+    corner_to_point: &'a [u32],
+    positions: Option<&'a Positions>,
+}
+
+impl PredictionContext<'_> {
+    /**
+     * The value across the edge opposite of `corner`,
+     * if the other three vertices of the parallelogram are decoded.
+     */
+    fn parallelogram(
+        &self,
+        values: &[i32],
+        components: usize,
+        entry: u32,
+        corner: u32,
+    ) -> Option<Vec<i32>> {
+        let opposite = self.table.opposite(corner);
+        if opposite == INVALID {
+            return None;
+        }
+        let [opposite, next, previous] = [opposite, next(opposite), previous(opposite)]
+            .map(|corner| self.encoding.entry(self.table, corner) as usize);
+        if [opposite, next, previous]
+            .iter()
+            .any(|other| *other >= entry as usize)
+        {
+            return None;
+        }
+        Some(
+            (0..components)
+                .map(|i| {
+                    values[next * components + i]
+                        .wrapping_add(values[previous * components + i])
+                        .wrapping_sub(values[opposite * components + i])
+                })
+                .collect(),
+        )
+    }
+
+    fn position(&self, corner: u32) -> Result<[i64; 3]> {
+        self.positions
+            .ok_or(DracoError::Corrupt("prediction without positions"))?
+            .at_corner(self.corner_to_point, corner)
+    }
+}
+
+/**
+ * Read the data of prediction `method` and undo it on `corrections`.
+ */
+fn predict(
+    reader: &mut Reader,
+    method: i8,
+    transform_kind: i8,
+    context: &PredictionContext,
+    corrections: &[i32],
+    components: usize,
+) -> Result<Vec<i32>> {
+    let entry_count = context.encoding.entry_count();
+    let table = context.table;
+    let corrupt = |what| DracoError::Corrupt(what);
+
+    let mut creases = Vec::new();
+    let mut orientations = Vec::new();
+    match method {
+        PREDICTION_CONSTRAINED_MULTI_PARALLELOGRAM => {
+            for _ in 0..4 {
+                let count = reader.varint_usize()?;
+                let mut flags = Vec::new();
+                if count > 0 {
+                    let mut decoder = RansBitDecoder::new(reader)?;
+                    flags.extend((0..count.min(entry_count * 4)).map(|_| decoder.next()));
+                }
+                creases.push(flags);
+            }
+        }
+        PREDICTION_TEX_COORDS_PORTABLE => {
+            let count = reader.i32()?;
+            let mut decoder = RansBitDecoder::new(reader)?;
+            let mut last = true;
+            for _ in 0..count.clamp(0, entry_count as i32) {
+                if !decoder.next() {
+                    last = !last;
+                }
+                orientations.push(last);
+            }
+        }
+        _ => {}
+    }
+    let transform = Transform::read(transform_kind, reader)?;
+    let mut flips = match method {
+        PREDICTION_GEOMETRIC_NORMAL => Some(RansBitDecoder::new(reader)?),
+        _ => None,
+    };
+
+    let mut values = vec![0; entry_count * components];
+    let zeros = vec![0; components];
+    let range = |entry: usize| entry * components..(entry + 1) * components;
+    match method {
+        PREDICTION_DIFFERENCE => {
+            for entry in 0..entry_count {
+                let predicted = match entry {
+                    0 => zeros.clone(),
+                    _ => values[range(entry - 1)].to_vec(),
+                };
+                transform.original(
+                    &predicted,
+                    &corrections[range(entry)],
+                    &mut values[range(entry)],
+                );
+            }
+        }
+        PREDICTION_PARALLELOGRAM | PREDICTION_CONSTRAINED_MULTI_PARALLELOGRAM => {
+            let mut crease_positions = [0; 4];
+            for entry in 0..entry_count {
+                let start = context.encoding.entry_to_corner[entry];
+                let mut predictions = Vec::new();
+                if entry == 0 {
+                } else if method == PREDICTION_PARALLELOGRAM {
+                    predictions.extend(context.parallelogram(
+                        &values,
+                        components,
+                        entry as u32,
+                        start,
+                    ));
+                } else {
+                    // Parallelograms around the vertex, swinging left and then right of `start`.
+                    let mut corner = start;
+                    let mut swing_left = true;
+                    while corner != INVALID && predictions.len() < 4 {
+                        predictions.extend(context.parallelogram(
+                            &values,
+                            components,
+                            entry as u32,
+                            corner,
+                        ));
+                        if predictions.len() == 4 {
+                            break;
+                        }
+                        if swing_left {
+                            corner = table.swing_left(corner);
+                            if corner == INVALID {
+                                swing_left = false;
+                                corner = table.swing_right(start);
+                            }
+                        } else {
+                            corner = table.swing_right(corner);
+                        }
+                        if corner == start {
+                            break;
+                        }
+                    }
+                    let context_index = predictions.len().max(1) - 1;
+                    let mut used = Vec::new();
+                    for prediction in predictions {
+                        let position = &mut crease_positions[context_index];
+                        let is_crease = *creases[context_index]
+                            .get(*position)
+                            .ok_or(corrupt("crease flags"))?;
+                        *position += 1;
+                        if !is_crease {
+                            used.push(prediction);
+                        }
+                    }
+                    predictions = used;
+                }
+
+                let predicted = if entry == 0 {
+                    zeros.clone()
+                } else if predictions.is_empty() {
+                    values[range(entry - 1)].to_vec()
+                } else {
+                    (0..components)
+                        .map(|i| {
+                            let sum: i64 = predictions.iter().map(|p| p[i] as i64).sum();
+                            (sum / predictions.len() as i64) as i32
+                        })
+                        .collect()
+                };
+                transform.original(
+                    &predicted,
+                    &corrections[range(entry)],
+                    &mut values[range(entry)],
+                );
+            }
+        }
+        PREDICTION_TEX_COORDS_PORTABLE => {
+            if components != 2 {
+                return Err(corrupt("texture coordinate prediction"));
+            }
+            for entry in 0..entry_count {
+                let corner = context.encoding.entry_to_corner[entry];
+                let next_entry = context.encoding.entry(table, next(corner)) as usize;
+                let previous_entry = context.encoding.entry(table, previous(corner)) as usize;
+                let mut predicted = None;
+                if previous_entry < entry && next_entry < entry {
+                    let next_uv = [
+                        values[2 * next_entry] as i64,
+                        values[2 * next_entry + 1] as i64,
+                    ];
+                    let previous_uv = [
+                        values[2 * previous_entry] as i64,
+                        values[2 * previous_entry + 1] as i64,
+                    ];
+                    if next_uv == previous_uv {
+                        predicted = Some(previous_uv);
+                    } else {
+                        predicted = predict_tex_coord(
+                            context,
+                            [corner, next(corner), previous(corner)],
+                            next_uv,
+                            previous_uv,
+                            &mut orientations,
+                        )?;
+                    }
+                }
+                let predicted = match predicted {
+                    Some([u, v]) => vec![u as i32, v as i32],
+                    None => {
+                        // Fall back to delta coding from a decoded neighbor, or the last entry.
+                        let source = if next_entry < entry {
+                            Some(next_entry)
+                        } else {
+                            entry.checked_sub(1)
+                        };
+                        source.map_or(zeros.clone(), |source| values[range(source)].to_vec())
+                    }
+                };
+                transform.original(
+                    &predicted,
+                    &corrections[range(entry)],
+                    &mut values[range(entry)],
+                );
+            }
+        }
+        PREDICTION_GEOMETRIC_NORMAL => {
+            let octahedron = *transform
+                .octahedron()
+                .ok_or(corrupt("normal prediction transform"))?;
+            let flips = flips.as_mut().unwrap();
+            for entry in 0..entry_count {
+                let corner = context.encoding.entry_to_corner[entry];
+                let normal = predict_normal(context, corner)?;
+                let mut normal = octahedron.canonicalize_vector(normal);
+                if flips.next() {
+                    normal = normal.map(|x| -x);
+                }
+                let predicted = octahedron.vector_to_octahedral(normal);
+                transform.original(
+                    &predicted,
+                    &corrections[range(entry)],
+                    &mut values[range(entry)],
+                );
+            }
+        }
+        _ => return Err(DracoError::Unsupported("prediction scheme")),
+    }
+    Ok(values)
+}
+
+/**
+ * Predict the texture coordinates at `corners[0]` by projecting its position
+ * onto the opposite edge, where the other two corners have known coordinates.
+ */
+fn predict_tex_coord(
+    context: &PredictionContext,
+    corners: [u32; 3],
+    next_uv: [i64; 2],
+    previous_uv: [i64; 2],
+    orientations: &mut Vec<bool>,
+) -> Result<Option<[i64; 2]>> {
+    let [tip, next_position, previous_position] = [
+        context.position(corners[0])?,
+        context.position(corners[1])?,
+        context.position(corners[2])?,
+    ];
+    let pn: [i64; 3] = std::array::from_fn(|i| previous_position[i] - next_position[i]);
+    let pn_squared: i64 = pn.iter().map(|x| x * x).sum();
+    if pn_squared == 0 {
+        return Ok(None);
+    }
+    let cn: [i64; 3] = std::array::from_fn(|i| tip[i] - next_position[i]);
+    let dot: i64 = (0..3).map(|i| pn[i] * cn[i]).sum();
+    let pn_uv = [previous_uv[0] - next_uv[0], previous_uv[1] - next_uv[1]];
+    let x_uv: [i64; 2] =
+        std::array::from_fn(|i| next_uv[i].wrapping_mul(pn_squared) + dot.wrapping_mul(pn_uv[i]));
+    let x_position: [i64; 3] = std::array::from_fn(|i| next_position[i] + dot * pn[i] / pn_squared);
+    let cx_squared: i64 = (0..3).map(|i| (tip[i] - x_position[i]).pow(2)).sum();
+    let norm = (cx_squared as u64).wrapping_mul(pn_squared as u64).isqrt() as i64;
+    let cx_uv = [pn_uv[1] * norm, -pn_uv[0] * norm];
+    let orientation = orientations
+        .pop()
+        .ok_or(DracoError::Corrupt("texture coordinate orientations"))?;
+    Ok(Some(std::array::from_fn(|i| {
+        if orientation {
+            (x_uv[i] + cx_uv[i]) / pn_squared
+        } else {
+            (x_uv[i] - cx_uv[i]) / pn_squared
+        }
+    })))
+}
+
+/**
+ * Sum of the cross products of the faces around the vertex of `corner`,
+ * scaled down to an L1 norm of at most 2^29.
+ */
+fn predict_normal(context: &PredictionContext, corner: u32) -> Result<[i64; 3]> {
+    let table = context.table;
+    let center = context.position(corner)?;
+    let mut normal = [0i64; 3];
+    let mut current = corner;
+    let mut swing_left = true;
+    while current != INVALID {
+        let next_position = context.position(next(current))?;
+        let previous_position = context.position(previous(current))?;
+        let n: [i64; 3] = std::array::from_fn(|i| next_position[i] - center[i]);
+        let p: [i64; 3] = std::array::from_fn(|i| previous_position[i] - center[i]);
+        let cross = [
+            n[1].wrapping_mul(p[2])
+                .wrapping_sub(n[2].wrapping_mul(p[1])),
+            n[2].wrapping_mul(p[0])
+                .wrapping_sub(n[0].wrapping_mul(p[2])),
+            n[0].wrapping_mul(p[1])
+                .wrapping_sub(n[1].wrapping_mul(p[0])),
+        ];
+        for (sum, value) in normal.iter_mut().zip(cross) {
+            *sum = sum.wrapping_add(value);
+        }
+
+        if swing_left {
+            current = table.swing_left(current);
+            if current == INVALID {
+                swing_left = false;
+                current = table.swing_right(corner);
+            } else if current == corner {
+                break;
+            }
+        } else {
+            current = table.swing_right(current);
+        }
+    }
+
+    let upper_bound = 1 << 29;
+    let sum = normal
+        .iter()
+        .fold(0i64, |sum, x| sum.saturating_add(x.saturating_abs()));
+    if sum > upper_bound {
+        let quotient = sum / upper_bound;
+        normal = normal.map(|x| x / quotient);
+    }
+    Ok(normal)
+}
+
+const DECODER_GENERIC: u8 = 0;
+const DECODER_INTEGER: u8 = 1;
+const DECODER_QUANTIZATION: u8 = 2;
+const DECODER_NORMALS: u8 = 3;
+
+enum Decoded {
+    Floats(Vec<f32>),
+
+    /**
+     * Values still to be dequantized or converted to floats.
+     */
+    Integers(Vec<i32>),
+}
+
+struct AttributeHeader {
+    attribute_type: AttributeType,
+    data_type: u8,
+    components: usize,
+    normalized: bool,
+    unique_id: u32,
+    decoder_type: u8,
+}
+
+/**
+ * Size in bytes and largest value of the integer Draco data types.
+ */
+fn integer_type(data_type: u8) -> Option<(usize, f32)> {
+    match data_type {
+        1 => Some((1, i8::MAX as f32)),
+        2 => Some((1, u8::MAX as f32)),
+        3 => Some((2, i16::MAX as f32)),
+        4 => Some((2, u16::MAX as f32)),
+        5 => Some((4, i32::MAX as f32)),
+        6 => Some((4, u32::MAX as f32)),
+        _ => None,
+    }
+}
+
+/**
+ * Little endian integer of `data_type` as a float, mapped to [0, 1] or [-1, 1] if `normalized`.
+ */
+fn integer_value(bytes: &[u8], data_type: u8, normalized: bool) -> f32 {
+    let value = match data_type {
+        1 => bytes[0] as i8 as f32,
+        2 => bytes[0] as f32,
+        3 => i16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+        4 => u16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+        5 => i32::from_le_bytes(bytes.try_into().unwrap()) as f32,
+        _ => u32::from_le_bytes(bytes.try_into().unwrap()) as f32,
+    };
+    match (normalized, integer_type(data_type)) {
+        (true, Some((_, max))) => (value / max).max(-1.0),
+        _ => value,
+    }
+}
+
+/**
+ * Read the entropy coded integer values of one attribute and undo their prediction.
+ */
+fn decode_integers(
+    reader: &mut Reader,
+    context: &PredictionContext,
+    components: usize,
+) -> Result<Vec<i32>> {
+    let method = reader.i8()?;
+    let transform_kind = match method {
+        PREDICTION_NONE => None,
+        _ => Some(reader.i8()?),
+    };
+
+    let count = context.encoding.entry_count() * components;
+    let symbols = match reader.u8()? {
+        0 => {
+            let size = reader.u8()? as usize;
+            if !(1..=4).contains(&size) {
+                return Err(DracoError::Corrupt("value size"));
+            }
+            let bytes = reader.bytes(count * size)?;
+            bytes
+                .chunks_exact(size)
+                .map(|chunk| {
+                    chunk
+                        .iter()
+                        .rev()
+                        .fold(0u32, |value, byte| value << 8 | *byte as u32)
+                })
+                .collect()
+        }
+        _ => decode_symbols(reader, count, components)?,
+    };
+    let corrections: Vec<i32> = if transform_kind.is_some_and(Transform::corrections_positive) {
+        symbols.into_iter().map(|symbol| symbol as i32).collect()
+    } else {
+        symbols
+            .into_iter()
+            .map(|symbol| (symbol >> 1) as i32 ^ -((symbol & 1) as i32))
+            .collect()
+    };
+
+    match transform_kind {
+        None => Ok(corrections),
+        Some(transform_kind) => predict(
+            reader,
+            method,
+            transform_kind,
+            context,
+            &corrections,
+            components,
+        ),
+    }
+}
+
+fn decode_attributes(reader: &mut Reader, connectivity: &Connectivity) -> Result<Vec<Attribute>> {
+    let decoder_count = reader.u8()?;
+    let mut tables: Vec<&dyn Corners> = Vec::new();
+    for _ in 0..decoder_count {
+        let attribute_data = reader.i8()?;
+        let element = reader.u8()?;
+        if reader.u8()? != 0 {
+            return Err(DracoError::Unsupported("attribute traversal"));
+        }
+        tables.push(match usize::try_from(attribute_data) {
+            Ok(index) if element == 1 => connectivity
+                .attribute_tables
+                .get(index)
+                .ok_or(DracoError::Corrupt("attribute data"))?,
+            _ => &connectivity.table,
+        });
+    }
+
+    let mut headers = Vec::new();
+    for _ in 0..decoder_count {
+        let count = reader.varint_usize()?;
+        let mut decoder_headers = Vec::new();
+        for _ in 0..count {
+            let attribute_type = match reader.u8()? {
+                0 => AttributeType::Position,
+                1 => AttributeType::Normal,
+                2 => AttributeType::Color,
+                3 => AttributeType::TexCoord,
+                4 => AttributeType::Generic,
+                _ => return Err(DracoError::Corrupt("attribute type")),
+            };
+            let data_type = reader.u8()?;
+            let components = reader.u8()? as usize;
+            let normalized = reader.u8()? != 0;
+            let unique_id =
+                u32::try_from(reader.varint()?).map_err(|_| DracoError::Corrupt("attribute id"))?;
+            if components == 0 {
+                return Err(DracoError::Corrupt("attribute components"));
+            }
+            decoder_headers.push(AttributeHeader {
+                attribute_type,
+                data_type,
+                components,
+                normalized,
+                unique_id,
+                decoder_type: 0,
+            });
+        }
+        for header in &mut decoder_headers {
+            header.decoder_type = reader.u8()?;
+        }
+        headers.push(decoder_headers);
+    }
+
+    let corner_to_point = &connectivity.corner_to_point;
+    let mut positions: Option<Positions> = None;
+    let mut attributes = Vec::new();
+    for (table, headers) in tables.into_iter().zip(headers) {
+        let encoding = Encoding::traverse(table);
+        let entry_count = encoding.entry_count();
+        let mut point_to_entry = vec![INVALID; connectivity.point_count];
+        for (corner, point) in corner_to_point.iter().enumerate() {
+            point_to_entry[*point as usize] = encoding.entry(table, corner as u32);
+        }
+
+        // Values of every attribute come first, followed by their dequantization data.
+        let mut decoded = Vec::new();
+        for header in &headers {
+            let values = match header.decoder_type {
+                DECODER_GENERIC => {
+                    let size = match header.data_type {
+                        9 => 4,
+                        data_type => {
+                            integer_type(data_type)
+                                .ok_or(DracoError::Unsupported("attribute data type"))?
+                                .0
+                        }
+                    };
+                    let bytes = reader.bytes(entry_count * header.components * size)?;
+                    let values = bytes
+                        .chunks_exact(size)
+                        .map(|chunk| match header.data_type {
+                            9 => f32::from_le_bytes(chunk.try_into().unwrap()),
+                            data_type => integer_value(chunk, data_type, header.normalized),
+                        })
+                        .collect();
+                    Decoded::Floats(values)
+                }
+                DECODER_INTEGER | DECODER_QUANTIZATION | DECODER_NORMALS => {
+                    let components = match header.decoder_type {
+                        DECODER_NORMALS => 2,
+                        _ => header.components,
+                    };
+                    let context = PredictionContext {
+                        table,
+                        encoding: &encoding,
+                        corner_to_point,
+                        positions: positions.as_ref(),
+                    };
+                    let values = decode_integers(reader, &context, components)?;
+                    if header.attribute_type == AttributeType::Position && positions.is_none() {
+                        if header.components != 3 {
+                            return Err(DracoError::Unsupported("position components"));
+                        }
+                        positions = Some(Positions {
+                            values: values.clone(),
+                            point_to_entry: point_to_entry.clone(),
+                        });
+                    }
+                    Decoded::Integers(values)
+                }
+                _ => return Err(DracoError::Unsupported("attribute decoder")),
+            };
+            decoded.push(values);
+        }
+
+        for (header, values) in headers.iter().zip(decoded) {
+            let (components, values) = match values {
+                Decoded::Floats(values) => (header.components, values),
+                Decoded::Integers(values) => match header.decoder_type {
+                    DECODER_INTEGER => {
+                        let values = values
+                            .iter()
+                            .map(|value| match header.data_type {
+                                6 => integer_value(&value.to_le_bytes(), 6, header.normalized),
+                                data_type if header.normalized => {
+                                    let max = integer_type(data_type).map_or(1.0, |(_, max)| max);
+                                    (*value as f32 / max).max(-1.0)
+                                }
+                                _ => *value as f32,
+                            })
+                            .collect();
+                        (header.components, values)
+                    }
+                    DECODER_QUANTIZATION => {
+                        let mins = (0..header.components)
+                            .map(|_| reader.f32())
+                            .collect::<Result<Vec<_>>>()?;
+                        let range = reader.f32()?;
+                        let bits = reader.u8()? as u32;
+                        if !(1..=30).contains(&bits) {
+                            return Err(DracoError::Corrupt("quantization bits"));
+                        }
+                        let delta = range / ((1u32 << bits) - 1) as f32;
+                        let values = values
+                            .iter()
+                            .enumerate()
+                            .map(|(i, value)| *value as f32 * delta + mins[i % header.components])
+                            .collect();
+                        (header.components, values)
+                    }
+                    _ => {
+                        let octahedron = Octahedron::new(reader.u8()? as u32)?;
+                        let values = values
+                            .chunks_exact(2)
+                            .flat_map(|value| octahedron.to_unit_vector(value[0], value[1]))
+                            .collect();
+                        (3, values)
+                    }
+                },
+            };
+
+            let mut point_values = Vec::with_capacity(connectivity.point_count * components);
+            for entry in &point_to_entry {
+                let entry = *entry as usize;
+                point_values.extend_from_slice(
+                    values
+                        .get(entry * components..(entry + 1) * components)
+                        .ok_or(DracoError::Corrupt("attribute mapping"))?,
+                );
+            }
+            attributes.push(Attribute {
+                attribute_type: header.attribute_type,
+                unique_id: header.unique_id,
+                components,
+                values: point_values,
+            });
+        }
+    }
+    Ok(attributes)
+}
+
+/**
+ * Decode a Draco 2.2 mesh compressed with edgebreaker,
+ * the encoding `KHR_draco_mesh_compression` buffer views hold.
+ */
+pub fn decode(data: &[u8]) -> Result<DracoMesh> {
+    let mut reader = Reader::new(data);
+    if reader.bytes(5)? != b"DRACO" {
+        return Err(DracoError::Corrupt("header"));
+    }
+    if (reader.u8()?, reader.u8()?) != (2, 2) {
+        return Err(DracoError::Unsupported("version"));
+    }
+    match reader.u8()? {
+        1 => {}
+        0 => return Err(DracoError::Unsupported("point cloud")),
+        _ => return Err(DracoError::Corrupt("header")),
+    }
+    match reader.u8()? {
+        1 => {}
+        0 => return Err(DracoError::Unsupported("sequential connectivity")),
+        _ => return Err(DracoError::Corrupt("header")),
+    }
+    let flags = u16::from_le_bytes([reader.u8()?, reader.u8()?]);
+    if flags & 0x8000 != 0 {
+        return Err(DracoError::Unsupported("metadata"));
+    }
+
+    let connectivity = decode_connectivity(&mut reader)?;
+    let attributes = decode_attributes(&mut reader, &connectivity)?;
+    Ok(DracoMesh {
+        indices: connectivity.corner_to_point,
+        point_count: connectivity.point_count,
+        attributes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn duck() -> Vec<u8> {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/models/Duck/glTF-Draco/Duck.bin"
+        );
+        std::fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn test_decode_duck() {
+        let mesh = decode(&duck()).unwrap();
+        assert_eq!(mesh.point_count, 2399);
+        assert_eq!(mesh.indices.len(), 4212 * 3);
+        assert!(mesh
+            .indices
+            .iter()
+            .all(|i| (*i as usize) < mesh.point_count));
+
+        let types: Vec<_> = mesh.attributes.iter().map(|a| a.attribute_type).collect();
+        assert_eq!(
+            types,
+            [
+                AttributeType::Position,
+                AttributeType::TexCoord,
+                AttributeType::Normal
+            ]
+        );
+        for attribute in &mesh.attributes {
+            assert_eq!(
+                attribute.values.len(),
+                mesh.point_count * attribute.components
+            );
+        }
+        let normal = mesh.attribute(0).unwrap().value(0);
+        let norm = normal.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let data = duck();
+        assert_eq!(
+            decode(&data[..data.len() / 2]).unwrap_err(),
+            DracoError::Truncated
+        );
+        assert_eq!(
+            decode(b"DRACU\x02\x02\x01\x01\x00\x00").unwrap_err(),
+            DracoError::Corrupt("header")
+        );
+
+        let mut old = data.clone();
+        old[5] = 1;
+        assert_eq!(
+            decode(&old).unwrap_err(),
+            DracoError::Unsupported("version")
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use crate::common::basetype::Bbox3;
+use nalgebra::{Matrix4, Vector3, Vector4};
+
+/**
+ * Where a volume lies relative to a frustum.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Containment {
+    Outside,
+    Intersecting,
+    Inside,
+}
+
+/**
+ * The volume a view projection keeps, as six planes `(a, b, c, d)` with points where
+ * `a x + b y + c z + d >= 0` inside and unit normals `(a, b, c)` pointing inwards.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /**
+     * Left, right, bottom, top, near and far.
+     */
+    pub planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /**
+     * Extract the planes of the clip volume `-w <= x, y, z <= w` (Gribb and Hartmann),
+     * in the space `view_projection` transforms from. The far plane of an infinite
+     * projection keeps everything.
+     */
+    pub fn from_matrix(view_projection: &Matrix4<f32>) -> Self {
+        let row = |i: usize| view_projection.row(i).transpose();
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z].map(|plane| {
+            let length = plane.xyz().norm();
+            if length > f32::EPSILON * plane.w.abs().max(1.0) {
+                plane / length
+            } else {
+                // Degenerate, like the far plane at infinity: everything or nothing is inside.
+                Vector4::new(0.0, 0.0, 0.0, plane.w.signum())
+            }
+        });
+        Self { planes }
+    }
+
+    /**
+     * Signed distance of `point` to each plane, positive inside.
+     */
+    fn distances(&self, point: &Vector3<f32>) -> impl Iterator<Item = f32> + '_ {
+        let point = point.push(1.0);
+        self.planes.iter().map(move |plane| plane.dot(&point))
+    }
+
+    pub fn contains_point(&self, point: &Vector3<f32>) -> bool {
+        self.distances(point).all(|distance| distance >= 0.0)
+    }
+
+    pub fn sphere_containment(&self, center: &Vector3<f32>, radius: f32) -> Containment {
+        let mut containment = Containment::Inside;
+        for distance in self.distances(center) {
+            if distance < -radius {
+                return Containment::Outside;
+            }
+            if distance < radius {
+                containment = Containment::Intersecting;
+            }
+        }
+        containment
+    }
+
+    /**
+     * Whether the sphere may be visible. Spheres near the corners of the frustum
+     * may be kept even though they are outside.
+     */
+    pub fn intersects_sphere(&self, center: &Vector3<f32>, radius: f32) -> bool {
+        self.sphere_containment(center, radius) != Containment::Outside
+    }
+
+    /**
+     * Test the corners of `bounds` nearest and farthest along each plane normal.
+     */
+    pub fn bbox_containment(&self, bounds: &Bbox3<f32>) -> Containment {
+        let mut containment = Containment::Inside;
+        for plane in &self.planes {
+            let corner = |positive: bool, low: f32, high: f32| if positive { high } else { low };
+            let farthest = Vector3::new(
+                corner(plane.x >= 0.0, bounds.l, bounds.r),
+                corner(plane.y >= 0.0, bounds.b, bounds.t),
+                corner(plane.z >= 0.0, bounds.n, bounds.f),
+            );
+            let nearest = Vector3::new(
+                corner(plane.x < 0.0, bounds.l, bounds.r),
+                corner(plane.y < 0.0, bounds.b, bounds.t),
+                corner(plane.z < 0.0, bounds.n, bounds.f),
+            );
+            if plane.dot(&farthest.push(1.0)) < 0.0 {
+                return Containment::Outside;
+            }
+            if plane.dot(&nearest.push(1.0)) < 0.0 {
+                containment = Containment::Intersecting;
+            }
+        }
+        containment
+    }
+
+    /**
+     * Whether the box may be visible. Boxes near the corners of the frustum
+     * may be kept even though they are outside.
+     */
+    pub fn intersects_bbox(&self, bounds: &Bbox3<f32>) -> bool {
+        self.bbox_containment(bounds) != Containment::Outside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::camera::{Projection, View};
+    use nalgebra::Point3;
+
+    fn view(zfar: Option<f32>) -> Matrix4<f32> {
+        // Looking down -Z from the origin, with a 90 degree field of view.
+        View::look_at(
+            Point3::origin(),
+            Point3::new(0.0, 0.0, -1.0),
+            Vector3::y(),
+            &Projection::Perspective {
+                yfov: std::f32::consts::FRAC_PI_2,
+                aspect_ratio: None,
+                znear: 1.0,
+                zfar,
+            },
+            1.0,
+        )
+        .view_projection()
+    }
+
+    #[test]
+    fn test_planes() {
+        let frustum = Frustum::from_matrix(&view(Some(10.0)));
+        assert!(frustum.contains_point(&Vector3::new(0.0, 0.0, -5.0)));
+        assert!(frustum.contains_point(&Vector3::new(4.9, -4.9, -5.0)));
+        assert!(!frustum.contains_point(&Vector3::new(5.1, 0.0, -5.0)));
+        assert!(!frustum.contains_point(&Vector3::new(0.0, 0.0, -0.5)));
+        assert!(!frustum.contains_point(&Vector3::new(0.0, 0.0, -11.0)));
+        assert!(!frustum.contains_point(&Vector3::new(0.0, 0.0, 5.0)));
+
+        // Without a far plane, distant points stay inside.
+        let infinite = Frustum::from_matrix(&view(None));
+        assert!(infinite.contains_point(&Vector3::new(0.0, 0.0, -1e6)));
+        assert!(!infinite.contains_point(&Vector3::new(0.0, 0.0, 5.0)));
+    }
+
+    #[test]
+    fn test_spheres_and_boxes() {
+        let frustum = Frustum::from_matrix(&view(Some(10.0)));
+        let center = Vector3::new(0.0, 0.0, -5.0);
+        assert_eq!(
+            frustum.sphere_containment(&center, 1.0),
+            Containment::Inside
+        );
+        assert_eq!(
+            frustum.sphere_containment(&Vector3::new(0.0, 0.0, -10.0), 1.0),
+            Containment::Intersecting
+        );
+        assert!(!frustum.intersects_sphere(&Vector3::new(0.0, 0.0, 3.0), 1.0));
+
+        let bbox = |x: f32, z: f32| Bbox3::new(x - 0.5, x + 0.5, -0.5, 0.5, z - 0.5, z + 0.5);
+        assert_eq!(
+            frustum.bbox_containment(&bbox(0.0, -5.0)),
+            Containment::Inside
+        );
+        assert_eq!(
+            frustum.bbox_containment(&bbox(5.0, -5.0)),
+            Containment::Intersecting
+        );
+        assert_eq!(
+            frustum.bbox_containment(&bbox(7.0, -5.0)),
+            Containment::Outside
+        );
+        assert!(!frustum.intersects_bbox(&bbox(0.0, 2.0)));
+        assert!(!frustum.intersects_bbox(&bbox(0.0, -11.0)));
+    }
+}
@@ -0,0 +1,539 @@
+use crate::{
+    common::basetype::Bbox3,
+    scene::mesh::{Mesh, Primitive, Vertex},
+};
+use nalgebra::{Matrix4, Vector2, Vector3};
+use std::{collections::HashMap, f32::consts::PI};
+
+/**
+ * A sphere enclosing a set of points, not necessarily the smallest one.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vector3<f32>,
+
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    /**
+     * Ritter's approximation, at most about 5% larger than the minimal sphere.
+     * `None` without points.
+     */
+    pub fn from_points(points: &[Vector3<f32>]) -> Option<Self> {
+        let first = points.first()?;
+        let farthest = |from: &Vector3<f32>| {
+            *points
+                .iter()
+                .max_by(|a, b| {
+                    (*a - from)
+                        .norm_squared()
+                        .total_cmp(&(*b - from).norm_squared())
+                })
+                .unwrap()
+        };
+        let a = farthest(first);
+        let b = farthest(&a);
+        let mut sphere = Self {
+            center: (a + b) / 2.0,
+            radius: (b - a).norm() / 2.0,
+        };
+        for point in points {
+            sphere.grow(point);
+        }
+        Some(sphere)
+    }
+
+    /**
+     * Move and enlarge the sphere just enough to enclose `point`.
+     */
+    pub fn grow(&mut self, point: &Vector3<f32>) {
+        let offset = point - self.center;
+        let distance = offset.norm();
+        if distance > self.radius {
+            let radius = (self.radius + distance) / 2.0;
+            self.center += offset * ((radius - self.radius) / distance);
+            self.radius = radius;
+        }
+    }
+
+    /**
+     * A sphere enclosing this one after `transform`, scaled by its largest axis.
+     */
+    pub fn transform(&self, transform: &Matrix4<f32>) -> Self {
+        let scale = (0..3)
+            .map(|i| transform.fixed_view::<3, 1>(0, i).norm())
+            .fold(0.0, f32::max);
+        Self {
+            center: transform.transform_point(&self.center.into()).coords,
+            radius: self.radius * scale,
+        }
+    }
+}
+
+/**
+ * Axis-aligned bounds enclosing `bounds` after an affine `transform`.
+ */
+pub fn transform_bounds(bounds: &Bbox3<f32>, transform: &Matrix4<f32>) -> Bbox3<f32> {
+    let corners = bounds
+        .corners()
+        .map(|corner| transform.transform_point(&corner.into()).coords);
+    Bbox3::from_vector3(&corners.iter().collect())
+}
+
+/**
+ * How `Primitive::generate_tex_coords` maps positions to texture coordinates.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UvProjection {
+    /**
+     * Along `normal` onto a plane, stretched to the bounds of the projected positions.
+     */
+    Planar { normal: Vector3<f32> },
+
+    /**
+     * Longitude and latitude around the center of the bounds, with `v` growing
+     * downwards from the +Y pole. Triangles crossing the -Z seam get copies of their
+     * vertices at its start with `u` past 1, so that they wrap around.
+     */
+    Spherical,
+}
+
+/**
+ * Bitwise identity of a position, to share normals between vertices split by other attributes.
+ */
+fn position_key(vertex: &Vertex) -> [u32; 3] {
+    vertex.position.xyz().map(|x| x.to_bits()).into()
+}
+
+impl Primitive {
+    fn positions(&self) -> Vec<Vector3<f32>> {
+        self.vertices
+            .iter()
+            .map(|vertex| vertex.position.xyz())
+            .collect()
+    }
+
+    /**
+     * Axis-aligned bounds of the vertices, `None` without any.
+     */
+    pub fn bounds(&self) -> Option<Bbox3<f32>> {
+        let positions = self.positions();
+        (!positions.is_empty()).then(|| Bbox3::from_vector3(&positions.iter().collect()))
+    }
+
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        BoundingSphere::from_points(&self.positions())
+    }
+
+    /**
+     * Replace the vertices by `vertices[i] = old[sources[i]]`, keeping morph targets in sync.
+     * Indices are left untouched.
+     */
+    pub fn remap_vertices(&mut self, sources: &[usize]) {
+        self.vertices = sources.iter().map(|&i| self.vertices[i]).collect();
+        for target in &mut self.targets {
+            for deltas in [
+                &mut target.positions,
+                &mut target.normals,
+                &mut target.tangents,
+            ] {
+                if !deltas.is_empty() {
+                    *deltas = sources.iter().map(|&i| deltas[i]).collect();
+                }
+            }
+        }
+    }
+
+    /**
+     * Give every triangle its own vertices, with the normal of its plane.
+     * Vertices of degenerate triangles keep their normal.
+     */
+    pub fn generate_flat_normals(&mut self) {
+        let sources: Vec<usize> = self.indices.iter().map(|&i| i as usize).collect();
+        self.remap_vertices(&sources);
+        self.indices = (0..self.vertices.len() as u32).collect();
+
+        for triangle in self.vertices.chunks_exact_mut(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| triangle[i].position.xyz());
+            if let Some(normal) = (b - a).cross(&(c - a)).try_normalize(0.0) {
+                for vertex in triangle {
+                    vertex.normal = Some(normal);
+                }
+            }
+        }
+    }
+
+    /**
+     * Average the normals of the triangles around each position, weighted by their angle
+     * at the corner, so the result does not depend on how the surface is tessellated.
+     * Vertices at the same position share the normal, even when split by other attributes.
+     * Vertices outside of any non-degenerate triangle keep their normal.
+     */
+    pub fn generate_smooth_normals(&mut self) {
+        let mut sums = HashMap::<[u32; 3], Vector3<f32>>::new();
+        for triangle in self.indices.chunks_exact(3) {
+            let corners = [0, 1, 2].map(|i| &self.vertices[triangle[i] as usize]);
+            let [a, b, c] = corners.map(|vertex| vertex.position.xyz());
+            let Some(normal) = (b - a).cross(&(c - a)).try_normalize(0.0) else {
+                continue;
+            };
+            for (i, vertex) in corners.iter().enumerate() {
+                let p = [a, b, c][i];
+                let (e0, e1) = ([a, b, c][(i + 1) % 3] - p, [a, b, c][(i + 2) % 3] - p);
+                let angle = e0.angle(&e1);
+                *sums
+                    .entry(position_key(vertex))
+                    .or_insert_with(Vector3::zeros) += normal * angle;
+            }
+        }
+        for vertex in &mut self.vertices {
+            if let Some(normal) = sums
+                .get(&position_key(vertex))
+                .and_then(|sum| sum.try_normalize(0.0))
+            {
+                vertex.normal = Some(normal);
+            }
+        }
+    }
+
+    /**
+     * Merge vertices whose attributes and morph target deltas all round to the same
+     * multiple of `epsilon`, or are bitwise equal when zero, and drop the vertices no
+     * index refers to.
+     * Rounding may keep apart values closer than `epsilon` that straddle a boundary.
+     * Returns the number of vertices removed.
+     */
+    pub fn weld(&mut self, epsilon: f32) -> usize {
+        let quantize = |x: f32| {
+            if epsilon > 0.0 {
+                ((x / epsilon).round() as i64).to_le_bytes()
+            } else {
+                (x.to_bits() as i64).to_le_bytes()
+            }
+        };
+        let key = |index: usize| -> Vec<u8> {
+            let vertex = &self.vertices[index];
+            let mut key = Vec::new();
+            let mut push = |values: &[f32]| key.extend(values.iter().flat_map(|&x| quantize(x)));
+            push(vertex.position.as_slice());
+            // Tags keep a missing attribute apart from a present one.
+            push(&[vertex.normal.is_some() as u8 as f32]);
+            push(vertex.normal.unwrap_or_default().as_slice());
+            push(&[vertex.color.is_some() as u8 as f32]);
+            let color = vertex.color.unwrap_or_default();
+            push(&[color.r, color.g, color.b, color.a]);
+            push(&[vertex.tex_coord.is_some() as u8 as f32]);
+            push(vertex.tex_coord.unwrap_or_default().as_slice());
+            push(&[vertex.tex_coord_1.is_some() as u8 as f32]);
+            push(vertex.tex_coord_1.unwrap_or_default().as_slice());
+            push(&[vertex.tangent.is_some() as u8 as f32]);
+            push(vertex.tangent.unwrap_or_default().as_slice());
+            let joints = vertex.joints.map(|joints| joints.map(f32::from));
+            push(&[joints.is_some() as u8 as f32]);
+            push(&joints.unwrap_or_default());
+            push(&[vertex.weights.is_some() as u8 as f32]);
+            push(vertex.weights.unwrap_or_default().as_slice());
+            for target in &self.targets {
+                for deltas in [&target.positions, &target.normals, &target.tangents] {
+                    if let Some(delta) = deltas.get(index) {
+                        push(delta.as_slice());
+                    }
+                }
+            }
+            key
+        };
+
+        let mut lookup = HashMap::new();
+        let mut sources = Vec::new();
+        for index in &mut self.indices {
+            let vertex = *index as usize;
+            *index = *lookup.entry(key(vertex)).or_insert_with(|| {
+                sources.push(vertex);
+                (sources.len() - 1) as u32
+            });
+        }
+        let removed = self.vertices.len() - sources.len();
+        self.remap_vertices(&sources);
+        removed
+    }
+
+    /**
+     * Fill in `tex_coord` of every vertex from its position, for meshes lacking them.
+     */
+    pub fn generate_tex_coords(&mut self, projection: UvProjection) {
+        let Some(bounds) = self.bounds() else {
+            return;
+        };
+        match projection {
+            UvProjection::Planar { normal } => {
+                let Some(normal) = normal.try_normalize(0.0) else {
+                    return;
+                };
+                // Any tangent works, pick the axis least aligned with the normal.
+                let axis = Vector3::ith(normal.iamin(), 1.0);
+                let u_axis = axis.cross(&normal).normalize();
+                let v_axis = u_axis.cross(&normal);
+                let projected: Vec<Vector2<f32>> = self
+                    .positions()
+                    .iter()
+                    .map(|p| Vector2::new(p.dot(&u_axis), p.dot(&v_axis)))
+                    .collect();
+                let min = projected
+                    .iter()
+                    .fold(Vector2::repeat(f32::INFINITY), |m, p| m.inf(p));
+                let max = projected
+                    .iter()
+                    .fold(Vector2::repeat(f32::NEG_INFINITY), |m, p| m.sup(p));
+                let extent = (max - min).map(|x| if x > 0.0 { x } else { 1.0 });
+                for (vertex, p) in self.vertices.iter_mut().zip(projected) {
+                    vertex.tex_coord = Some((p - min).component_div(&extent));
+                }
+            }
+            UvProjection::Spherical => {
+                let center = Vector3::new(
+                    (bounds.l + bounds.r) / 2.0,
+                    (bounds.b + bounds.t) / 2.0,
+                    (bounds.n + bounds.f) / 2.0,
+                );
+                for vertex in &mut self.vertices {
+                    let Some(d) = (vertex.position.xyz() - center).try_normalize(0.0) else {
+                        vertex.tex_coord = Some(Vector2::new(0.5, 0.5));
+                        continue;
+                    };
+                    let u = d.x.atan2(d.z) / (2.0 * PI) + 0.5;
+                    let v = d.y.clamp(-1.0, 1.0).acos() / PI;
+                    vertex.tex_coord = Some(Vector2::new(u, v));
+                }
+
+                // Spanning more than half a turn, a triangle goes the short way across the seam.
+                let mut sources: Vec<usize> = (0..self.vertices.len()).collect();
+                let mut wrapped: HashMap<u32, u32> = HashMap::new();
+                let u = |vertex: &Vertex| vertex.tex_coord.unwrap().x;
+                for triangle in self.indices.chunks_exact_mut(3) {
+                    let us = [0, 1, 2].map(|i| u(&self.vertices[triangle[i] as usize]));
+                    let (min, max) = (
+                        us.iter().copied().fold(f32::INFINITY, f32::min),
+                        us.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+                    );
+                    if max - min <= 0.5 {
+                        continue;
+                    }
+                    for (index, u) in triangle.iter_mut().zip(us) {
+                        if u < 0.5 {
+                            *index = *wrapped.entry(*index).or_insert_with(|| {
+                                sources.push(*index as usize);
+                                (sources.len() - 1) as u32
+                            });
+                        }
+                    }
+                }
+                if !wrapped.is_empty() {
+                    self.remap_vertices(&sources);
+                    for &copy in wrapped.values() {
+                        if let Some(tex_coord) = &mut self.vertices[copy as usize].tex_coord {
+                            tex_coord.x += 1.0;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Mesh {
+    /**
+     * Axis-aligned bounds of all primitives, in the space of the mesh.
+     */
+    pub fn bounds(&self) -> Option<Bbox3<f32>> {
+        let positions: Vec<Vector3<f32>> = self
+            .primitives
+            .iter()
+            .flat_map(Primitive::positions)
+            .collect();
+        (!positions.is_empty()).then(|| Bbox3::from_vector3(&positions.iter().collect()))
+    }
+
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        let positions: Vec<Vector3<f32>> = self
+            .primitives
+            .iter()
+            .flat_map(Primitive::positions)
+            .collect();
+        BoundingSphere::from_points(&positions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::morph::MorphTarget;
+
+    /**
+     * A unit square in the XY plane, split along the diagonal into two triangles
+     * with their own vertices.
+     */
+    fn square() -> Primitive {
+        let positions = [
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [1.0, 1.0],
+            [0.0, 0.0],
+            [1.0, 1.0],
+            [0.0, 1.0],
+        ];
+        Primitive {
+            vertices: positions
+                .iter()
+                .map(|&[x, y]| Vertex::new(Vector3::new(x, y, 0.0)))
+                .collect(),
+            indices: (0..6).collect(),
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_weld() {
+        let mut primitive = square();
+        assert_eq!(primitive.weld(0.0), 2);
+        assert_eq!(primitive.vertices.len(), 4);
+        assert_eq!(primitive.indices, vec![0, 1, 2, 0, 2, 3]);
+
+        // Close positions merge within epsilon, differing attributes stay apart.
+        let mut primitive = square();
+        primitive.vertices[3].position.x = 1e-4;
+        primitive.vertices[4].tex_coord = Some(Vector2::zeros());
+        assert_eq!(primitive.weld(1e-3), 1);
+        assert_eq!(primitive.indices, vec![0, 1, 2, 0, 3, 4]);
+
+        // Vertices moving apart under a morph target stay apart, keeping their deltas.
+        let mut primitive = square();
+        let mut positions = vec![Vector3::zeros(); 6];
+        positions[3] = Vector3::z();
+        primitive.targets = vec![MorphTarget {
+            positions,
+            normals: Vec::new(),
+            tangents: Vec::new(),
+        }];
+        assert_eq!(primitive.weld(0.0), 1);
+        assert_eq!(primitive.indices, vec![0, 1, 2, 3, 2, 4]);
+        let target = &primitive.targets[0];
+        assert_eq!(target.positions.len(), 5);
+        assert_eq!(target.positions[0], Vector3::zeros());
+        assert_eq!(target.positions[3], Vector3::z());
+    }
+
+    #[test]
+    fn test_normals() {
+        // Fold the square along its diagonal into a right angle.
+        let mut primitive = square();
+        primitive.vertices[5].position = Vector3::new(0.0, 0.0, 1.0).push(1.0);
+        primitive.weld(0.0);
+
+        let mut flat = primitive.clone();
+        flat.generate_flat_normals();
+        assert_eq!(flat.vertices.len(), 6);
+        assert_eq!(flat.vertex(0).normal, Some(Vector3::z()));
+        let upright = Vector3::new(1.0, -1.0, 0.0).normalize();
+        assert!((flat.vertex(3).normal.unwrap() - upright).norm() < 1e-6);
+
+        primitive.generate_smooth_normals();
+        // At the origin, the flat triangle has a 45 degree corner and the upright one 90.
+        let expected = (Vector3::z() + upright * 2.0).normalize();
+        let normal = primitive.vertices[0].normal.unwrap();
+        assert!((normal - expected).norm() < 1e-6, "{:?}", normal);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let primitive = square();
+        let bounds = primitive.bounds().unwrap();
+        assert_eq!(
+            [bounds.l, bounds.r, bounds.b, bounds.t, bounds.n, bounds.f],
+            [0.0, 1.0, 0.0, 1.0, 0.0, 0.0]
+        );
+        let sphere = primitive.bounding_sphere().unwrap();
+        for vertex in &primitive.vertices {
+            assert!((vertex.position.xyz() - sphere.center).norm() <= sphere.radius + 1e-6);
+        }
+        assert!(sphere.radius <= 0.5_f32.sqrt() * 1.05);
+
+        let moved = sphere.transform(&Matrix4::new_nonuniform_scaling(&Vector3::new(
+            1.0, 3.0, 1.0,
+        )));
+        assert!((moved.radius - sphere.radius * 3.0).abs() < 1e-6);
+        assert!(Primitive {
+            vertices: Vec::new(),
+            ..square()
+        }
+        .bounds()
+        .is_none());
+    }
+
+    #[test]
+    fn test_generate_tex_coords() {
+        let mut primitive = square();
+        primitive.generate_tex_coords(UvProjection::Planar {
+            normal: Vector3::z(),
+        });
+        let uvs: Vec<_> = primitive
+            .vertices
+            .iter()
+            .map(|v| v.tex_coord.unwrap())
+            .collect();
+        // Opposite corners map to opposite corners of the texture.
+        assert!(((uvs[0] - uvs[2]).abs() - Vector2::repeat(1.0)).norm() < 1e-6);
+        assert!(uvs
+            .iter()
+            .all(|uv| uv.x >= 0.0 && uv.x <= 1.0 && uv.y >= 0.0 && uv.y <= 1.0));
+
+        let mut primitive = square();
+        primitive.generate_tex_coords(UvProjection::Spherical);
+        let uvs: Vec<_> = primitive
+            .vertices
+            .iter()
+            .map(|v| v.tex_coord.unwrap())
+            .collect();
+        // (1, 1) is above the center and (1, 0) below, at the same longitude.
+        assert!(uvs[2].y < 0.5 && uvs[1].y > 0.5);
+        assert!((uvs[2].x - uvs[1].x).abs() < 1e-6);
+
+        // A ring around Y whose last quad crosses the -Z seam.
+        let segments = 8;
+        let mut ring = square();
+        ring.vertices = (0..segments)
+            .flat_map(|i| {
+                let angle = (i as f32 + 0.5) / segments as f32 * 2.0 * PI;
+                let (x, z) = (angle.sin(), angle.cos());
+                [
+                    Vertex::new(Vector3::new(x, -0.5, z)),
+                    Vertex::new(Vector3::new(x, 0.5, z)),
+                ]
+            })
+            .collect();
+        ring.indices = (0..segments)
+            .flat_map(|i| {
+                let (a, b) = (2 * i, 2 * ((i + 1) % segments));
+                [a, b, b + 1, a, b + 1, a + 1]
+            })
+            .collect();
+        ring.generate_tex_coords(UvProjection::Spherical);
+        // Two copies for the vertices at the start of the texture.
+        assert_eq!(ring.vertices.len(), 2 * segments as usize + 2);
+        for triangle in ring.indices.chunks_exact(3) {
+            let us = triangle
+                .iter()
+                .map(|&i| ring.vertices[i as usize].tex_coord.unwrap().x);
+            let (min, max) = us.fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), u| {
+                (min.min(u), max.max(u))
+            });
+            assert!(max - min <= 1.0 / segments as f32 + 1e-6, "{min} {max}");
+        }
+        assert!(ring
+            .vertices
+            .iter()
+            .all(|v| v.tex_coord.unwrap().x <= 1.0 + 1.0 / segments as f32));
+    }
+}
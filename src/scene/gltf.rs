@@ -0,0 +1,1297 @@
+use crate::{
+    common::color::Color,
+    rasterizer::texture::{Data, Format, Texture},
+    scene::{
+        animation::{Animation, Channel, Interpolation, Property, Sampler},
+        camera::{Camera, Projection},
+        draco::{self, DracoError, DracoMesh},
+        light::{Light, LightKind},
+        material::{Material, TextureInfo, TextureTransform},
+        mesh::{Mesh, Primitive, Vertex},
+        morph::MorphTarget,
+        node::{Node, SceneGraph, Transform},
+        skin::Skin,
+        Scene,
+    },
+};
+use ::gltf::{
+    accessor::{DataType, Item, Iter},
+    mesh::{Mode, Semantic},
+};
+use nalgebra::{Matrix4, Quaternion, UnitQuaternion, Vector2, Vector3, Vector4};
+use std::{collections::HashMap, fmt, path::Path};
+
+#[derive(Debug)]
+pub enum GltfError {
+    Import(::gltf::Error),
+    UnsupportedMode {
+        mesh: usize,
+        primitive: usize,
+        mode: Mode,
+    },
+    MissingPositions {
+        mesh: usize,
+        primitive: usize,
+    },
+    IndexOutOfRange {
+        mesh: usize,
+        primitive: usize,
+        index: u32,
+    },
+    UnsupportedImageFormat {
+        image: usize,
+        format: Format,
+    },
+    Draco {
+        mesh: usize,
+        primitive: usize,
+        error: DracoError,
+    },
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GltfError::Import(err) => write!(f, "failed to import glTF: {}", err),
+            GltfError::UnsupportedMode {
+                mesh,
+                primitive,
+                mode,
+            } => write!(
+                f,
+                "mesh {} primitive {}: unsupported mode {:?}",
+                mesh, primitive, mode
+            ),
+            GltfError::MissingPositions { mesh, primitive } => {
+                write!(
+                    f,
+                    "mesh {} primitive {}: missing positions",
+                    mesh, primitive
+                )
+            }
+            GltfError::IndexOutOfRange {
+                mesh,
+                primitive,
+                index,
+            } => write!(
+                f,
+                "mesh {} primitive {}: index {} out of range",
+                mesh, primitive, index
+            ),
+            GltfError::UnsupportedImageFormat { image, format } => {
+                write!(f, "image {}: unsupported format {:?}", image, format)
+            }
+            GltfError::Draco {
+                mesh,
+                primitive,
+                error,
+            } => write!(f, "mesh {} primitive {}: {}", mesh, primitive, error),
+        }
+    }
+}
+
+impl std::error::Error for GltfError {}
+
+impl From<::gltf::Error> for GltfError {
+    fn from(err: ::gltf::Error) -> Self {
+        GltfError::Import(err)
+    }
+}
+
+/**
+ * Load a `.gltf` or `.glb` file, together with its external buffers and images.
+ */
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Scene, GltfError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(::gltf::Error::Io)?;
+    import(&bytes, path.parent())
+}
+
+/**
+ * Load a glTF asset from memory, it must not reference external files.
+ */
+pub fn load_slice(bytes: &[u8]) -> Result<Scene, GltfError> {
+    import(bytes, None)
+}
+
+/**
+ * Extensions decoded here rather than by `::gltf`, which reports them as unsupported
+ * when an asset requires them.
+ */
+const IMPLEMENTED_EXTENSIONS: &[&str] = &["KHR_draco_mesh_compression", "KHR_mesh_quantization"];
+
+/**
+ * Import like `::gltf::import`, but accept assets requiring `IMPLEMENTED_EXTENSIONS`,
+ * and the accessors of `KHR_draco_mesh_compression` primitives, which have no buffer
+ * view since their data is compressed.
+ */
+fn import(bytes: &[u8], base: Option<&Path>) -> Result<Scene, GltfError> {
+    use ::gltf::json::validation::Error;
+
+    let ::gltf::Gltf { document, blob } = match ::gltf::Gltf::from_slice(bytes) {
+        Err(::gltf::Error::Validation(errors)) => {
+            let gltf = ::gltf::Gltf::from_slice_without_validation(bytes)?;
+            let uses_draco = gltf
+                .extensions_used()
+                .any(|name| name == "KHR_draco_mesh_compression");
+            let required: Vec<&str> = gltf.extensions_required().collect();
+            let accepted = errors.iter().all(|(path, error)| {
+                let path = path.as_str();
+                match error {
+                    Error::Missing => {
+                        uses_draco
+                            && path.starts_with("accessors[")
+                            && path.ends_with("].bufferView")
+                    }
+                    // Reported as `extensionsRequired[i] = "name"`.
+                    Error::Unsupported => path
+                        .strip_prefix("extensionsRequired[")
+                        .and_then(|rest| rest.split(']').next()?.parse::<usize>().ok())
+                        .and_then(|i| required.get(i))
+                        .is_some_and(|name| IMPLEMENTED_EXTENSIONS.contains(name)),
+                    _ => false,
+                }
+            });
+            if !accepted {
+                return Err(::gltf::Error::Validation(errors).into());
+            }
+            gltf
+        }
+        result => result?,
+    };
+    let buffers = ::gltf::import_buffers(&document, base, blob)?;
+    let images = ::gltf::import_images(&document, base, &buffers)?;
+    from_import(&document, &buffers, &images)
+}
+
+pub fn from_import(
+    document: &::gltf::Document,
+    buffers: &[::gltf::buffer::Data],
+    images: &[Data],
+) -> Result<Scene, GltfError> {
+    Ok(Scene {
+        meshes: load_meshes(document, buffers)?,
+        textures: load_textures(document, images)?,
+        materials: load_materials(document),
+        cameras: load_cameras(document),
+        lights: load_lights(document),
+        skins: load_skins(document, buffers),
+        animations: load_animations(document, buffers),
+        nodes: load_nodes(document),
+        graphs: document
+            .scenes()
+            .map(|scene| SceneGraph {
+                name: scene.name().map(String::from),
+                roots: scene.nodes().map(|node| node.index()).collect(),
+            })
+            .collect(),
+        default_graph: document.default_scene().map(|scene| scene.index()),
+    })
+}
+
+pub fn load_nodes(document: &::gltf::Document) -> Vec<Node> {
+    document
+        .nodes()
+        .map(|node| Node {
+            name: node.name().map(String::from),
+            transform: match node.transform() {
+                ::gltf::scene::Transform::Matrix { matrix } => {
+                    Transform::Matrix(Matrix4::from(matrix))
+                }
+                ::gltf::scene::Transform::Decomposed {
+                    translation,
+                    rotation: [x, y, z, w],
+                    scale,
+                } => Transform::Trs {
+                    translation: Vector3::from(translation),
+                    rotation: UnitQuaternion::from_quaternion(Quaternion::new(w, x, y, z)),
+                    scale: Vector3::from(scale),
+                },
+            },
+            mesh: node.mesh().map(|mesh| mesh.index()),
+            camera: node.camera().map(|camera| camera.index()),
+            light: node.light().map(|light| light.index()),
+            skin: node.skin().map(|skin| skin.index()),
+            weights: node.weights().map(|weights| weights.to_vec()),
+            children: node.children().map(|child| child.index()).collect(),
+        })
+        .collect()
+}
+
+pub fn load_cameras(document: &::gltf::Document) -> Vec<Camera> {
+    document
+        .cameras()
+        .map(|camera| Camera {
+            name: camera.name().map(String::from),
+            projection: match camera.projection() {
+                ::gltf::camera::Projection::Perspective(perspective) => Projection::Perspective {
+                    yfov: perspective.yfov(),
+                    aspect_ratio: perspective.aspect_ratio(),
+                    znear: perspective.znear(),
+                    zfar: perspective.zfar(),
+                },
+                ::gltf::camera::Projection::Orthographic(orthographic) => {
+                    Projection::Orthographic {
+                        xmag: orthographic.xmag(),
+                        ymag: orthographic.ymag(),
+                        znear: orthographic.znear(),
+                        zfar: orthographic.zfar(),
+                    }
+                }
+            },
+        })
+        .collect()
+}
+
+/**
+ * Lights of the `KHR_lights_punctual` extension.
+ */
+pub fn load_lights(document: &::gltf::Document) -> Vec<Light> {
+    let Some(lights) = document.lights() else {
+        return Vec::new();
+    };
+    lights
+        .map(|light| {
+            let [r, g, b] = light.color();
+            Light {
+                name: light.name().map(String::from),
+                kind: match light.kind() {
+                    ::gltf::khr_lights_punctual::Kind::Directional => LightKind::Directional,
+                    ::gltf::khr_lights_punctual::Kind::Point => LightKind::Point,
+                    ::gltf::khr_lights_punctual::Kind::Spot {
+                        inner_cone_angle,
+                        outer_cone_angle,
+                    } => LightKind::Spot {
+                        inner_cone_angle,
+                        outer_cone_angle,
+                    },
+                },
+                color: Color::new(r, g, b, 1.0),
+                intensity: light.intensity(),
+                range: light.range(),
+            }
+        })
+        .collect()
+}
+
+pub fn load_materials(document: &::gltf::Document) -> Vec<Material> {
+    // A texture transform may override the texture coordinate set.
+    let info = |info: ::gltf::texture::Info| {
+        let transform = info.texture_transform();
+        TextureInfo {
+            texture: info.texture().index(),
+            tex_coord: transform
+                .as_ref()
+                .and_then(|transform| transform.tex_coord())
+                .unwrap_or(info.tex_coord()) as usize,
+            transform: transform.map(|transform| TextureTransform {
+                offset: Vector2::from(transform.offset()),
+                rotation: transform.rotation(),
+                scale: Vector2::from(transform.scale()),
+            }),
+        }
+    };
+    // The crate only exposes the transform of normal and occlusion textures as raw JSON.
+    let raw_info =
+        |texture: ::gltf::Texture, tex_coord: u32, value: Option<&::gltf::json::Value>| {
+            let transform: Option<::gltf::json::extensions::texture::TextureTransform> =
+                value.and_then(|value| ::gltf::json::deserialize::from_value(value.clone()).ok());
+            TextureInfo {
+                texture: texture.index(),
+                tex_coord: transform
+                    .as_ref()
+                    .and_then(|transform| transform.tex_coord)
+                    .unwrap_or(tex_coord) as usize,
+                transform: transform.map(|transform| TextureTransform {
+                    offset: Vector2::from(transform.offset.0),
+                    rotation: transform.rotation.0,
+                    scale: Vector2::from(transform.scale.0),
+                }),
+            }
+        };
+
+    document
+        .materials()
+        .map(|material| {
+            let pbr = material.pbr_metallic_roughness();
+            let [r, g, b, a] = pbr.base_color_factor();
+            let normal = material.normal_texture();
+            let occlusion = material.occlusion_texture();
+            Material {
+                name: material.name().map(String::from),
+                base_color_factor: Color::new(r, g, b, a),
+                base_color_texture: pbr.base_color_texture().map(info),
+                metallic_factor: pbr.metallic_factor(),
+                roughness_factor: pbr.roughness_factor(),
+                metallic_roughness_texture: pbr.metallic_roughness_texture().map(info),
+                normal_texture: normal.as_ref().map(|normal| {
+                    raw_info(
+                        normal.texture(),
+                        normal.tex_coord(),
+                        normal.extension_value("KHR_texture_transform"),
+                    )
+                }),
+                normal_scale: normal.as_ref().map_or(1.0, |normal| normal.scale()),
+                occlusion_texture: occlusion.as_ref().map(|occlusion| {
+                    raw_info(
+                        occlusion.texture(),
+                        occlusion.tex_coord(),
+                        occlusion.extension_value("KHR_texture_transform"),
+                    )
+                }),
+                occlusion_strength: occlusion
+                    .as_ref()
+                    .map_or(1.0, |occlusion| occlusion.strength()),
+                emissive_factor: Vector3::from(material.emissive_factor()),
+                emissive_texture: material.emissive_texture().map(info),
+                emissive_strength: material.emissive_strength().unwrap_or(1.0),
+                double_sided: material.double_sided(),
+                unlit: material.unlit(),
+            }
+        })
+        .collect()
+}
+
+pub fn load_skins(document: &::gltf::Document, buffers: &[::gltf::buffer::Data]) -> Vec<Skin> {
+    document
+        .skins()
+        .map(|skin| {
+            let reader = skin.reader(|buffer| Some(&buffers[buffer.index()]));
+            Skin {
+                name: skin.name().map(String::from),
+                joints: skin.joints().map(|joint| joint.index()).collect(),
+                inverse_bind_matrices: reader
+                    .read_inverse_bind_matrices()
+                    .map(|iter| iter.map(Matrix4::from).collect())
+                    .unwrap_or_default(),
+                skeleton: skin.skeleton().map(|node| node.index()),
+            }
+        })
+        .collect()
+}
+
+/**
+ * Animations, skipping channels whose keyframes can't be read.
+ */
+pub fn load_animations(
+    document: &::gltf::Document,
+    buffers: &[::gltf::buffer::Data],
+) -> Vec<Animation> {
+    use ::gltf::animation::util::ReadOutputs;
+
+    document
+        .animations()
+        .map(|animation| Animation {
+            name: animation.name().map(String::from),
+            channels: animation
+                .channels()
+                .filter_map(|channel| {
+                    let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+                    let inputs = reader.read_inputs()?.collect();
+                    let (property, outputs): (Property, Vec<f32>) = match reader.read_outputs()? {
+                        ReadOutputs::Translations(iter) => {
+                            (Property::Translation, iter.flatten().collect())
+                        }
+                        ReadOutputs::Rotations(iter) => {
+                            (Property::Rotation, iter.into_f32().flatten().collect())
+                        }
+                        ReadOutputs::Scales(iter) => (Property::Scale, iter.flatten().collect()),
+                        ReadOutputs::MorphTargetWeights(iter) => {
+                            (Property::Weights, iter.into_f32().collect())
+                        }
+                    };
+                    Some(Channel {
+                        node: channel.target().node().index(),
+                        property,
+                        sampler: Sampler {
+                            interpolation: match channel.sampler().interpolation() {
+                                ::gltf::animation::Interpolation::Linear => Interpolation::Linear,
+                                ::gltf::animation::Interpolation::Step => Interpolation::Step,
+                                ::gltf::animation::Interpolation::CubicSpline => {
+                                    Interpolation::CubicSpline
+                                }
+                            },
+                            inputs,
+                            outputs,
+                        },
+                    })
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn load_meshes(
+    document: &::gltf::Document,
+    buffers: &[::gltf::buffer::Data],
+) -> Result<Vec<Mesh>, GltfError> {
+    document
+        .meshes()
+        .map(|mesh| {
+            Ok(Mesh {
+                name: mesh.name().map(String::from),
+                primitives: load_primitives(document, &mesh, buffers)?,
+                weights: mesh.weights().map(<[f32]>::to_vec).unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/**
+ * Load the primitives of `mesh`, decoding those compressed with `KHR_draco_mesh_compression`.
+ */
+pub fn load_primitives(
+    document: &::gltf::Document,
+    mesh: &::gltf::Mesh,
+    buffers: &[::gltf::buffer::Data],
+) -> Result<Vec<Primitive>, GltfError> {
+    let mut primitives: Vec<Primitive> = Vec::new();
+
+    for primitive in mesh.primitives() {
+        let (mesh_index, primitive_index) = (mesh.index(), primitive.index());
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        let draco =
+            load_draco(document, &primitive, buffers).map_err(|error| GltfError::Draco {
+                mesh: mesh_index,
+                primitive: primitive_index,
+                error,
+            })?;
+        let draco = draco.as_ref();
+        // Attributes missing from the compressed data are stored as usual.
+        let compressed = |semantic: Semantic| draco.filter(|draco| draco.contains(&semantic));
+
+        let positions: Vec<[f32; 3]> =
+            read_attribute(&primitive, Semantic::Positions, draco, buffers).ok_or(
+                GltfError::MissingPositions {
+                    mesh: mesh_index,
+                    primitive: primitive_index,
+                },
+            )?;
+
+        let mut vertices: Vec<Vertex> = positions
+            .iter()
+            .map(|p| Vertex::new(Vector3::from(*p)))
+            .collect();
+
+        if let Some(normals) = read_attribute(&primitive, Semantic::Normals, draco, buffers) {
+            for (vertex, n) in vertices.iter_mut().zip(normals) {
+                vertex.normal = Some(Vector3::from(n));
+            }
+        }
+        let colors: Option<Vec<[f32; 4]>> = match compressed(Semantic::Colors(0)) {
+            Some(draco) => draco.read(&Semantic::Colors(0)).or_else(|| {
+                let colors: Vec<[f32; 3]> = draco.read(&Semantic::Colors(0))?;
+                Some(colors.into_iter().map(|[r, g, b]| [r, g, b, 1.0]).collect())
+            }),
+            None => reader
+                .read_colors(0)
+                .map(|iter| iter.into_rgba_f32().collect()),
+        };
+        if let Some(colors) = colors {
+            for (vertex, c) in vertices.iter_mut().zip(colors) {
+                vertex.color = Some(Color::new(c[0], c[1], c[2], c[3]));
+            }
+        }
+        if let Some(tex_coords) = read_attribute(&primitive, Semantic::TexCoords(0), draco, buffers)
+        {
+            for (vertex, t) in vertices.iter_mut().zip(tex_coords) {
+                vertex.tex_coord = Some(Vector2::from(t));
+            }
+        }
+        if let Some(tex_coords) = read_attribute(&primitive, Semantic::TexCoords(1), draco, buffers)
+        {
+            for (vertex, t) in vertices.iter_mut().zip(tex_coords) {
+                vertex.tex_coord_1 = Some(Vector2::from(t));
+            }
+        }
+        let joints: Option<Vec<[u16; 4]>> = match compressed(Semantic::Joints(0)) {
+            Some(draco) => draco
+                .read(&Semantic::Joints(0))
+                .map(|joints: Vec<[f32; 4]>| {
+                    joints.into_iter().map(|j| j.map(|j| j as u16)).collect()
+                }),
+            None => reader.read_joints(0).map(|iter| iter.into_u16().collect()),
+        };
+        if let Some(joints) = joints {
+            for (vertex, j) in vertices.iter_mut().zip(joints) {
+                vertex.joints = Some(j);
+            }
+        }
+        let weights: Option<Vec<[f32; 4]>> = match compressed(Semantic::Weights(0)) {
+            Some(draco) => draco.read(&Semantic::Weights(0)),
+            None => reader.read_weights(0).map(|iter| iter.into_f32().collect()),
+        };
+        if let Some(weights) = weights {
+            for (vertex, w) in vertices.iter_mut().zip(weights) {
+                vertex.weights = Some(Vector4::from(w));
+            }
+        }
+        let has_tangents = match read_attribute(&primitive, Semantic::Tangents, draco, buffers) {
+            Some(tangents) => {
+                for (vertex, t) in vertices.iter_mut().zip(tangents) {
+                    vertex.tangent = Some(Vector4::from(t));
+                }
+                true
+            }
+            None => false,
+        };
+
+        // Non-indexed primitives use every vertex in order.
+        let indices: Vec<u32> = match (draco, reader.read_indices()) {
+            (Some(draco), _) => draco.mesh.indices.clone(),
+            (None, Some(iter)) => iter.into_u32().collect(),
+            (None, None) => (0..vertices.len() as u32).collect(),
+        };
+        if let Some(&index) = indices.iter().find(|i| **i as usize >= vertices.len()) {
+            return Err(GltfError::IndexOutOfRange {
+                mesh: mesh_index,
+                primitive: primitive_index,
+                index,
+            });
+        }
+
+        let indices =
+            triangulate(primitive.mode(), &indices).ok_or(GltfError::UnsupportedMode {
+                mesh: mesh_index,
+                primitive: primitive_index,
+                mode: primitive.mode(),
+            })?;
+
+        let texture_index = primitive
+            .material()
+            .pbr_metallic_roughness()
+            .base_color_texture()
+            .map(|info| info.texture().index());
+
+        let vec3s = |accessor: Option<::gltf::Accessor>| -> Vec<Vector3<f32>> {
+            accessor
+                .and_then(|accessor| read_floats(accessor, buffers))
+                .into_iter()
+                .flatten()
+                .map(Vector3::from)
+                .collect()
+        };
+        let targets = primitive
+            .morph_targets()
+            .map(|target| MorphTarget {
+                positions: vec3s(target.positions()),
+                normals: vec3s(target.normals()),
+                tangents: vec3s(target.tangents()),
+            })
+            .collect();
+
+        let mut primitive = Primitive {
+            vertices,
+            indices,
+            texture_index,
+            material: primitive.material().index(),
+            targets,
+        };
+        if !has_tangents {
+            primitive.generate_tangents();
+        }
+        primitives.push(primitive);
+    }
+
+    Ok(primitives)
+}
+
+/**
+ * A decoded `KHR_draco_mesh_compression` primitive.
+ */
+struct DracoPrimitive {
+    mesh: DracoMesh,
+
+    /**
+     * Unique ids of the compressed attributes, by semantic name.
+     */
+    attributes: HashMap<String, u32>,
+}
+
+impl DracoPrimitive {
+    fn contains(&self, semantic: &Semantic) -> bool {
+        self.attributes.contains_key(&semantic.to_string())
+    }
+
+    /**
+     * The compressed attribute `semantic`, `None` when it has another number of components.
+     */
+    fn read<const N: usize>(&self, semantic: &Semantic) -> Option<Vec<[f32; N]>> {
+        let attribute = self
+            .mesh
+            .attribute(*self.attributes.get(&semantic.to_string())?)?;
+        if attribute.components != N {
+            return None;
+        }
+        Some(
+            attribute
+                .values
+                .chunks_exact(N)
+                .map(|value| value.try_into().unwrap())
+                .collect(),
+        )
+    }
+}
+
+/**
+ * Decode the `KHR_draco_mesh_compression` data of `primitive`, `None` when it isn't compressed.
+ */
+fn load_draco(
+    document: &::gltf::Document,
+    primitive: &::gltf::Primitive,
+    buffers: &[::gltf::buffer::Data],
+) -> Result<Option<DracoPrimitive>, DracoError> {
+    let Some(extension) = primitive.extension_value("KHR_draco_mesh_compression") else {
+        return Ok(None);
+    };
+    let invalid = DracoError::Corrupt("extension");
+    let view = extension["bufferView"]
+        .as_u64()
+        .and_then(|index| document.views().nth(index as usize))
+        .ok_or(invalid.clone())?;
+    let attributes = extension["attributes"]
+        .as_object()
+        .ok_or(invalid.clone())?
+        .iter()
+        .map(|(name, id)| Some((name.clone(), u32::try_from(id.as_u64()?).ok()?)))
+        .collect::<Option<HashMap<_, _>>>()
+        .ok_or(invalid.clone())?;
+
+    let data = buffers[view.buffer().index()]
+        .get(view.offset()..view.offset() + view.length())
+        .ok_or(DracoError::Truncated)?;
+    let mesh = draco::decode(data)?;
+    Ok(Some(DracoPrimitive { mesh, attributes }))
+}
+
+/**
+ * Read the `N` component float attribute `semantic` of `primitive`,
+ * from its compressed data when `draco` has it.
+ */
+fn read_attribute<const N: usize>(
+    primitive: &::gltf::Primitive,
+    semantic: Semantic,
+    draco: Option<&DracoPrimitive>,
+    buffers: &[::gltf::buffer::Data],
+) -> Option<Vec<[f32; N]>>
+where
+    [f32; N]: Item,
+    [i8; N]: Item,
+    [u8; N]: Item,
+    [i16; N]: Item,
+    [u16; N]: Item,
+{
+    match draco.filter(|draco| draco.contains(&semantic)) {
+        Some(draco) => draco.read(&semantic),
+        None => read_floats(primitive.get(&semantic)?, buffers),
+    }
+}
+
+/**
+ * Read the `N` component float attribute `accessor`, dequantizing the integer component
+ * types allowed by `KHR_mesh_quantization`: normalized ones to [0, 1] or [-1, 1] and the
+ * others as is. `None` when the accessor has another number of components or its data is
+ * out of bounds.
+ */
+fn read_floats<const N: usize>(
+    accessor: ::gltf::Accessor,
+    buffers: &[::gltf::buffer::Data],
+) -> Option<Vec<[f32; N]>>
+where
+    [f32; N]: Item,
+    [i8; N]: Item,
+    [u8; N]: Item,
+    [i16; N]: Item,
+    [u16; N]: Item,
+{
+    fn read<'a, T: Item + Copy, const N: usize>(
+        accessor: ::gltf::Accessor<'a>,
+        buffers: &'a [::gltf::buffer::Data],
+        convert: impl Fn(T) -> f32,
+    ) -> Option<Vec<[f32; N]>>
+    where
+        [T; N]: Item,
+    {
+        let iter = Iter::<[T; N]>::new(accessor, |buffer| Some(&buffers[buffer.index()][..]))?;
+        Some(iter.map(|v| v.map(&convert)).collect())
+    }
+
+    if accessor.dimensions().multiplicity() != N {
+        return None;
+    }
+    let normalized = accessor.normalized();
+    match accessor.data_type() {
+        DataType::F32 => read(accessor, buffers, |c: f32| c),
+        DataType::I8 if normalized => read(accessor, buffers, |c: i8| (c as f32 / 127.0).max(-1.0)),
+        DataType::U8 if normalized => read(accessor, buffers, |c: u8| c as f32 / 255.0),
+        DataType::I16 if normalized => {
+            read(accessor, buffers, |c: i16| (c as f32 / 32767.0).max(-1.0))
+        }
+        DataType::U16 if normalized => read(accessor, buffers, |c: u16| c as f32 / 65535.0),
+        DataType::I8 => read(accessor, buffers, |c: i8| c as f32),
+        DataType::U8 => read(accessor, buffers, |c: u8| c as f32),
+        DataType::I16 => read(accessor, buffers, |c: i16| c as f32),
+        DataType::U16 => read(accessor, buffers, |c: u16| c as f32),
+        DataType::U32 => None,
+    }
+}
+
+/**
+ * Convert strips and fans to a triangle list, keeping the winding order.
+ * Returns `None` for point and line modes.
+ */
+fn triangulate(mode: Mode, indices: &[u32]) -> Option<Vec<u32>> {
+    match mode {
+        Mode::Triangles => Some(indices[..indices.len() / 3 * 3].to_vec()),
+        Mode::TriangleStrip => Some(
+            (0..indices.len().saturating_sub(2))
+                .flat_map(|i| {
+                    if i % 2 == 0 {
+                        [indices[i], indices[i + 1], indices[i + 2]]
+                    } else {
+                        [indices[i + 1], indices[i], indices[i + 2]]
+                    }
+                })
+                .collect(),
+        ),
+        Mode::TriangleFan => Some(
+            (1..indices.len().saturating_sub(1))
+                .flat_map(|i| [indices[0], indices[i], indices[i + 1]])
+                .collect(),
+        ),
+        Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => None,
+    }
+}
+
+/**
+ * Decode every texture, in the order of `document.textures()`.
+ */
+pub fn load_textures(
+    document: &::gltf::Document,
+    images: &[Data],
+) -> Result<Vec<Texture<Color>>, GltfError> {
+    document
+        .textures()
+        .map(|texture| {
+            let index = texture.source().index();
+            let data = &images[index];
+            match data.format {
+                Format::R8 | Format::R8G8B8 | Format::R8G8B8A8 => Ok(Texture::from(data)),
+                format => Err(GltfError::UnsupportedImageFormat {
+                    image: index,
+                    format,
+                }),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::{node::Instance, skin::skin_vertex};
+
+    const MODELS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/models");
+
+    fn triangle_count(scene: &Scene) -> usize {
+        scene
+            .meshes
+            .iter()
+            .flat_map(|mesh| &mesh.primitives)
+            .map(|primitive| primitive.triangle_count())
+            .sum()
+    }
+
+    #[test]
+    fn test_load_duck_variants() {
+        let variants = [
+            "Duck/glTF/Duck.gltf",
+            "Duck/glTF-Binary/Duck.glb",
+            "Duck/glTF-Embedded/Duck.gltf",
+        ];
+        for variant in variants {
+            let scene = load(format!("{}/{}", MODELS, variant)).unwrap();
+            assert_eq!(scene.meshes.len(), 1, "{}", variant);
+            assert_eq!(triangle_count(&scene), 4212, "{}", variant);
+            assert_eq!(scene.textures.len(), 1, "{}", variant);
+
+            let primitive = &scene.meshes[0].primitives[0];
+            // 2399 in the asset, plus those split by generating tangents where the
+            // handedness of the texture coordinates flips.
+            assert_eq!(primitive.vertices.len(), 2608, "{}", variant);
+            assert_eq!(primitive.texture_index, Some(0));
+
+            // The mesh node is scaled by 0.01 under the root node.
+            let instances = scene.instances(None);
+            assert_eq!(instances.len(), 1);
+            assert!((instances[0].world[(0, 0)] - 0.01).abs() < 1e-6);
+            assert!(primitive.vertex(0).normal.is_some());
+            assert!(primitive.vertex(0).tex_coord.is_some());
+            assert!(primitive.vertex(0).tangent.is_some());
+        }
+    }
+
+    #[test]
+    fn test_load_avocado() {
+        let scene = load(format!("{}/Avocado/glTF/Avocado.gltf", MODELS)).unwrap();
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(triangle_count(&scene), 682);
+        assert_eq!(scene.textures.len(), 3);
+
+        let material = &scene.materials[scene.meshes[0].primitives[0].material.unwrap()];
+        let texture = |info: Option<TextureInfo>| info.map(|info| info.texture);
+        assert_eq!(texture(material.base_color_texture), Some(0));
+        assert_eq!(texture(material.metallic_roughness_texture), Some(1));
+        assert_eq!(texture(material.normal_texture), Some(2));
+        assert!(material.base_color_texture.unwrap().transform.is_none());
+        assert_eq!(
+            (material.metallic_factor, material.roughness_factor),
+            (1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_load_quantized() {
+        for name in ["Duck", "Avocado"] {
+            let float = load(format!("{}/{name}/glTF/{name}.gltf", MODELS)).unwrap();
+            let quantized = load(format!("{}/{name}/glTF-Quantized/{name}.gltf", MODELS)).unwrap();
+            assert_eq!(triangle_count(&quantized), triangle_count(&float));
+
+            // The node transforms dequantize the positions.
+            let extents = |scene: &Scene| {
+                let b = scene.bounds(None).unwrap();
+                [b.l, b.r, b.b, b.t, b.n, b.f]
+            };
+            let (expected, actual) = (extents(&float), extents(&quantized));
+            let tolerance = 1e-3 * (expected[1] - expected[0]).abs();
+            for (a, e) in actual.iter().zip(expected) {
+                assert!((a - e).abs() < tolerance, "{name}: {actual:?} {expected:?}");
+            }
+
+            // Texture transforms dequantize the texture coordinates.
+            let material = &quantized.materials[0];
+            let info = material.base_color_texture.unwrap();
+            assert!(info.transform.is_some());
+            let primitive = &quantized.meshes[0].primitives[0];
+            let normal = primitive.vertices[0].normal.unwrap();
+            assert!((normal.norm() - 1.0).abs() < 0.02, "{name}");
+            let uv = info.map(&primitive.vertices[0].tex_coord.unwrap());
+            assert!(
+                uv.iter().all(|c| (-0.01..=1.01).contains(c)),
+                "{name}: {uv:?}"
+            );
+        }
+
+        let avocado = load(format!("{}/Avocado/glTF-Quantized/Avocado.gltf", MODELS)).unwrap();
+        assert!(avocado.materials[0]
+            .normal_texture
+            .unwrap()
+            .transform
+            .is_some());
+    }
+
+    #[test]
+    fn test_load_draco() {
+        for name in ["Duck", "Avocado"] {
+            let float = load(format!("{}/{name}/glTF/{name}.gltf", MODELS)).unwrap();
+            let draco = load(format!("{}/{name}/glTF-Draco/{name}.gltf", MODELS)).unwrap();
+            assert_eq!(triangle_count(&draco), triangle_count(&float));
+
+            let extents = |scene: &Scene| {
+                let b = scene.bounds(None).unwrap();
+                [b.l, b.r, b.b, b.t, b.n, b.f]
+            };
+            let (expected, actual) = (extents(&float), extents(&draco));
+            let tolerance = 1e-3 * (expected[1] - expected[0]).abs();
+            for (a, e) in actual.iter().zip(expected) {
+                assert!((a - e).abs() < tolerance, "{name}: {actual:?} {expected:?}");
+            }
+
+            let primitive = &draco.meshes[0].primitives[0];
+            for vertex in &primitive.vertices {
+                assert!((vertex.normal.unwrap().norm() - 1.0).abs() < 1e-3, "{name}");
+                let uv = vertex.tex_coord.unwrap();
+                assert!(
+                    uv.iter().all(|c| (-0.01..=1.01).contains(c)),
+                    "{name}: {uv:?}"
+                );
+                assert!(vertex.tangent.is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_duck_camera() {
+        let scene = load(format!("{}/Duck/glTF/Duck.gltf", MODELS)).unwrap();
+        assert_eq!(scene.cameras.len(), 1);
+        assert_eq!(scene.nodes[1].camera, Some(0));
+        match scene.cameras[0].projection {
+            Projection::Perspective {
+                aspect_ratio, zfar, ..
+            } => assert_eq!((aspect_ratio, zfar), (Some(1.5), Some(10000.0))),
+            _ => panic!("expected a perspective camera"),
+        }
+
+        // The camera node looks at the duck, which lands in front of it.
+        let view = scene.view(Some(0), None, 1.0).unwrap();
+        let bounds = scene.bounds(None).unwrap();
+        let center = Vector3::new(
+            (bounds.l + bounds.r) / 2.0,
+            (bounds.b + bounds.t) / 2.0,
+            (bounds.n + bounds.f) / 2.0,
+        );
+        let clip = view.view_projection() * center.push(1.0);
+        let ndc = clip.xyz() / clip.w;
+        assert!(clip.w > 0.0 && ndc.x.abs() < 1.0 && ndc.y.abs() < 1.0);
+        assert!((view.eye.coords.norm() - 7.5).abs() < 0.1);
+
+        // Without cameras, the view frames the bounds.
+        let avocado = load(format!("{}/Avocado/glTF/Avocado.gltf", MODELS)).unwrap();
+        assert!(avocado.cameras.is_empty());
+        assert!(avocado.view(None, None, 1.0).is_some());
+        assert!(avocado.view(Some(0), None, 1.0).is_none());
+    }
+
+    /**
+     * Pack a JSON document and a binary chunk into a GLB container.
+     */
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        let pad = |bytes: &[u8], fill: u8| {
+            let mut bytes = bytes.to_vec();
+            bytes.resize(bytes.len().div_ceil(4) * 4, fill);
+            bytes
+        };
+        let json = pad(json.as_bytes(), b' ');
+        let bin = pad(bin, 0);
+
+        let mut glb = Vec::new();
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(&json);
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"BIN\0");
+        glb.extend_from_slice(&bin);
+        glb
+    }
+
+    /**
+     * A single mesh whose primitives all share four positions of a unit quad.
+     */
+    fn quad_glb(primitives: &str) -> Vec<u8> {
+        let positions: [f32; 12] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0];
+        let indices: [u16; 6] = [0, 1, 2, 2, 1, 3];
+        let mut bin: Vec<u8> = positions.iter().flat_map(|v| v.to_le_bytes()).collect();
+        bin.extend(indices.iter().flat_map(|v| v.to_le_bytes()));
+
+        let json = format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "buffers": [{{ "byteLength": 60 }}],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteOffset": 0, "byteLength": 48 }},
+                    {{ "buffer": 0, "byteOffset": 48, "byteLength": 12 }}
+                ],
+                "accessors": [
+                    {{
+                        "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3",
+                        "min": [0, 0, 0], "max": [1, 1, 0]
+                    }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 6, "type": "SCALAR" }}
+                ],
+                "meshes": [{{ "primitives": [{}] }}]
+            }}"#,
+            primitives
+        );
+        glb(&json, &bin)
+    }
+
+    #[test]
+    fn test_load_primitive_modes() {
+        let scene = load_slice(&quad_glb(
+            r#"
+            { "attributes": { "POSITION": 0 }, "indices": 1 },
+            { "attributes": { "POSITION": 0 } },
+            { "attributes": { "POSITION": 0 }, "mode": 5 },
+            { "attributes": { "POSITION": 0 }, "mode": 6 }
+            "#,
+        ))
+        .unwrap();
+        let primitives = &scene.meshes[0].primitives;
+        assert_eq!(primitives[0].indices, vec![0, 1, 2, 2, 1, 3]);
+        // Non-indexed triangles drop the incomplete trailing triangle.
+        assert_eq!(primitives[1].indices, vec![0, 1, 2]);
+        assert_eq!(primitives[2].indices, vec![0, 1, 2, 2, 1, 3]);
+        assert_eq!(primitives[3].indices, vec![0, 1, 2, 0, 2, 3]);
+        assert_eq!(primitives[0].vertex(5).position.x, 1.0);
+
+        let err = load_slice(&quad_glb(
+            r#"{ "attributes": { "POSITION": 0 }, "mode": 1 }"#,
+        ));
+        assert!(matches!(err, Err(GltfError::UnsupportedMode { .. })));
+    }
+
+    #[test]
+    fn test_load_tex_coord_sets() {
+        let positions: [f32; 9] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let tex_coords: [f32; 12] = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5];
+        let bin: Vec<u8> = positions
+            .iter()
+            .chain(&tex_coords)
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "extensionsUsed": ["KHR_materials_unlit", "KHR_texture_transform"],
+            "buffers": [{ "byteLength": 84 }],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+                { "buffer": 0, "byteOffset": 36, "byteLength": 24 },
+                { "buffer": 0, "byteOffset": 60, "byteLength": 24 }
+            ],
+            "accessors": [
+                {
+                    "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [0, 0, 0], "max": [1, 1, 0]
+                },
+                { "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2" },
+                { "bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC2" }
+            ],
+            "images": [{ "uri": "unused.png" }],
+            "textures": [{ "source": 0 }],
+            "materials": [{
+                "pbrMetallicRoughness": {
+                    "baseColorTexture": { "index": 0, "texCoord": 1 },
+                    "metallicRoughnessTexture": { "index": 0, "texCoord": 1, "extensions": {
+                        "KHR_texture_transform": { "scale": [2, 2], "texCoord": 0 }
+                    } }
+                },
+                "occlusionTexture": { "index": 0, "extensions": {
+                    "KHR_texture_transform": { "offset": [0.5, 0], "texCoord": 1 }
+                } },
+                "extensions": { "KHR_materials_unlit": {} }
+            }],
+            "meshes": [{ "primitives": [{
+                "attributes": { "POSITION": 0, "TEXCOORD_0": 1, "TEXCOORD_1": 2 },
+                "material": 0
+            }] }]
+        }"#;
+        let document = ::gltf::Gltf::from_slice(&glb(json, &bin)).unwrap().document;
+        let buffers = [::gltf::buffer::Data(bin)];
+
+        let vertex = load_meshes(&document, &buffers).unwrap()[0].primitives[0].vertices[1];
+        assert_eq!(vertex.tex_coord, Some(Vector2::new(1.0, 0.0)));
+        assert_eq!(vertex.tex_coord_1, Some(Vector2::new(0.5, 0.5)));
+        assert_eq!(vertex.tex_coord_set(1), vertex.tex_coord_1);
+
+        // Texture transforms override the set of the texture info.
+        let material = &load_materials(&document)[0];
+        assert!(material.unlit);
+        assert_eq!(material.base_color_texture.unwrap().tex_coord, 1);
+        let metallic_roughness = material.metallic_roughness_texture.unwrap();
+        assert_eq!(metallic_roughness.tex_coord, 0);
+        assert_eq!(
+            metallic_roughness.transform.unwrap().scale,
+            Vector2::new(2.0, 2.0)
+        );
+        let occlusion = material.occlusion_texture.unwrap();
+        assert_eq!(occlusion.tex_coord, 1);
+        assert_eq!(
+            occlusion.map_set(&[Vector2::zeros(), Vector2::new(0.5, 0.5)]),
+            Vector2::new(1.0, 0.5)
+        );
+    }
+
+    #[test]
+    fn test_load_lights() {
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "extensionsUsed": ["KHR_lights_punctual"],
+            "extensions": {
+                "KHR_lights_punctual": {
+                    "lights": [
+                        { "type": "directional", "color": [1, 0.5, 0], "intensity": 2 },
+                        { "name": "lamp", "type": "point", "range": 10 },
+                        {
+                            "type": "spot",
+                            "spot": { "innerConeAngle": 0.1, "outerConeAngle": 0.5 }
+                        }
+                    ]
+                }
+            },
+            "nodes": [
+                { "rotation": [-0.7071068, 0, 0, 0.7071068], "extensions": {
+                    "KHR_lights_punctual": { "light": 0 } } },
+                { "translation": [0, 3, 0], "extensions": {
+                    "KHR_lights_punctual": { "light": 1 } } },
+                { "extensions": { "KHR_lights_punctual": { "light": 2 } } }
+            ],
+            "scenes": [{ "nodes": [0, 1] }],
+            "scene": 0
+        }"#;
+        let scene = load_slice(json.as_bytes()).unwrap();
+        assert_eq!(scene.lights.len(), 3);
+        assert_eq!(scene.lights[0].kind, LightKind::Directional);
+        assert_eq!(scene.lights[0].intensity, 2.0);
+        assert_eq!(scene.lights[1].name.as_deref(), Some("lamp"));
+        assert_eq!(scene.lights[1].range, Some(10.0));
+        assert_eq!(
+            scene.lights[2].kind,
+            LightKind::Spot {
+                inner_cone_angle: 0.1,
+                outer_cone_angle: 0.5
+            }
+        );
+
+        // Only the lights placed in the default scene, pointing down and sitting above.
+        let lighting = scene.lighting(None, Vector3::zeros());
+        assert_eq!(lighting.lights.len(), 2);
+        assert!((lighting.lights[0].direction - -Vector3::y()).norm() < 1e-5);
+        assert_eq!(lighting.lights[1].position, Vector3::new(0.0, 3.0, 0.0));
+    }
+
+    #[test]
+    fn test_load_skin_and_animation() {
+        let f32s =
+            |values: &[f32]| -> Vec<u8> { values.iter().flat_map(|v| v.to_le_bytes()).collect() };
+        let mut bin = f32s(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0, 0.0]);
+        let joints: [u16; 12] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0];
+        bin.extend(joints.iter().flat_map(|v| v.to_le_bytes()));
+        bin.extend(f32s(&[
+            1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0,
+        ]));
+        let identity = Matrix4::<f32>::identity();
+        let inverse_bind = Matrix4::new_translation(&Vector3::new(0.0, -1.0, 0.0));
+        bin.extend(f32s(identity.as_slice()));
+        bin.extend(f32s(inverse_bind.as_slice()));
+        bin.extend(f32s(&[0.0, 1.0]));
+        bin.extend(f32s(&[0.0, 1.0, 0.0, 2.0, 1.0, 0.0]));
+
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "buffers": [{ "byteLength": 268 }],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+                { "buffer": 0, "byteOffset": 36, "byteLength": 24 },
+                { "buffer": 0, "byteOffset": 60, "byteLength": 48 },
+                { "buffer": 0, "byteOffset": 108, "byteLength": 128 },
+                { "buffer": 0, "byteOffset": 236, "byteLength": 8 },
+                { "buffer": 0, "byteOffset": 244, "byteLength": 24 }
+            ],
+            "accessors": [
+                {
+                    "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [0, 0, 0], "max": [1, 2, 0]
+                },
+                { "bufferView": 1, "componentType": 5123, "count": 3, "type": "VEC4" },
+                { "bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC4" },
+                { "bufferView": 3, "componentType": 5126, "count": 2, "type": "MAT4" },
+                {
+                    "bufferView": 4, "componentType": 5126, "count": 2, "type": "SCALAR",
+                    "min": [0], "max": [1]
+                },
+                { "bufferView": 5, "componentType": 5126, "count": 2, "type": "VEC3" }
+            ],
+            "meshes": [{ "primitives": [{
+                "attributes": { "POSITION": 0, "JOINTS_0": 1, "WEIGHTS_0": 2 }
+            }] }],
+            "skins": [{ "joints": [1, 2], "inverseBindMatrices": 3, "skeleton": 1 }],
+            "animations": [{
+                "channels": [{ "sampler": 0, "target": { "node": 2, "path": "translation" } }],
+                "samplers": [{ "input": 4, "output": 5 }]
+            }],
+            "nodes": [
+                { "mesh": 0, "skin": 0 },
+                { "children": [2] },
+                { "translation": [0, 1, 0] }
+            ],
+            "scenes": [{ "nodes": [0, 1] }]
+        }"#;
+        let mut scene = load_slice(&glb(json, &bin)).unwrap();
+        assert_eq!(scene.skins[0].joints, vec![1, 2]);
+        assert_eq!(scene.skins[0].skeleton, Some(1));
+        assert_eq!(scene.nodes[0].skin, Some(0));
+        assert_eq!(scene.animations[0].duration(), 1.0);
+        let vertex = scene.meshes[0].primitives[0].vertices[2];
+        assert_eq!(vertex.joints, Some([1, 0, 0, 0]));
+
+        // Halfway through, the second joint has moved by one along X.
+        scene.animate(0, 0.5);
+        let joint_matrices = scene.skins[0].joint_matrices(&scene.world_transforms(None));
+        let (position, _) = skin_vertex(&vertex, &joint_matrices);
+        assert!((position - Vector4::new(1.0, 2.0, 0.0, 1.0)).norm() < 1e-6);
+        let (position, _) =
+            skin_vertex(&scene.meshes[0].primitives[0].vertices[1], &joint_matrices);
+        assert_eq!(position, Vector4::new(1.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_load_morph_targets() {
+        let positions: [f32; 27] = [
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, // Base triangle
+            0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, // Raise the apex
+            0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, // Push along Z
+        ];
+        let bin: Vec<u8> = positions.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "buffers": [{ "byteLength": 108 }],
+            "bufferViews": [{ "buffer": 0, "byteLength": 108 }],
+            "accessors": [
+                {
+                    "bufferView": 0, "byteOffset": 0, "componentType": 5126, "count": 3,
+                    "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0]
+                },
+                {
+                    "bufferView": 0, "byteOffset": 36, "componentType": 5126, "count": 3,
+                    "type": "VEC3", "min": [0, 0, 0], "max": [0, 1, 0]
+                },
+                {
+                    "bufferView": 0, "byteOffset": 72, "componentType": 5126, "count": 3,
+                    "type": "VEC3", "min": [0, 0, 1], "max": [0, 0, 1]
+                }
+            ],
+            "meshes": [{
+                "primitives": [{
+                    "attributes": { "POSITION": 0 },
+                    "targets": [{ "POSITION": 1 }, { "POSITION": 2 }]
+                }],
+                "weights": [0.5, 0.0]
+            }],
+            "nodes": [{ "mesh": 0 }, { "mesh": 0, "weights": [0.0, 1.0] }]
+        }"#;
+        let mut scene = load_slice(&glb(json, &bin)).unwrap();
+        let primitive = &scene.meshes[0].primitives[0];
+        assert_eq!(primitive.targets.len(), 2);
+        assert!(primitive.targets[0].normals.is_empty());
+        assert_eq!(scene.meshes[0].weights, vec![0.5, 0.0]);
+
+        // The mesh weights by default, the node's when it has some.
+        let instances = scene.instances(None);
+        assert_eq!(scene.morph_weights(&instances[0]), &[0.5, 0.0]);
+        assert_eq!(scene.morph_weights(&instances[1]), &[0.0, 1.0]);
+        let apex = |scene: &Scene, instance: &Instance| {
+            let weights = scene.morph_weights(instance);
+            scene.meshes[0].primitives[0]
+                .morphed_vertex(2, weights)
+                .position
+        };
+        assert_eq!(
+            apex(&scene, &instances[0]),
+            Vector4::new(0.0, 1.5, 0.0, 1.0)
+        );
+        assert_eq!(
+            apex(&scene, &instances[1]),
+            Vector4::new(0.0, 1.0, 1.0, 1.0)
+        );
+
+        // Weights set per frame by user code.
+        scene.nodes[0].weights = Some(vec![1.0, 1.0]);
+        assert_eq!(
+            apex(&scene, &instances[0]),
+            Vector4::new(0.0, 2.0, 1.0, 1.0)
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::common::color::Color;
+use nalgebra::{Matrix4, Vector3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightKind {
+    /**
+     * Infinitely far away, shining along the node's local -Z axis.
+     */
+    Directional,
+
+    /**
+     * Shining in all directions from the node's position.
+     */
+    Point,
+
+    /**
+     * Shining along the node's local -Z axis, within cones given in radians from it.
+     */
+    Spot {
+        inner_cone_angle: f32,
+        outer_cone_angle: f32,
+    },
+}
+
+/**
+ * A light of `KHR_lights_punctual`, placed in the world by a node.
+ */
+#[derive(Debug, Clone)]
+pub struct Light {
+    pub name: Option<String>,
+
+    pub kind: LightKind,
+
+    pub color: Color,
+
+    /**
+     * In lux for directional lights, in candela otherwise.
+     */
+    pub intensity: f32,
+
+    /**
+     * Distance past which the light has no effect, unlimited when `None`.
+     */
+    pub range: Option<f32>,
+}
+
+/**
+ * A light in world space, as consumed by shaders.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct PunctualLight {
+    pub kind: LightKind,
+
+    pub position: Vector3<f32>,
+
+    /**
+     * Unit direction the light travels in, unused by point lights.
+     */
+    pub direction: Vector3<f32>,
+
+    /**
+     * Color scaled by intensity.
+     */
+    pub radiance: Vector3<f32>,
+
+    pub range: Option<f32>,
+}
+
+impl PunctualLight {
+    /**
+     * Place `light` with the world matrix of its node.
+     */
+    pub fn new(light: &Light, world: &Matrix4<f32>) -> Self {
+        let direction = world.transform_vector(&-Vector3::z());
+        Self {
+            kind: light.kind,
+            position: world.column(3).xyz(),
+            direction: direction
+                .try_normalize(f32::EPSILON)
+                .unwrap_or(-Vector3::z()),
+            radiance: Vector3::new(light.color.r, light.color.g, light.color.b) * light.intensity,
+            range: light.range,
+        }
+    }
+
+    pub fn directional(direction: Vector3<f32>, radiance: Vector3<f32>) -> Self {
+        Self {
+            kind: LightKind::Directional,
+            position: Vector3::zeros(),
+            direction: direction
+                .try_normalize(f32::EPSILON)
+                .unwrap_or(-Vector3::z()),
+            radiance,
+            range: None,
+        }
+    }
+
+    /**
+     * The unit direction towards the light and the radiance arriving at `position`,
+     * attenuated by distance and cone the way the glTF reference viewer does.
+     * `None` when no light arrives.
+     */
+    pub fn incident(&self, position: &Vector3<f32>) -> Option<(Vector3<f32>, Vector3<f32>)> {
+        if self.kind == LightKind::Directional {
+            return Some((-self.direction, self.radiance));
+        }
+
+        let to_light = self.position - position;
+        let distance_squared = to_light.norm_squared().max(f32::EPSILON);
+        let l = to_light / distance_squared.sqrt();
+
+        let mut attenuation = match self.range {
+            Some(range) if range > 0.0 => {
+                let ratio = distance_squared / (range * range);
+                (1.0 - ratio * ratio).clamp(0.0, 1.0) / distance_squared
+            }
+            _ => 1.0 / distance_squared,
+        };
+
+        if let LightKind::Spot {
+            inner_cone_angle,
+            outer_cone_angle,
+        } = self.kind
+        {
+            let outer = outer_cone_angle.cos();
+            let scale = 1.0 / (inner_cone_angle.cos() - outer).max(0.001);
+            let angular = ((self.direction.dot(&-l) - outer) * scale).clamp(0.0, 1.0);
+            attenuation *= angular * angular;
+        }
+
+        if attenuation > 0.0 {
+            Some((l, self.radiance * attenuation))
+        } else {
+            None
+        }
+    }
+}
+
+/**
+ * Every light of a scene, the uniform block of the built-in shaders.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Lighting {
+    /**
+     * Radiance arriving equally from every direction.
+     */
+    pub ambient: Vector3<f32>,
+
+    pub lights: Vec<PunctualLight>,
+}
+
+impl Lighting {
+    /**
+     * Diffuse irradiance at `position` on a surface facing the unit `normal`, ambient
+     * included. Multiply by the albedo for Lambertian shading.
+     */
+    pub fn irradiance(&self, position: &Vector3<f32>, normal: &Vector3<f32>) -> Vector3<f32> {
+        self.lights
+            .iter()
+            .filter_map(|light| light.incident(position))
+            .fold(self.ambient, |sum, (l, radiance)| {
+                sum + radiance * normal.dot(&l).max(0.0)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn light(kind: LightKind, range: Option<f32>) -> PunctualLight {
+        let world = Matrix4::new_translation(&Vector3::new(0.0, 0.0, 2.0));
+        PunctualLight::new(
+            &Light {
+                name: None,
+                kind,
+                color: Color::new(1.0, 0.5, 0.0, 1.0),
+                intensity: 4.0,
+                range,
+            },
+            &world,
+        )
+    }
+
+    #[test]
+    fn test_incident_light() {
+        let origin = Vector3::zeros();
+
+        let directional = light(LightKind::Directional, None);
+        let (l, radiance) = directional.incident(&origin).unwrap();
+        assert_eq!(l, Vector3::z());
+        assert_eq!(radiance, Vector3::new(4.0, 2.0, 0.0));
+
+        // Inverse square falloff, cut off at the range.
+        let point = light(LightKind::Point, None);
+        let (l, radiance) = point.incident(&origin).unwrap();
+        assert_eq!(l, Vector3::z());
+        assert!((radiance.x - 1.0).abs() < 1e-6);
+        assert!(light(LightKind::Point, Some(1.5))
+            .incident(&origin)
+            .is_none());
+
+        // Full inside of the inner cone, nothing past the outer cone.
+        let spot = light(
+            LightKind::Spot {
+                inner_cone_angle: 0.2,
+                outer_cone_angle: 0.4,
+            },
+            None,
+        );
+        assert!((spot.incident(&origin).unwrap().1.x - 1.0).abs() < 1e-6);
+        assert!(spot.incident(&Vector3::new(2.0, 0.0, 0.0)).is_none());
+
+        let lighting = Lighting {
+            ambient: Vector3::repeat(0.1),
+            lights: vec![directional],
+        };
+        let irradiance = lighting.irradiance(&origin, &-Vector3::z());
+        assert_eq!(irradiance, Vector3::repeat(0.1));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::common::color::Color;
+use nalgebra::{Matrix3, Vector2, Vector3};
+
+/**
+ * An affine transform of texture coordinates, from `KHR_texture_transform`.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureTransform {
+    pub offset: Vector2<f32>,
+
+    /**
+     * Counter-clockwise, in radians, around the UV origin.
+     */
+    pub rotation: f32,
+
+    pub scale: Vector2<f32>,
+}
+
+impl TextureTransform {
+    /**
+     * Scale, then rotate, then offset.
+     */
+    pub fn matrix(&self) -> Matrix3<f32> {
+        let (sin, cos) = self.rotation.sin_cos();
+        let translation = Matrix3::new(
+            1.0,
+            0.0,
+            self.offset.x,
+            0.0,
+            1.0,
+            self.offset.y,
+            0.0,
+            0.0,
+            1.0,
+        );
+        let rotation = Matrix3::new(cos, sin, 0.0, -sin, cos, 0.0, 0.0, 0.0, 1.0);
+        translation * rotation * Matrix3::new_nonuniform_scaling(&self.scale)
+    }
+
+    pub fn apply(&self, tex_coord: &Vector2<f32>) -> Vector2<f32> {
+        self.matrix().transform_point(&(*tex_coord).into()).coords
+    }
+}
+
+impl Default for TextureTransform {
+    fn default() -> Self {
+        Self {
+            offset: Vector2::zeros(),
+            rotation: 0.0,
+            scale: Vector2::repeat(1.0),
+        }
+    }
+}
+
+/**
+ * A reference from a material to one of `Scene::textures`.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureInfo {
+    pub texture: usize,
+
+    /**
+     * The texture coordinate set to sample with, as overridden by the transform.
+     */
+    pub tex_coord: usize,
+
+    pub transform: Option<TextureTransform>,
+}
+
+impl TextureInfo {
+    /**
+     * Where a vertex with `tex_coord` samples this texture.
+     */
+    pub fn map(&self, tex_coord: &Vector2<f32>) -> Vector2<f32> {
+        match &self.transform {
+            Some(transform) => transform.apply(tex_coord),
+            None => *tex_coord,
+        }
+    }
+
+    /**
+     * Where a vertex with the texture coordinate sets `tex_coords` samples this texture,
+     * the origin when it lacks the set.
+     */
+    pub fn map_set(&self, tex_coords: &[Vector2<f32>]) -> Vector2<f32> {
+        self.map(tex_coords.get(self.tex_coord).unwrap_or(&Vector2::zeros()))
+    }
+}
+
+/**
+ * The metallic-roughness material model of glTF.
+ * Factors are linear, color textures are sRGB encoded.
+ */
+#[derive(Debug, Clone)]
+pub struct Material {
+    pub name: Option<String>,
+
+    pub base_color_factor: Color,
+
+    pub base_color_texture: Option<TextureInfo>,
+
+    pub metallic_factor: f32,
+
+    pub roughness_factor: f32,
+
+    /**
+     * Roughness in the green channel, metalness in the blue channel.
+     */
+    pub metallic_roughness_texture: Option<TextureInfo>,
+
+    /**
+     * Tangent space normals.
+     */
+    pub normal_texture: Option<TextureInfo>,
+
+    /**
+     * Scales the X and Y of the sampled normals.
+     */
+    pub normal_scale: f32,
+
+    /**
+     * Ambient occlusion in the red channel.
+     */
+    pub occlusion_texture: Option<TextureInfo>,
+
+    /**
+     * How much of the occlusion applies, from 0 (none) to 1 (all).
+     */
+    pub occlusion_strength: f32,
+
+    pub emissive_factor: Vector3<f32>,
+
+    pub emissive_texture: Option<TextureInfo>,
+
+    /**
+     * Multiplies the emission, from `KHR_materials_emissive_strength`.
+     */
+    pub emissive_strength: f32,
+
+    /**
+     * Back faces are lit with flipped normals instead of being culled.
+     */
+    pub double_sided: bool,
+
+    /**
+     * Shaded with the base color alone, from `KHR_materials_unlit`.
+     */
+    pub unlit: bool,
+}
+
+impl Default for Material {
+    /**
+     * The material of primitives without one, as defined by glTF.
+     */
+    fn default() -> Self {
+        Self {
+            name: None,
+            base_color_factor: Color::WHITE,
+            base_color_texture: None,
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            normal_scale: 1.0,
+            occlusion_texture: None,
+            occlusion_strength: 1.0,
+            emissive_factor: Vector3::zeros(),
+            emissive_texture: None,
+            emissive_strength: 1.0,
+            double_sided: false,
+            unlit: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_texture_transform() {
+        let transform = TextureTransform {
+            offset: Vector2::new(0.5, 0.0),
+            rotation: FRAC_PI_2,
+            scale: Vector2::new(2.0, 1.0),
+        };
+        // (1, 0) scales to (2, 0), rotates to (0, -2) and moves to (0.5, -2).
+        let uv = transform.apply(&Vector2::new(1.0, 0.0));
+        assert!((uv - Vector2::new(0.5, -2.0)).norm() < 1e-6, "{:?}", uv);
+
+        let info = TextureInfo {
+            texture: 0,
+            tex_coord: 0,
+            transform: None,
+        };
+        assert_eq!(
+            info.map(&Vector2::new(0.25, 0.75)),
+            Vector2::new(0.25, 0.75)
+        );
+        let sets = [Vector2::new(0.25, 0.75), Vector2::new(0.5, 0.5)];
+        let second = TextureInfo {
+            tex_coord: 1,
+            transform: Some(transform),
+            ..info
+        };
+        assert_eq!(second.map_set(&sets), transform.apply(&sets[1]));
+        assert_eq!(
+            second.map_set(&sets[..1]),
+            transform.apply(&Vector2::zeros())
+        );
+        let identity = TextureTransform::default();
+        assert_eq!(
+            identity.apply(&Vector2::new(0.25, 0.75)),
+            Vector2::new(0.25, 0.75)
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{common::color::Color, scene::morph::MorphTarget};
+use nalgebra::{Vector2, Vector3, Vector4};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Vertex {
+    pub position: Vector4<f32>,
+
+    pub normal: Option<Vector3<f32>>,
+
+    pub color: Option<Color>,
+
+    pub tex_coord: Option<Vector2<f32>>,
+
+    /**
+     * The second texture coordinate set, `TEXCOORD_1` in glTF.
+     */
+    pub tex_coord_1: Option<Vector2<f32>>,
+
+    /**
+     * XYZ along increasing U, with the sign of the bitangent in W.
+     */
+    pub tangent: Option<Vector4<f32>>,
+
+    /**
+     * Indices into `Skin::joints` of up to four influencing joints.
+     */
+    pub joints: Option<[u16; 4]>,
+
+    /**
+     * Influence of each of `joints`, summing to one.
+     */
+    pub weights: Option<Vector4<f32>>,
+}
+
+impl Vertex {
+    pub fn new(position: Vector3<f32>) -> Self {
+        Self {
+            position: position.push(1.0),
+            normal: None,
+            color: None,
+            tex_coord: None,
+            tex_coord_1: None,
+            tangent: None,
+            joints: None,
+            weights: None,
+        }
+    }
+
+    /**
+     * Texture coordinates of the set `TextureInfo::tex_coord` refers to.
+     */
+    pub fn tex_coord_set(&self, set: usize) -> Option<Vector2<f32>> {
+        match set {
+            0 => self.tex_coord,
+            1 => self.tex_coord_1,
+            _ => None,
+        }
+    }
+}
+
+/**
+ * An indexed triangle list, every three indices form a triangle.
+ */
+#[derive(Debug, Clone)]
+pub struct Primitive {
+    pub vertices: Vec<Vertex>,
+
+    pub indices: Vec<u32>,
+
+    /**
+     * Index into `Scene::textures` of the base color texture.
+     */
+    pub texture_index: Option<usize>,
+
+    /**
+     * Index into `Scene::materials`, the default material when `None`.
+     */
+    pub material: Option<usize>,
+
+    /**
+     * Blend shapes, weighted by `Mesh::weights` or `Node::weights`.
+     */
+    pub targets: Vec<MorphTarget>,
+}
+
+impl Primitive {
+    /**
+     * The vertex referenced by the `index`-th index,
+     * matching the index passed to `Shader::vertex_shader`.
+     */
+    pub fn vertex(&self, index: usize) -> &Vertex {
+        &self.vertices[self.indices[index] as usize]
+    }
+
+    /**
+     * The number of vertices to pass to `RenderPass::draw`.
+     */
+    pub fn vertex_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Mesh {
+    pub name: Option<String>,
+
+    pub primitives: Vec<Primitive>,
+
+    /**
+     * Default weights of the morph targets of the primitives.
+     */
+    pub weights: Vec<f32>,
+}
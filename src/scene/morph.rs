@@ -0,0 +1,135 @@
+use crate::scene::mesh::{Primitive, Vertex};
+use nalgebra::Vector3;
+
+/**
+ * Per-vertex displacements of a blend shape, empty for attributes it doesn't move.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct MorphTarget {
+    pub positions: Vec<Vector3<f32>>,
+
+    pub normals: Vec<Vector3<f32>>,
+
+    pub tangents: Vec<Vector3<f32>>,
+}
+
+impl Primitive {
+    /**
+     * The vertex referenced by the `index`-th index with the targets blended in by
+     * `weights`, for vertex shaders. Missing weights count as zero.
+     * Normals and tangents are not renormalized.
+     */
+    pub fn morphed_vertex(&self, index: usize, weights: &[f32]) -> Vertex {
+        let vertex_index = self.indices[index] as usize;
+        let mut vertex = self.vertices[vertex_index];
+        self.blend(&mut vertex, vertex_index, weights);
+        vertex
+    }
+
+    /**
+     * A copy with the targets blended into the vertices by `weights` on the CPU,
+     * drawable with shaders unaware of morphing. The copy has no targets left.
+     */
+    pub fn morphed(&self, weights: &[f32]) -> Primitive {
+        let mut vertices = self.vertices.clone();
+        for (i, vertex) in vertices.iter_mut().enumerate() {
+            self.blend(vertex, i, weights);
+            if let Some(normal) = vertex.normal.as_mut() {
+                *normal = normal.try_normalize(f32::EPSILON).unwrap_or(*normal);
+            }
+        }
+
+        Primitive {
+            vertices,
+            indices: self.indices.clone(),
+            texture_index: self.texture_index,
+            material: self.material,
+            targets: Vec::new(),
+        }
+    }
+
+    fn blend(&self, vertex: &mut Vertex, vertex_index: usize, weights: &[f32]) {
+        for (target, weight) in self.targets.iter().zip(weights) {
+            if *weight == 0.0 {
+                continue;
+            }
+            if let Some(delta) = target.positions.get(vertex_index) {
+                vertex.position += (delta * *weight).push(0.0);
+            }
+            if let (Some(normal), Some(delta)) =
+                (vertex.normal.as_mut(), target.normals.get(vertex_index))
+            {
+                *normal += delta * *weight;
+            }
+            if let (Some(tangent), Some(delta)) =
+                (vertex.tangent.as_mut(), target.tangents.get(vertex_index))
+            {
+                *tangent += (delta * *weight).push(0.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * A triangle with one target raising its apex and one pulling its base apart.
+     */
+    fn morphing_triangle() -> Primitive {
+        let mut vertices: Vec<Vertex> = [(-1.0, 0.0), (1.0, 0.0), (0.0, 1.0)]
+            .iter()
+            .map(|&(x, y)| Vertex::new(Vector3::new(x, y, 0.0)))
+            .collect();
+        vertices
+            .iter_mut()
+            .for_each(|v| v.normal = Some(Vector3::z()));
+        Primitive {
+            vertices,
+            indices: vec![0, 1, 2],
+            texture_index: None,
+            material: None,
+            targets: vec![
+                MorphTarget {
+                    positions: vec![Vector3::zeros(), Vector3::zeros(), Vector3::y()],
+                    normals: vec![Vector3::zeros(), Vector3::zeros(), Vector3::y()],
+                    tangents: Vec::new(),
+                },
+                MorphTarget {
+                    positions: vec![-Vector3::x(), Vector3::x(), Vector3::zeros()],
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_morph_positions() {
+        let primitive = morphing_triangle();
+        let weights = [0.5, 0.25];
+
+        let apex = primitive.morphed_vertex(2, &weights);
+        assert_eq!(apex.position.xyz(), Vector3::new(0.0, 1.5, 0.0));
+        assert_eq!(apex.normal, Some(Vector3::new(0.0, 0.5, 1.0)));
+        let left = primitive.morphed_vertex(0, &weights);
+        assert_eq!(left.position.xyz(), Vector3::new(-1.25, 0.0, 0.0));
+
+        // The CPU path agrees, with normals renormalized.
+        let morphed = primitive.morphed(&weights);
+        assert!(morphed.targets.is_empty());
+        for index in 0..3 {
+            assert_eq!(
+                morphed.vertex(index).position,
+                primitive.morphed_vertex(index, &weights).position
+            );
+        }
+        assert!((morphed.vertices[2].normal.unwrap().norm() - 1.0).abs() < 1e-6);
+
+        // Missing or zero weights leave the base shape.
+        assert_eq!(
+            primitive.morphed_vertex(2, &[]).position,
+            primitive.vertices[2].position
+        );
+    }
+}
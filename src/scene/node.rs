@@ -0,0 +1,170 @@
+use nalgebra::{Matrix3, Matrix4, Rotation3, UnitQuaternion, Vector3};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Transform {
+    Matrix(Matrix4<f32>),
+
+    /**
+     * Translation, rotation and scale, applied as `T * R * S`.
+     */
+    Trs {
+        translation: Vector3<f32>,
+        rotation: UnitQuaternion<f32>,
+        scale: Vector3<f32>,
+    },
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform::Trs {
+            translation: Vector3::zeros(),
+            rotation: UnitQuaternion::identity(),
+            scale: Vector3::repeat(1.0),
+        }
+    }
+
+    /**
+     * Translation, rotation and scale of the transform. Matrices with shear lose it.
+     */
+    pub fn decompose(&self) -> (Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>) {
+        match self {
+            Transform::Trs {
+                translation,
+                rotation,
+                scale,
+            } => (*translation, *rotation, *scale),
+            Transform::Matrix(matrix) => {
+                let linear = matrix.fixed_view::<3, 3>(0, 0).into_owned();
+                let mut scale = Vector3::from_fn(|i, _| linear.column(i).norm());
+                if linear.determinant() < 0.0 {
+                    scale.x = -scale.x;
+                }
+                let mut rotation = linear;
+                for i in 0..3 {
+                    if scale[i] != 0.0 {
+                        rotation.column_mut(i).unscale_mut(scale[i]);
+                    }
+                }
+                (
+                    matrix.column(3).xyz(),
+                    UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(
+                        rotation,
+                    )),
+                    scale,
+                )
+            }
+        }
+    }
+
+    pub fn matrix(&self) -> Matrix4<f32> {
+        match self {
+            Transform::Matrix(matrix) => *matrix,
+            Transform::Trs {
+                translation,
+                rotation,
+                scale,
+            } => {
+                Matrix4::new_translation(translation)
+                    * rotation.to_homogeneous()
+                    * Matrix4::new_nonuniform_scaling(scale)
+            }
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub name: Option<String>,
+
+    pub transform: Transform,
+
+    pub mesh: Option<usize>,
+
+    /**
+     * Index into `Scene::cameras`.
+     */
+    pub camera: Option<usize>,
+
+    /**
+     * Index into `Scene::lights`.
+     */
+    pub light: Option<usize>,
+
+    /**
+     * Index into `Scene::skins`, deforming the node's mesh.
+     */
+    pub skin: Option<usize>,
+
+    /**
+     * Morph target weights overriding the mesh's.
+     */
+    pub weights: Option<Vec<f32>>,
+
+    pub children: Vec<usize>,
+}
+
+/**
+ * The root nodes of one of the scenes defined by an asset.
+ */
+#[derive(Debug, Clone)]
+pub struct SceneGraph {
+    pub name: Option<String>,
+
+    pub roots: Vec<usize>,
+}
+
+/**
+ * A mesh placed in the world by a node.
+ * The same mesh appears once per node referencing it.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct Instance {
+    /**
+     * The placing node, `None` when the asset has no nodes.
+     */
+    pub node: Option<usize>,
+
+    pub mesh: usize,
+
+    pub world: Matrix4<f32>,
+}
+
+impl Instance {
+    /**
+     * The inverse transpose of the upper 3x3 of the world matrix, for transforming normals.
+     */
+    pub fn normal_matrix(&self) -> Matrix3<f32> {
+        let linear = self.world.fixed_view::<3, 3>(0, 0).into_owned();
+        linear
+            .try_inverse()
+            .map(|inverse| inverse.transpose())
+            .unwrap_or(linear)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trs_matrix() {
+        let transform = Transform::Trs {
+            translation: Vector3::new(1.0, 2.0, 3.0),
+            rotation: UnitQuaternion::from_axis_angle(
+                &Vector3::z_axis(),
+                std::f32::consts::FRAC_PI_2,
+            ),
+            scale: Vector3::new(2.0, 2.0, 2.0),
+        };
+        let p = transform
+            .matrix()
+            .transform_point(&nalgebra::Point3::new(1.0, 0.0, 0.0));
+        assert!((p - nalgebra::Point3::new(1.0, 4.0, 3.0)).norm() < 1e-6);
+    }
+}
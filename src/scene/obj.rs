@@ -0,0 +1,825 @@
+use crate::{
+    common::color::Color,
+    rasterizer::{image::ImageError, texture::Texture},
+    scene::{
+        material::{Material, TextureInfo},
+        mesh::{Mesh, Primitive, Vertex},
+        Scene,
+    },
+};
+use nalgebra::{Vector2, Vector3};
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    Parse {
+        file: String,
+        line: usize,
+        message: String,
+    },
+    Texture {
+        path: String,
+        error: ImageError,
+    },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(err) => write!(f, "io error: {}", err),
+            ObjError::Parse {
+                file,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file, line, message),
+            ObjError::Texture { path, error } => write!(f, "texture {}: {}", path, error),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<io::Error> for ObjError {
+    fn from(err: io::Error) -> Self {
+        ObjError::Io(err)
+    }
+}
+
+/**
+ * Load a Wavefront `.obj` file, together with its `.mtl` libraries and their textures.
+ */
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Scene, ObjError> {
+    let path = path.as_ref();
+    let base = path.parent().unwrap_or_else(|| Path::new(""));
+    let source = fs::read_to_string(path)?;
+    parse(
+        &path.display().to_string(),
+        &source,
+        |name| Ok(fs::read_to_string(base.join(name))?),
+        |name| {
+            Texture::load(base.join(name)).map_err(|error| ObjError::Texture {
+                path: name.to_string(),
+                error,
+            })
+        },
+    )
+}
+
+/**
+ * Parse the OBJ `source` named `file`. Material libraries are read through `read_library`
+ * and textures through `load_texture`, both by their path relative to the OBJ file,
+ * textures being relative to the library referencing them.
+ *
+ * Every object or group with faces becomes a mesh, with a primitive per material.
+ * Polygons are triangulated and vertices without normals get the normals of their
+ * smoothing group, or of their face when flat shaded.
+ */
+pub fn parse(
+    file: &str,
+    source: &str,
+    mut read_library: impl FnMut(&str) -> Result<String, ObjError>,
+    mut load_texture: impl FnMut(&str) -> Result<Texture<Color>, ObjError>,
+) -> Result<Scene, ObjError> {
+    let mut positions = Vec::new();
+    let mut colors = Vec::new();
+    let mut tex_coords = Vec::new();
+    let mut normals = Vec::new();
+
+    let mut library = Library::default();
+    let mut objects = vec![Object::default()];
+    let mut material: Option<String> = None;
+    let mut smoothing = 0;
+
+    for (line, statement, args) in statements(source) {
+        let error = |message: String| ObjError::Parse {
+            file: file.to_string(),
+            line,
+            message,
+        };
+        match statement {
+            "v" => {
+                let values = floats(&args).map_err(error)?;
+                match values.len() {
+                    3 | 4 => colors.push(None),
+                    6 | 7 => colors.push(Some(Color::new(values[3], values[4], values[5], 1.0))),
+                    n => return Err(error(format!("expected 3 or 6 coordinates, got {}", n))),
+                }
+                positions.push(Vector3::new(values[0], values[1], values[2]));
+            }
+            "vt" => {
+                let values = floats(&args).map_err(error)?;
+                if values.is_empty() || values.len() > 3 {
+                    return Err(error(format!(
+                        "expected 1 to 3 coordinates, got {}",
+                        values.len()
+                    )));
+                }
+                // OBJ puts the origin at the bottom left, textures are stored top row first.
+                let v = values.get(1).copied().unwrap_or(0.0);
+                tex_coords.push(Vector2::new(values[0], 1.0 - v));
+            }
+            "vn" => {
+                let values = floats(&args).map_err(error)?;
+                if values.len() != 3 {
+                    return Err(error(format!(
+                        "expected 3 coordinates, got {}",
+                        values.len()
+                    )));
+                }
+                normals.push(Vector3::new(values[0], values[1], values[2]));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(error(format!(
+                        "expected at least 3 vertices, got {}",
+                        args.len()
+                    )));
+                }
+                let counts = [positions.len(), tex_coords.len(), normals.len()];
+                let corners = args
+                    .iter()
+                    .map(|arg| corner(arg, counts))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                objects
+                    .last_mut()
+                    .unwrap()
+                    .group(&material)
+                    .push(Face { corners, smoothing });
+            }
+            "o" | "g" => {
+                let name = (!args.is_empty()).then(|| args.join(" "));
+                let object = objects.last_mut().unwrap();
+                if object.groups.is_empty() {
+                    object.name = name;
+                } else {
+                    objects.push(Object {
+                        name,
+                        groups: Vec::new(),
+                    });
+                }
+            }
+            "s" => {
+                smoothing = match args.first().copied() {
+                    None | Some("off") => 0,
+                    Some(group) => group
+                        .parse()
+                        .map_err(|_| error(format!("invalid smoothing group `{}`", group)))?,
+                };
+            }
+            "usemtl" => material = (!args.is_empty()).then(|| args.join(" ")),
+            "mtllib" => {
+                for name in &args {
+                    let source = read_library(name)?;
+                    let base = Path::new(name).parent().unwrap_or_else(|| Path::new(""));
+                    library.parse(name, &source, base, &mut load_texture)?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let vertices = Vertices {
+        positions: &positions,
+        colors: &colors,
+        tex_coords: &tex_coords,
+        normals: &normals,
+    };
+    let meshes = objects
+        .iter()
+        .filter(|object| !object.groups.is_empty())
+        .map(|object| vertices.mesh(object, &library))
+        .collect();
+
+    Ok(Scene {
+        meshes,
+        textures: library.textures,
+        materials: library.materials,
+        cameras: Vec::new(),
+        lights: Vec::new(),
+        skins: Vec::new(),
+        animations: Vec::new(),
+        nodes: Vec::new(),
+        graphs: Vec::new(),
+        default_graph: None,
+    })
+}
+
+/**
+ * The statements of an OBJ or MTL source, with their line number and arguments,
+ * after joining continued lines and stripping comments.
+ */
+fn statements(source: &str) -> Vec<(usize, &str, Vec<&str>)> {
+    let mut statements = Vec::new();
+    let mut tokens = Vec::new();
+    let mut first_line = None;
+    for (index, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        let (line, continued) = match line.trim_end().strip_suffix('\\') {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        first_line.get_or_insert(index + 1);
+        tokens.extend(line.split_whitespace());
+        if continued {
+            continue;
+        }
+        if let Some((&statement, args)) = tokens.split_first() {
+            statements.push((first_line.unwrap(), statement, args.to_vec()));
+        }
+        tokens.clear();
+        first_line = None;
+    }
+    statements
+}
+
+fn floats(args: &[&str]) -> Result<Vec<f32>, String> {
+    args.iter()
+        .map(|arg| arg.parse().map_err(|_| format!("invalid number `{}`", arg)))
+        .collect()
+}
+
+/**
+ * Resolve a 1-based index, or a negative one counting back from the last element.
+ */
+fn resolve(index: &str, count: usize) -> Result<usize, String> {
+    let value: i64 = index
+        .parse()
+        .map_err(|_| format!("invalid index `{}`", index))?;
+    let resolved = match value {
+        1.. => value - 1,
+        ..=-1 => count as i64 + value,
+        0 => return Err(String::from("index 0 is invalid, indices start at 1")),
+    };
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(format!("index {} out of range", value));
+    }
+    Ok(resolved as usize)
+}
+
+/**
+ * Parse a face corner `v`, `v/vt`, `v//vn` or `v/vt/vn`, given the number of
+ * positions, texture coordinates and normals defined so far.
+ */
+fn corner(arg: &str, [positions, tex_coords, normals]: [usize; 3]) -> Result<Corner, String> {
+    let mut parts = arg.split('/');
+    let position = resolve(parts.next().unwrap_or(""), positions)?;
+    let tex_coord = match parts.next() {
+        None | Some("") => None,
+        Some(index) => Some(resolve(index, tex_coords)?),
+    };
+    let normal = match parts.next() {
+        None | Some("") => None,
+        Some(index) => Some(resolve(index, normals)?),
+    };
+    if parts.next().is_some() {
+        return Err(format!("invalid face vertex `{}`", arg));
+    }
+    Ok(Corner {
+        position,
+        tex_coord,
+        normal,
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Corner {
+    position: usize,
+
+    tex_coord: Option<usize>,
+
+    normal: Option<usize>,
+}
+
+#[derive(Debug)]
+struct Face {
+    corners: Vec<Corner>,
+
+    /**
+     * Smoothing group, 0 when flat shaded.
+     */
+    smoothing: u32,
+}
+
+#[derive(Debug, Default)]
+struct Object {
+    name: Option<String>,
+
+    /**
+     * Faces by material name, in order of first use.
+     */
+    groups: Vec<(Option<String>, Vec<Face>)>,
+}
+
+impl Object {
+    fn group(&mut self, material: &Option<String>) -> &mut Vec<Face> {
+        let index = match self.groups.iter().position(|(name, _)| name == material) {
+            Some(index) => index,
+            None => {
+                self.groups.push((material.clone(), Vec::new()));
+                self.groups.len() - 1
+            }
+        };
+        &mut self.groups[index].1
+    }
+}
+
+#[derive(Debug, Default)]
+struct Library {
+    materials: Vec<Material>,
+
+    textures: Vec<Texture<Color>>,
+
+    /**
+     * Indices of the textures loaded so far, by path.
+     */
+    texture_paths: HashMap<PathBuf, usize>,
+}
+
+impl Library {
+    fn find(&self, name: &str) -> Option<usize> {
+        self.materials
+            .iter()
+            .position(|material| material.name.as_deref() == Some(name))
+    }
+
+    /**
+     * Add the materials of the MTL `source` named `file`, whose textures are relative to `base`.
+     * Statements without a metallic-roughness counterpart, like `Ka` and `Ks`, are ignored.
+     */
+    fn parse(
+        &mut self,
+        file: &str,
+        source: &str,
+        base: &Path,
+        load_texture: &mut impl FnMut(&str) -> Result<Texture<Color>, ObjError>,
+    ) -> Result<(), ObjError> {
+        for (line, statement, args) in statements(source) {
+            let error = |message: String| ObjError::Parse {
+                file: file.to_string(),
+                line,
+                message,
+            };
+            if statement == "newmtl" {
+                self.materials.push(Material {
+                    name: Some(args.join(" ")),
+                    metallic_factor: 0.0,
+                    ..Material::default()
+                });
+                continue;
+            }
+            let Some(material) = self.materials.last_mut() else {
+                return Err(error(format!("`{}` before `newmtl`", statement)));
+            };
+            let color = || -> Result<Vector3<f32>, ObjError> {
+                match floats(&args).map_err(error)?[..] {
+                    [r, g, b] => Ok(Vector3::new(r, g, b)),
+                    [value] => Ok(Vector3::repeat(value)),
+                    _ => Err(error(String::from("expected an rgb color"))),
+                }
+            };
+            let scalar = || -> Result<f32, ObjError> {
+                match floats(&args).map_err(error)?[..] {
+                    [value] => Ok(value),
+                    _ => Err(error(String::from("expected a number"))),
+                }
+            };
+            match statement {
+                "Kd" => {
+                    let [r, g, b] = color()?.into();
+                    material.base_color_factor = Color::new(r, g, b, material.base_color_factor.a);
+                }
+                "Ke" => material.emissive_factor = color()?,
+                // Maps the Phong exponent to the roughness of a matching Beckmann lobe.
+                "Ns" => material.roughness_factor = (2.0 / (scalar()?.max(0.0) + 2.0)).sqrt(),
+                "d" => material.base_color_factor.a = scalar()?,
+                "Tr" => material.base_color_factor.a = 1.0 - scalar()?,
+                "map_Kd" | "map_Bump" | "map_bump" | "bump" => {
+                    let map = TextureMap::parse(&args).map_err(error)?;
+                    let path = base.join(map.path);
+                    let texture = match self.texture_paths.get(&path) {
+                        Some(&texture) => texture,
+                        None => {
+                            self.textures.push(load_texture(&path.to_string_lossy())?);
+                            self.texture_paths.insert(path, self.textures.len() - 1);
+                            self.textures.len() - 1
+                        }
+                    };
+                    let info = Some(TextureInfo {
+                        texture,
+                        tex_coord: 0,
+                        transform: None,
+                    });
+                    if statement == "map_Kd" {
+                        material.base_color_texture = info;
+                    } else {
+                        material.normal_texture = info;
+                        material.normal_scale = map.bump_multiplier;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+ * The arguments of a `map_*` statement, options followed by a path.
+ */
+struct TextureMap {
+    path: String,
+
+    /**
+     * The `-bm` option.
+     */
+    bump_multiplier: f32,
+}
+
+impl TextureMap {
+    fn parse(args: &[&str]) -> Result<Self, String> {
+        let mut bump_multiplier = 1.0;
+        let mut rest = args;
+        while let Some((option, tail)) = rest.split_first().filter(|(arg, _)| arg.starts_with('-'))
+        {
+            // Options take up to three numbers or a single word.
+            let count = match *option {
+                "-imfchan" | "-type" | "-blendu" | "-blendv" | "-clamp" | "-cc" => 1,
+                _ => tail
+                    .iter()
+                    .take(3)
+                    .take_while(|arg| arg.parse::<f32>().is_ok())
+                    .count(),
+            };
+            if *option == "-bm" {
+                bump_multiplier = floats(&tail[..count.min(1)])?
+                    .first()
+                    .copied()
+                    .ok_or("`-bm` expects a number")?;
+            }
+            rest = &tail[count.min(tail.len())..];
+        }
+        if rest.is_empty() {
+            return Err(String::from("missing texture path"));
+        }
+        Ok(Self {
+            path: rest.join(" "),
+            bump_multiplier,
+        })
+    }
+}
+
+/**
+ * Ear clip a simple polygon into triangles of the same winding, after projecting it onto
+ * the plane of its Newell normal. Falls back to a fan when no ear is found.
+ */
+fn triangulate(points: &[Vector3<f32>]) -> Vec<[usize; 3]> {
+    let n = points.len();
+    if n == 3 {
+        return vec![[0, 1, 2]];
+    }
+
+    let normal = newell_normal(points);
+    let axis = normal.iamax();
+    let (mut a, mut b) = ((axis + 1) % 3, (axis + 2) % 3);
+    // Keep the polygon counter-clockwise in the projection.
+    if normal[axis] < 0.0 {
+        std::mem::swap(&mut a, &mut b);
+    }
+    let projected: Vec<Vector2<f32>> = points.iter().map(|p| Vector2::new(p[a], p[b])).collect();
+    let cross = |o: usize, p: usize, q: usize| {
+        (projected[p] - projected[o]).perp(&(projected[q] - projected[o]))
+    };
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let ear = (0..len).find(|&i| {
+            let [prev, cur, next] = [
+                remaining[(i + len - 1) % len],
+                remaining[i],
+                remaining[(i + 1) % len],
+            ];
+            cross(prev, cur, next) > 0.0
+                && remaining.iter().all(|&other| {
+                    [prev, cur, next].contains(&other)
+                        || projected[other] == projected[prev]
+                        || projected[other] == projected[cur]
+                        || projected[other] == projected[next]
+                        || cross(prev, cur, other) < 0.0
+                        || cross(cur, next, other) < 0.0
+                        || cross(next, prev, other) < 0.0
+                })
+        });
+        let i = ear.unwrap_or(1);
+        triangles.push([
+            remaining[(i + len - 1) % len],
+            remaining[i],
+            remaining[(i + 1) % len],
+        ]);
+        remaining.remove(i);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+/**
+ * Twice the area times the unit normal of a possibly non-planar polygon.
+ */
+fn newell_normal(points: &[Vector3<f32>]) -> Vector3<f32> {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(p, q)| (p - q).cross(&(p + q)) / 2.0)
+        .sum::<Vector3<f32>>()
+}
+
+struct Vertices<'a> {
+    positions: &'a [Vector3<f32>],
+
+    colors: &'a [Option<Color>],
+
+    tex_coords: &'a [Vector2<f32>],
+
+    normals: &'a [Vector3<f32>],
+}
+
+impl Vertices<'_> {
+    fn mesh(&self, object: &Object, library: &Library) -> Mesh {
+        let faces = || object.groups.iter().flat_map(|(_, faces)| faces);
+
+        // Smoothing keys are the smoothing group, or distinct negative keys for flat faces.
+        let keys: Vec<i64> = faces()
+            .enumerate()
+            .map(|(index, face)| match face.smoothing {
+                0 => -(index as i64) - 1,
+                group => group as i64,
+            })
+            .collect();
+        let mut smooth_normals = HashMap::<(usize, i64), Vector3<f32>>::new();
+        for (face, &key) in faces().zip(&keys) {
+            if face.corners.iter().all(|corner| corner.normal.is_some()) {
+                continue;
+            }
+            let points: Vec<_> = face
+                .corners
+                .iter()
+                .map(|c| self.positions[c.position])
+                .collect();
+            let normal = newell_normal(&points);
+            for corner in &face.corners {
+                *smooth_normals
+                    .entry((corner.position, key))
+                    .or_insert_with(Vector3::zeros) += normal;
+            }
+        }
+
+        let mut keys = keys.into_iter();
+        let primitives = object
+            .groups
+            .iter()
+            .map(|(name, faces)| {
+                let material = name.as_deref().and_then(|name| library.find(name));
+                let mut vertices = Vec::new();
+                let mut indices = Vec::new();
+                let mut lookup = HashMap::new();
+                for face in faces {
+                    let key = keys.next().unwrap();
+                    let points: Vec<_> = face
+                        .corners
+                        .iter()
+                        .map(|c| self.positions[c.position])
+                        .collect();
+                    let corner_indices: Vec<u32> = face
+                        .corners
+                        .iter()
+                        .map(|corner| {
+                            let smoothing = corner.normal.is_none().then_some(key);
+                            let id = (corner.position, corner.tex_coord, corner.normal, smoothing);
+                            *lookup.entry(id).or_insert_with(|| {
+                                let mut vertex = Vertex::new(self.positions[corner.position]);
+                                vertex.color = self.colors[corner.position];
+                                vertex.tex_coord =
+                                    corner.tex_coord.map(|index| self.tex_coords[index]);
+                                vertex.normal = Some(match corner.normal {
+                                    Some(index) => self.normals[index],
+                                    None => smooth_normals[&(corner.position, key)],
+                                })
+                                .map(|normal| normal.try_normalize(0.0).unwrap_or_else(Vector3::z));
+                                vertices.push(vertex);
+                                (vertices.len() - 1) as u32
+                            })
+                        })
+                        .collect();
+                    for triangle in triangulate(&points) {
+                        indices.extend(triangle.map(|corner| corner_indices[corner]));
+                    }
+                }
+
+                let mut primitive = Primitive {
+                    vertices,
+                    indices,
+                    texture_index: material
+                        .and_then(|index| library.materials[index].base_color_texture)
+                        .map(|info| info.texture),
+                    material,
+                    targets: Vec::new(),
+                };
+                primitive.generate_tangents();
+                primitive
+            })
+            .collect();
+
+        Mesh {
+            name: object.name.clone(),
+            primitives,
+            weights: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(source: &str) -> Result<Scene, ObjError> {
+        parse(
+            "test.obj",
+            source,
+            |name| Err(io::Error::new(io::ErrorKind::NotFound, name.to_string()).into()),
+            |_| Ok(Texture::new(1, 1)),
+        )
+    }
+
+    fn area(primitive: &Primitive) -> f32 {
+        primitive
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let [a, b, c] =
+                    [0, 1, 2].map(|i| primitive.vertices[triangle[i] as usize].position.xyz());
+                (b - a).cross(&(c - a)).z / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_triangulate_concave() {
+        // An L shape with a reflex vertex at (1, 1), of area 3.
+        let scene =
+            parse_str("v 0 0 0\nv 2 0 0\nv 2 1 0\nv 1 1 0\nv 1 2 0\nv 0 2 0\nf 1 2 3 4 5 6\n")
+                .unwrap();
+        let primitive = &scene.meshes[0].primitives[0];
+        assert_eq!(primitive.triangle_count(), 4);
+        // Every triangle is counter-clockwise, so they cover the shape without overlap.
+        for triangle in primitive.indices.chunks_exact(3) {
+            let [a, b, c] =
+                [0, 1, 2].map(|i| primitive.vertices[triangle[i] as usize].position.xyz());
+            assert!((b - a).cross(&(c - a)).z > 0.0);
+        }
+        assert!((area(primitive) - 3.0).abs() < 1e-6);
+        assert_eq!(primitive.vertex(0).normal, Some(Vector3::z()));
+    }
+
+    #[test]
+    fn test_negative_indices() {
+        let scene = parse_str(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nvt 0 0\nvt 1 0\nvt 1 1\nvn 0 0 1\nf -3/-3/-1 -2/-2/-1 -1/-1/-1\n",
+        )
+        .unwrap();
+        let primitive = &scene.meshes[0].primitives[0];
+        assert_eq!(primitive.indices, vec![0, 1, 2]);
+        assert_eq!(
+            primitive.vertex(2).position.xyz(),
+            Vector3::new(1.0, 1.0, 0.0)
+        );
+        // V is flipped to the top-left origin of textures.
+        assert_eq!(primitive.vertex(2).tex_coord, Some(Vector2::new(1.0, 0.0)));
+
+        for source in [
+            "v 0 0 0\nf 1 2 3\n",
+            "v 0 0 0\nf 0 1 1\n",
+            "v 0 0 0\nf -2 1 1\n",
+        ] {
+            assert!(
+                matches!(parse_str(source), Err(ObjError::Parse { line: 2, .. })),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_objects_and_materials() {
+        let obj = "\
+mtllib scene.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+o first
+usemtl red
+f 1 2 3
+usemtl textured
+f 1 3 4
+usemtl red
+f 2 3 4
+g second
+usemtl unknown
+f 1 2 \\
+  3 4
+";
+        let mtl = "\
+# Materials
+newmtl red
+Kd 1 0 0
+Ks 0.5 0.5 0.5
+Ns 48
+d 0.5
+
+newmtl textured
+map_Kd -s 2 2 1 textures/wood.png
+map_Bump -bm 0.5 textures/normal map.png
+bump textures/wood.png
+";
+        let mut libraries = Vec::new();
+        let mut textures = Vec::new();
+        let scene = parse(
+            "scene.obj",
+            obj,
+            |name| {
+                libraries.push(name.to_string());
+                Ok(mtl.to_string())
+            },
+            |path| {
+                textures.push(path.to_string());
+                Ok(Texture::new(1, 1))
+            },
+        )
+        .unwrap();
+        assert_eq!(libraries, ["scene.mtl"]);
+        assert_eq!(textures, ["textures/wood.png", "textures/normal map.png"]);
+
+        assert_eq!(scene.meshes.len(), 2);
+        let first = &scene.meshes[0];
+        assert_eq!(first.name.as_deref(), Some("first"));
+        assert_eq!(first.primitives.len(), 2);
+        assert_eq!(first.primitives[0].material, Some(0));
+        assert_eq!(first.primitives[0].triangle_count(), 2);
+        assert_eq!(first.primitives[1].material, Some(1));
+        assert_eq!(first.primitives[1].texture_index, Some(0));
+
+        let second = &scene.meshes[1];
+        assert_eq!(second.name.as_deref(), Some("second"));
+        assert_eq!(second.primitives[0].material, None);
+        assert_eq!(second.primitives[0].triangle_count(), 2);
+
+        let red = &scene.materials[0];
+        let Color { r, g, b, a } = red.base_color_factor;
+        assert_eq!([r, g, b, a], [1.0, 0.0, 0.0, 0.5]);
+        assert_eq!(red.metallic_factor, 0.0);
+        assert!((red.roughness_factor - 0.2).abs() < 1e-6);
+
+        let textured = &scene.materials[1];
+        assert_eq!(
+            textured.base_color_texture.map(|info| info.texture),
+            Some(0)
+        );
+        assert_eq!(textured.normal_texture.map(|info| info.texture), Some(0));
+        assert_eq!(textured.normal_scale, 1.0);
+        assert_eq!(scene.textures.len(), 2);
+    }
+
+    #[test]
+    fn test_smoothing_groups() {
+        // Two faces folded along the y axis.
+        let source = "\
+v 0 0 0
+v 0 1 0
+v 1 0 0
+v -1 0 1
+s 1
+f 1 3 2
+f 1 2 4
+";
+        let smooth = parse_str(source).unwrap();
+        let primitive = &smooth.meshes[0].primitives[0];
+        // The shared edge is welded, with the average normal.
+        assert_eq!(primitive.vertices.len(), 4);
+        let normal = primitive.vertex(0).normal.unwrap();
+        // Face normals are weighted by area, 1/2 and sqrt(2)/2.
+        let expected = Vector3::new(1.0, 0.0, 2.0).normalize();
+        assert!((normal - expected).norm() < 1e-6, "{:?}", normal);
+
+        let flat = parse_str(&source.replace("s 1", "s off")).unwrap();
+        let primitive = &flat.meshes[0].primitives[0];
+        assert_eq!(primitive.vertices.len(), 6);
+        assert_eq!(primitive.vertex(0).normal, Some(Vector3::z()));
+    }
+}
@@ -0,0 +1,335 @@
+use crate::{
+    common::color::{linear_to_srgb, srgb_to_linear, Color},
+    interpolate::Interpolate,
+    rasterizer::{
+        shader::{FsPayload, Shader, VsOutput},
+        texture::{EdgeBehavior, SamplingMethod, Texture},
+    },
+    scene::{
+        light::Lighting,
+        material::{Material, TextureInfo},
+        mesh::Primitive,
+        tangent::perturb_normal,
+    },
+};
+use nalgebra::{Matrix3, Matrix4, Vector2, Vector3, Vector4};
+use std::f32::consts::PI;
+
+/**
+ * Reflectance of dielectrics at normal incidence.
+ */
+const DIELECTRIC_F0: f32 = 0.04;
+
+/**
+ * Surface properties at a point, after textures and factors are applied.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct SurfacePoint {
+    /**
+     * Linear base color.
+     */
+    pub base_color: Vector3<f32>,
+
+    pub metallic: f32,
+
+    pub roughness: f32,
+}
+
+/**
+ * The glTF reference BRDF: Lambertian diffuse and a Cook–Torrance specular lobe with
+ * the GGX distribution, height-correlated Smith visibility and Schlick Fresnel,
+ * mixed by metalness.
+ *
+ * `n`, `v` and `l` are unit vectors: the normal, towards the viewer and towards the light.
+ */
+pub fn brdf(
+    surface: &SurfacePoint,
+    n: &Vector3<f32>,
+    v: &Vector3<f32>,
+    l: &Vector3<f32>,
+) -> Vector3<f32> {
+    let n_dot_l = n.dot(l);
+    let n_dot_v = n.dot(v).abs().max(1e-4);
+    if n_dot_l <= 0.0 {
+        return Vector3::zeros();
+    }
+    let h = (v + l).try_normalize(f32::EPSILON).unwrap_or(*n);
+    let n_dot_h = n.dot(&h).max(0.0);
+    let v_dot_h = v.dot(&h).max(0.0);
+
+    let f0 = Vector3::repeat(DIELECTRIC_F0).lerp(&surface.base_color, surface.metallic);
+    let c_diff = surface.base_color * (1.0 - surface.metallic);
+    let fresnel = f0 + (Vector3::repeat(1.0) - f0) * (1.0 - v_dot_h).powi(5);
+
+    let alpha = surface.roughness * surface.roughness;
+    let alpha_squared = (alpha * alpha).max(1e-6);
+    let d = n_dot_h * n_dot_h * (alpha_squared - 1.0) + 1.0;
+    let distribution = alpha_squared / (PI * d * d);
+    let visibility = 0.5
+        / (n_dot_l * (n_dot_v * n_dot_v * (1.0 - alpha_squared) + alpha_squared).sqrt()
+            + n_dot_v * (n_dot_l * n_dot_l * (1.0 - alpha_squared) + alpha_squared).sqrt());
+
+    let diffuse = (Vector3::repeat(1.0) - fresnel).component_mul(&c_diff) / PI;
+    let specular = fresnel * distribution * visibility;
+    diffuse + specular
+}
+
+#[derive(Debug, Interpolate)]
+pub struct PbrVarying {
+    /**
+     * World space.
+     */
+    pub position: Vector3<f32>,
+
+    /**
+     * World space, not normalized.
+     */
+    pub normal: Vector3<f32>,
+
+    /**
+     * World space, with the bitangent sign in `w`, zero without vertex tangents.
+     */
+    pub tangent: Vector4<f32>,
+
+    pub tex_coord: Vector2<f32>,
+
+    pub tex_coord_1: Vector2<f32>,
+
+    pub color: Vector4<f32>,
+}
+
+/**
+ * Shades a primitive with its metallic-roughness material under `lighting`,
+ * writing sRGB encoded colors. Unlit materials show their base color.
+ *
+ * Normal textures are ignored on vertices without tangents.
+ */
+pub struct PbrProgram<'a> {
+    pub primitive: &'a Primitive,
+
+    pub material: &'a Material,
+
+    /**
+     * The textures referenced by `material`, usually `Scene::textures`.
+     */
+    pub textures: &'a [Texture<Color>],
+
+    pub model: Matrix4<f32>,
+
+    pub normal_matrix: Matrix3<f32>,
+
+    pub view_projection: Matrix4<f32>,
+
+    /**
+     * World space camera position.
+     */
+    pub eye: Vector3<f32>,
+
+    pub lighting: &'a Lighting,
+}
+
+impl<'a> PbrProgram<'a> {
+    fn sample(&self, info: Option<TextureInfo>, tex_coords: &[Vector2<f32>]) -> Option<Color> {
+        let info = info?;
+        let texture = self.textures.get(info.texture)?;
+        Some(texture.sample(
+            &info.map_set(tex_coords),
+            SamplingMethod::Bilinear,
+            EdgeBehavior::Wrap,
+        ))
+    }
+}
+
+impl<'a> Shader for PbrProgram<'a> {
+    type Varying = PbrVarying;
+
+    fn vertex_shader(&self, index: usize) -> VsOutput<Self::Varying> {
+        let vertex = self.primitive.vertex(index);
+        let position = self.model * vertex.position;
+        let color = vertex.color.unwrap_or(Color::WHITE);
+        let tangent = vertex.tangent.unwrap_or_else(Vector4::zeros);
+        VsOutput {
+            position: self.view_projection * position,
+            varying: PbrVarying {
+                position: position.xyz(),
+                normal: self.normal_matrix * vertex.normal.unwrap_or_else(Vector3::zeros),
+                tangent: (self.model * tangent.xyz().push(0.0)).xyz().push(tangent.w),
+                tex_coord: vertex.tex_coord.unwrap_or_else(Vector2::zeros),
+                tex_coord_1: vertex.tex_coord_1.unwrap_or_else(Vector2::zeros),
+                color: Vector4::new(color.r, color.g, color.b, color.a),
+            },
+        }
+    }
+
+    fn fragment_shader(&self, payload: FsPayload<Self::Varying>) -> Color {
+        let varying = payload.varying;
+        let material = self.material;
+        let tex_coord = [varying.tex_coord, varying.tex_coord_1];
+
+        let factor = material.base_color_factor;
+        let mut base_color =
+            Vector4::new(factor.r, factor.g, factor.b, factor.a).component_mul(&varying.color);
+        if let Some(texel) = self.sample(material.base_color_texture, &tex_coord) {
+            base_color.x *= srgb_to_linear(texel.r);
+            base_color.y *= srgb_to_linear(texel.g);
+            base_color.z *= srgb_to_linear(texel.b);
+            base_color.w *= texel.a;
+        }
+        if material.unlit {
+            return Color::new(
+                linear_to_srgb(base_color.x),
+                linear_to_srgb(base_color.y),
+                linear_to_srgb(base_color.z),
+                base_color.w,
+            );
+        }
+
+        let mut metallic = material.metallic_factor;
+        let mut roughness = material.roughness_factor;
+        if let Some(texel) = self.sample(material.metallic_roughness_texture, &tex_coord) {
+            roughness *= texel.g;
+            metallic *= texel.b;
+        }
+        let surface = SurfacePoint {
+            base_color: base_color.xyz(),
+            metallic: metallic.clamp(0.0, 1.0),
+            roughness: roughness.clamp(0.0, 1.0),
+        };
+
+        let v = (self.eye - varying.position)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::z);
+        let mut normal = varying.normal;
+        if let Some(texel) = self.sample(material.normal_texture, &tex_coord) {
+            normal = perturb_normal(&normal, &varying.tangent, &texel, material.normal_scale);
+        }
+        let mut n = normal.try_normalize(f32::EPSILON).unwrap_or(v);
+        if material.double_sided && n.dot(&v) < 0.0 {
+            n = -n;
+        }
+
+        let mut radiance = self
+            .lighting
+            .lights
+            .iter()
+            .filter_map(|light| light.incident(&varying.position))
+            .fold(Vector3::zeros(), |sum, (l, incident)| {
+                sum + brdf(&surface, &n, &v, &l).component_mul(&incident) * n.dot(&l).max(0.0)
+            });
+
+        let mut ambient = self
+            .lighting
+            .ambient
+            .component_mul(&(surface.base_color * (1.0 - surface.metallic)));
+        if let Some(texel) = self.sample(material.occlusion_texture, &tex_coord) {
+            ambient *= 1.0 + material.occlusion_strength * (texel.r - 1.0);
+        }
+        radiance += ambient;
+
+        let mut emissive = material.emissive_factor * material.emissive_strength;
+        if let Some(texel) = self.sample(material.emissive_texture, &tex_coord) {
+            emissive.x *= srgb_to_linear(texel.r);
+            emissive.y *= srgb_to_linear(texel.g);
+            emissive.z *= srgb_to_linear(texel.b);
+        }
+        radiance += emissive;
+
+        Color::new(
+            linear_to_srgb(radiance.x.max(0.0)),
+            linear_to_srgb(radiance.y.max(0.0)),
+            linear_to_srgb(radiance.z.max(0.0)),
+            base_color.w,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brdf() {
+        let n = Vector3::z();
+        let dielectric = SurfacePoint {
+            base_color: Vector3::repeat(1.0),
+            metallic: 0.0,
+            roughness: 1.0,
+        };
+        // Head-on, D = 1/π and V = 1/4 at full roughness.
+        let f = brdf(&dielectric, &n, &n, &n);
+        assert!((f.x - 0.97 / PI).abs() < 1e-5, "{:?}", f);
+
+        // Metals have no diffuse lobe and reflect tinted by their base color.
+        let gold = SurfacePoint {
+            base_color: Vector3::new(1.0, 0.8, 0.3),
+            metallic: 1.0,
+            roughness: 0.5,
+        };
+        let f = brdf(&gold, &n, &n, &n);
+        assert!((f.y / f.x - 0.8).abs() < 1e-5 && (f.z / f.x - 0.3).abs() < 1e-5);
+
+        // Smoother surfaces concentrate the highlight.
+        let smooth = SurfacePoint {
+            roughness: 0.2,
+            ..gold
+        };
+        assert!(brdf(&smooth, &n, &n, &n).x > f.x);
+        assert_eq!(brdf(&gold, &n, &n, &-n), Vector3::zeros());
+    }
+
+    #[test]
+    fn test_unlit() {
+        let primitive = Primitive {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        };
+        let material = Material {
+            base_color_factor: Color::new(0.5, 0.25, 1.0, 0.8),
+            unlit: true,
+            ..Material::default()
+        };
+        let lighting = Lighting {
+            ambient: Vector3::repeat(1.0),
+            lights: Vec::new(),
+        };
+        let mut program = PbrProgram {
+            primitive: &primitive,
+            material: &material,
+            textures: &[],
+            model: Matrix4::identity(),
+            normal_matrix: Matrix3::identity(),
+            view_projection: Matrix4::identity(),
+            eye: Vector3::z(),
+            lighting: &lighting,
+        };
+        let payload = || FsPayload {
+            position: Vector4::zeros(),
+            varying: PbrVarying {
+                position: Vector3::zeros(),
+                normal: Vector3::z(),
+                tangent: Vector4::zeros(),
+                tex_coord: Vector2::zeros(),
+                tex_coord_1: Vector2::zeros(),
+                color: Vector4::repeat(1.0),
+            },
+            bary_coord: Vector3::repeat(1.0 / 3.0),
+            primitive_id: 0,
+        };
+
+        let color = program.fragment_shader(payload());
+        assert!((color.r - linear_to_srgb(0.5)).abs() < 1e-6);
+        assert!((color.g - linear_to_srgb(0.25)).abs() < 1e-6);
+        assert!((color.b - 1.0).abs() < 1e-6 && color.a == 0.8);
+
+        // Lit, the fully metallic default has no diffuse ambient.
+        let lit = Material {
+            unlit: false,
+            ..material.clone()
+        };
+        program.material = &lit;
+        assert_eq!(program.fragment_shader(payload()).r, 0.0);
+    }
+}
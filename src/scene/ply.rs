@@ -0,0 +1,649 @@
+use crate::{
+    common::color::Color,
+    scene::mesh::{Mesh, Primitive, Vertex},
+};
+use nalgebra::{Vector2, Vector3};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug)]
+pub enum PlyError {
+    Io(io::Error),
+    Malformed(String),
+}
+
+impl fmt::Display for PlyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlyError::Io(err) => write!(f, "io error: {}", err),
+            PlyError::Malformed(reason) => write!(f, "malformed PLY: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for PlyError {}
+
+impl From<io::Error> for PlyError {
+    fn from(err: io::Error) -> Self {
+        PlyError::Io(err)
+    }
+}
+
+fn malformed(reason: impl Into<String>) -> PlyError {
+    PlyError::Malformed(reason.into())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Result<Self, PlyError> {
+        match name {
+            "char" | "int8" => Ok(ScalarType::Int8),
+            "uchar" | "uint8" => Ok(ScalarType::UInt8),
+            "short" | "int16" => Ok(ScalarType::Int16),
+            "ushort" | "uint16" => Ok(ScalarType::UInt16),
+            "int" | "int32" => Ok(ScalarType::Int32),
+            "uint" | "uint32" => Ok(ScalarType::UInt32),
+            "float" | "float32" => Ok(ScalarType::Float32),
+            "double" | "float64" => Ok(ScalarType::Float64),
+            _ => Err(malformed(format!("unknown type `{}`", name))),
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ScalarType::Int8 | ScalarType::UInt8 => 1,
+            ScalarType::Int16 | ScalarType::UInt16 => 2,
+            ScalarType::Int32 | ScalarType::UInt32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8,
+        }
+    }
+
+    /**
+     * The value integer colors are divided by to normalize them.
+     */
+    fn color_scale(self) -> f64 {
+        match self {
+            ScalarType::Int8 => i8::MAX as f64,
+            ScalarType::UInt8 => u8::MAX as f64,
+            ScalarType::Int16 => i16::MAX as f64,
+            ScalarType::UInt16 => u16::MAX as f64,
+            ScalarType::Int32 => i32::MAX as f64,
+            ScalarType::UInt32 => u32::MAX as f64,
+            ScalarType::Float32 | ScalarType::Float64 => 1.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum PropertyType {
+    Scalar(ScalarType),
+    List { count: ScalarType, item: ScalarType },
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+
+    kind: PropertyType,
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+
+    count: usize,
+
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn find(&self, names: &[&str]) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|property| names.contains(&property.name.as_str()))
+    }
+}
+
+#[derive(Debug)]
+enum Value {
+    Scalar(f64),
+    List(Vec<f64>),
+}
+
+impl Value {
+    fn scalar(&self) -> f64 {
+        match self {
+            Value::Scalar(value) => *value,
+            Value::List(values) => values.first().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+/**
+ * The data following the header.
+ */
+struct Body<'a> {
+    format: PlyFormat,
+
+    tokens: std::str::SplitAsciiWhitespace<'a>,
+
+    bytes: &'a [u8],
+}
+
+impl Body<'_> {
+    fn read(&mut self, ty: ScalarType) -> Result<f64, PlyError> {
+        if self.format == PlyFormat::Ascii {
+            let token = self
+                .tokens
+                .next()
+                .ok_or_else(|| malformed("unexpected end of data"))?;
+            return token
+                .parse()
+                .map_err(|_| malformed(format!("invalid number `{}`", token)));
+        }
+
+        if self.bytes.len() < ty.size() {
+            return Err(malformed("unexpected end of data"));
+        }
+        let (bytes, rest) = self.bytes.split_at(ty.size());
+        self.bytes = rest;
+        let big_endian = self.format == PlyFormat::BinaryBigEndian;
+        macro_rules! decode {
+            ($t:ty) => {{
+                let bytes = bytes.try_into().unwrap();
+                (if big_endian {
+                    <$t>::from_be_bytes(bytes)
+                } else {
+                    <$t>::from_le_bytes(bytes)
+                }) as f64
+            }};
+        }
+        Ok(match ty {
+            ScalarType::Int8 => decode!(i8),
+            ScalarType::UInt8 => decode!(u8),
+            ScalarType::Int16 => decode!(i16),
+            ScalarType::UInt16 => decode!(u16),
+            ScalarType::Int32 => decode!(i32),
+            ScalarType::UInt32 => decode!(u32),
+            ScalarType::Float32 => decode!(f32),
+            ScalarType::Float64 => decode!(f64),
+        })
+    }
+
+    fn read_row(&mut self, element: &Element) -> Result<Vec<Value>, PlyError> {
+        element
+            .properties
+            .iter()
+            .map(|property| match property.kind {
+                PropertyType::Scalar(ty) => Ok(Value::Scalar(self.read(ty)?)),
+                PropertyType::List { count, item } => {
+                    let count = self.read(count)?;
+                    if count < 0.0 {
+                        return Err(malformed(format!("negative list length {}", count)));
+                    }
+                    (0..count as usize)
+                        .map(|_| self.read(item))
+                        .collect::<Result<_, _>>()
+                        .map(Value::List)
+                }
+            })
+            .collect()
+    }
+}
+
+/**
+ * Read a PLY file in any of the three formats.
+ */
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Mesh, PlyError> {
+    read_from(BufReader::new(File::open(path)?))
+}
+
+/**
+ * Decode the `vertex` and `face` elements of a PLY file into a mesh of a single primitive,
+ * other elements are skipped. Polygons are triangulated as fans.
+ *
+ * Vertices may have normals (`nx`, `ny`, `nz`), colors (`red`, `green`, `blue` and
+ * optionally `alpha`, integers being normalized to their maximum) and texture
+ * coordinates (`s`, `t` or `u`, `v`), whose origin is moved to the top left.
+ */
+pub fn read_from<R: BufRead>(mut reader: R) -> Result<Mesh, PlyError> {
+    let mut line = String::new();
+    let mut next_line = |line: &mut String| -> Result<(), PlyError> {
+        line.clear();
+        if reader.read_line(line)? == 0 {
+            return Err(malformed("missing `end_header`"));
+        }
+        Ok(())
+    };
+
+    next_line(&mut line)?;
+    if line.trim_end() != "ply" {
+        return Err(malformed("missing `ply` magic"));
+    }
+
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    loop {
+        next_line(&mut line)?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            ["end_header"] => break,
+            ["format", name, _version] => {
+                format = Some(match name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Err(malformed(format!("unknown format `{}`", name))),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| malformed(format!("invalid element count `{}`", count)))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => elements
+                .last_mut()
+                .ok_or_else(|| malformed("property before element"))?
+                .properties
+                .push(Property {
+                    name: name.to_string(),
+                    kind: PropertyType::List {
+                        count: ScalarType::parse(count)?,
+                        item: ScalarType::parse(item)?,
+                    },
+                }),
+            ["property", ty, name] => elements
+                .last_mut()
+                .ok_or_else(|| malformed("property before element"))?
+                .properties
+                .push(Property {
+                    name: name.to_string(),
+                    kind: PropertyType::Scalar(ScalarType::parse(ty)?),
+                }),
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            _ => {
+                return Err(malformed(format!(
+                    "invalid header line `{}`",
+                    line.trim_end()
+                )))
+            }
+        }
+    }
+    let format = format.ok_or_else(|| malformed("missing format"))?;
+
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let text = match format {
+        PlyFormat::Ascii => {
+            std::str::from_utf8(&data).map_err(|_| malformed("ASCII data is not valid UTF-8"))?
+        }
+        _ => "",
+    };
+    let mut body = Body {
+        format,
+        tokens: text.split_ascii_whitespace(),
+        bytes: &data,
+    };
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for element in &elements {
+        match element.name.as_str() {
+            "vertex" => {
+                let property = |name: &str| element.find(&[name]);
+                let [x, y, z] = ["x", "y", "z"].map(property);
+                let (Some(x), Some(y), Some(z)) = (x, y, z) else {
+                    return Err(malformed("vertices without positions"));
+                };
+                let normal = ["nx", "ny", "nz"].map(property);
+                let color = ["red", "green", "blue"].map(property);
+                let alpha = property("alpha");
+                let s = element.find(&["s", "u", "texture_u"]);
+                let t = element.find(&["t", "v", "texture_v"]);
+                let scale = |index: usize| match element.properties[index].kind {
+                    PropertyType::Scalar(ty) => ty.color_scale(),
+                    PropertyType::List { .. } => 1.0,
+                };
+
+                vertices.reserve(element.count);
+                for _ in 0..element.count {
+                    let row = body.read_row(element)?;
+                    let get = |index: usize| row[index].scalar() as f32;
+                    let channel = |index: usize| (row[index].scalar() / scale(index)) as f32;
+                    let mut vertex = Vertex::new(Vector3::new(get(x), get(y), get(z)));
+                    if let [Some(nx), Some(ny), Some(nz)] = normal {
+                        vertex.normal = Some(Vector3::new(get(nx), get(ny), get(nz)));
+                    }
+                    if let [Some(r), Some(g), Some(b)] = color {
+                        let a = alpha.map_or(1.0, channel);
+                        vertex.color = Some(Color::new(channel(r), channel(g), channel(b), a));
+                    }
+                    if let (Some(s), Some(t)) = (s, t) {
+                        vertex.tex_coord = Some(Vector2::new(get(s), 1.0 - get(t)));
+                    }
+                    vertices.push(vertex);
+                }
+            }
+            "face" => {
+                let list = element
+                    .find(&["vertex_indices", "vertex_index"])
+                    .ok_or_else(|| malformed("faces without vertex indices"))?;
+                for _ in 0..element.count {
+                    let row = body.read_row(element)?;
+                    let Value::List(polygon) = &row[list] else {
+                        return Err(malformed("vertex indices are not a list"));
+                    };
+                    let polygon = polygon
+                        .iter()
+                        .map(|&index| {
+                            u32::try_from(index as i64)
+                                .ok()
+                                .filter(|&index| (index as usize) < vertices.len())
+                                .ok_or_else(|| malformed(format!("index {} out of range", index)))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    for i in 2..polygon.len() {
+                        indices.extend([polygon[0], polygon[i - 1], polygon[i]]);
+                    }
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    body.read_row(element)?;
+                }
+            }
+        }
+    }
+
+    Ok(Mesh {
+        name: None,
+        primitives: vec![Primitive {
+            vertices,
+            indices,
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        }],
+        weights: Vec::new(),
+    })
+}
+
+/**
+ * Write the vertices and triangles of every primitive of `mesh` to `path`.
+ */
+pub fn save<P: AsRef<Path>>(path: P, mesh: &Mesh, format: PlyFormat) -> Result<(), PlyError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_to(&mut writer, mesh, format)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/**
+ * Encode the vertices and triangles of every primitive of `mesh`. Normals, colors and
+ * texture coordinates are written when every vertex has them, colors as `uchar`.
+ */
+pub fn write_to<W: Write>(mut writer: W, mesh: &Mesh, format: PlyFormat) -> Result<(), PlyError> {
+    let vertices = || mesh.primitives.iter().flat_map(|p| &p.vertices);
+    let vertex_count = vertices().count();
+    let triangle_count: usize = mesh.primitives.iter().map(Primitive::triangle_count).sum();
+    let has_normals = vertex_count > 0 && vertices().all(|v| v.normal.is_some());
+    let has_colors = vertex_count > 0 && vertices().all(|v| v.color.is_some());
+    let has_tex_coords = vertex_count > 0 && vertices().all(|v| v.tex_coord.is_some());
+
+    let format_name = match format {
+        PlyFormat::Ascii => "ascii",
+        PlyFormat::BinaryLittleEndian => "binary_little_endian",
+        PlyFormat::BinaryBigEndian => "binary_big_endian",
+    };
+    writeln!(writer, "ply\nformat {} 1.0", format_name)?;
+    writeln!(writer, "element vertex {}", vertex_count)?;
+    writeln!(
+        writer,
+        "property float x\nproperty float y\nproperty float z"
+    )?;
+    if has_normals {
+        writeln!(
+            writer,
+            "property float nx\nproperty float ny\nproperty float nz"
+        )?;
+    }
+    if has_colors {
+        writeln!(
+            writer,
+            "property uchar red\nproperty uchar green\nproperty uchar blue\nproperty uchar alpha"
+        )?;
+    }
+    if has_tex_coords {
+        writeln!(writer, "property float s\nproperty float t")?;
+    }
+    writeln!(writer, "element face {}", triangle_count)?;
+    writeln!(writer, "property list uchar int vertex_indices\nend_header")?;
+
+    let mut row = Row {
+        writer: &mut writer,
+        format,
+        first: true,
+    };
+    for vertex in vertices() {
+        for value in vertex.position.xyz().iter() {
+            row.float(*value)?;
+        }
+        if let Some(normal) = vertex.normal.filter(|_| has_normals) {
+            for value in normal.iter() {
+                row.float(*value)?;
+            }
+        }
+        if let Some(color) = vertex.color.filter(|_| has_colors) {
+            for value in [color.r, color.g, color.b, color.a] {
+                row.byte((value.clamp(0.0, 1.0) * 255.0).round() as u8)?;
+            }
+        }
+        if let Some(tex_coord) = vertex.tex_coord.filter(|_| has_tex_coords) {
+            row.float(tex_coord.x)?;
+            row.float(1.0 - tex_coord.y)?;
+        }
+        row.end()?;
+    }
+
+    let mut offset = 0;
+    for primitive in &mesh.primitives {
+        for triangle in primitive.indices.chunks_exact(3) {
+            row.byte(3)?;
+            for index in triangle {
+                row.int((offset + index) as i32)?;
+            }
+            row.end()?;
+        }
+        offset += primitive.vertices.len() as u32;
+    }
+    Ok(())
+}
+
+/**
+ * Writes the values of a row in the chosen format.
+ */
+struct Row<'a, W: Write> {
+    writer: &'a mut W,
+
+    format: PlyFormat,
+
+    /**
+     * Whether no value was written on the current ASCII line yet.
+     */
+    first: bool,
+}
+
+impl<W: Write> Row<'_, W> {
+    fn ascii(&mut self, value: impl fmt::Display) -> io::Result<()> {
+        if !self.first {
+            write!(self.writer, " ")?;
+        }
+        self.first = false;
+        write!(self.writer, "{}", value)
+    }
+
+    fn bytes<const N: usize>(&mut self, le: [u8; N], be: [u8; N]) -> io::Result<()> {
+        match self.format {
+            PlyFormat::BinaryBigEndian => self.writer.write_all(&be),
+            _ => self.writer.write_all(&le),
+        }
+    }
+
+    fn float(&mut self, value: f32) -> io::Result<()> {
+        match self.format {
+            PlyFormat::Ascii => self.ascii(value),
+            _ => self.bytes(value.to_le_bytes(), value.to_be_bytes()),
+        }
+    }
+
+    fn int(&mut self, value: i32) -> io::Result<()> {
+        match self.format {
+            PlyFormat::Ascii => self.ascii(value),
+            _ => self.bytes(value.to_le_bytes(), value.to_be_bytes()),
+        }
+    }
+
+    fn byte(&mut self, value: u8) -> io::Result<()> {
+        match self.format {
+            PlyFormat::Ascii => self.ascii(value),
+            _ => self.writer.write_all(&[value]),
+        }
+    }
+
+    fn end(&mut self) -> io::Result<()> {
+        self.first = true;
+        match self.format {
+            PlyFormat::Ascii => writeln!(self.writer),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_ascii() {
+        let source = "\
+ply
+format ascii 1.0
+comment a colored quad
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+element edge 1
+property int vertex1
+property int vertex2
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+0 1
+";
+        let mesh = read_from(source.as_bytes()).unwrap();
+        let primitive = &mesh.primitives[0];
+        assert_eq!(primitive.vertices.len(), 4);
+        assert_eq!(primitive.indices, vec![0, 1, 2, 0, 2, 3]);
+        let color = primitive.vertices[1].color.unwrap();
+        assert_eq!([color.r, color.g, color.b, color.a], [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(
+            primitive.vertices[2].position.xyz(),
+            Vector3::new(1.0, 1.0, 0.0)
+        );
+        assert_eq!(primitive.vertices[0].normal, None);
+
+        let truncated = source.replace("4 0 1 2 3\n0 1\n", "4 0 1 2\n");
+        assert!(matches!(
+            read_from(truncated.as_bytes()),
+            Err(PlyError::Malformed(_))
+        ));
+        let out_of_range = source.replace("4 0 1 2 3", "4 0 1 2 4");
+        assert!(matches!(
+            read_from(out_of_range.as_bytes()),
+            Err(PlyError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let vertex = |x: f32, y: f32, color: Color| Vertex {
+            normal: Some(Vector3::z()),
+            color: Some(color),
+            tex_coord: Some(Vector2::new(x, 1.0 - y)),
+            ..Vertex::new(Vector3::new(x, y, 0.5))
+        };
+        let triangle = |offset: f32| Primitive {
+            vertices: vec![
+                vertex(offset, 0.0, Color::new(1.0, 0.0, 0.0, 1.0)),
+                vertex(offset + 1.0, 0.0, Color::new(0.0, 1.0, 0.0, 1.0)),
+                vertex(offset, 1.0, Color::new(0.0, 0.0, 1.0, 0.0)),
+            ],
+            indices: vec![0, 1, 2],
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        };
+        let mesh = Mesh {
+            name: None,
+            primitives: vec![triangle(0.0), triangle(2.0)],
+            weights: Vec::new(),
+        };
+
+        for format in [
+            PlyFormat::Ascii,
+            PlyFormat::BinaryLittleEndian,
+            PlyFormat::BinaryBigEndian,
+        ] {
+            let mut bytes = Vec::new();
+            write_to(&mut bytes, &mesh, format).unwrap();
+            let read = read_from(bytes.as_slice()).unwrap();
+            let primitive = &read.primitives[0];
+            // Primitives are merged, with their indices offset.
+            assert_eq!(primitive.indices, vec![0, 1, 2, 3, 4, 5]);
+            let expected = mesh.primitives.iter().flat_map(|p| &p.vertices);
+            for (vertex, expected) in primitive.vertices.iter().zip(expected) {
+                assert_eq!(vertex.position, expected.position);
+                assert_eq!(vertex.normal, expected.normal);
+                assert_eq!(vertex.tex_coord, expected.tex_coord);
+                let [color, expected] = [vertex.color, expected.color].map(|color| {
+                    let color = color.unwrap();
+                    [color.r, color.g, color.b, color.a]
+                });
+                assert_eq!(color, expected);
+            }
+        }
+    }
+}
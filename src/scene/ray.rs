@@ -0,0 +1,237 @@
+use crate::{
+    common::basetype::Bbox3,
+    rasterizer::shader::Shader,
+    scene::bvh::{TriangleBvh, TriangleHit},
+};
+use interpolate::Interpolate;
+use nalgebra::{Matrix4, Point3, Vector3};
+
+/**
+ * A half-line from `origin` along `direction`, which need not be normalized;
+ * distances along the ray are in units of its length.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Point3<f32>, direction: Vector3<f32>) -> Self {
+        Self { origin, direction }
+    }
+
+    pub fn at(&self, t: f32) -> Point3<f32> {
+        self.origin + self.direction * t
+    }
+
+    /**
+     * The ray in the space `transform` maps to. Distances along the ray are unchanged
+     * by affine transforms, since the direction is transformed too.
+     */
+    pub fn transform(&self, transform: &Matrix4<f32>) -> Self {
+        Self {
+            origin: transform.transform_point(&self.origin),
+            direction: transform.transform_vector(&self.direction),
+        }
+    }
+
+    /**
+     * Slab test, the distances at which the ray enters and leaves `bounds`,
+     * with the entry clamped to the origin.
+     */
+    pub fn intersect_bbox(&self, bounds: &Bbox3<f32>) -> Option<(f32, f32)> {
+        let slabs = [
+            (bounds.l, bounds.r),
+            (bounds.b, bounds.t),
+            (bounds.n, bounds.f),
+        ];
+        let mut near = 0.0f32;
+        let mut far = f32::INFINITY;
+        for (axis, (low, high)) in slabs.into_iter().enumerate() {
+            let (origin, direction) = (self.origin[axis], self.direction[axis]);
+            if direction == 0.0 {
+                // Parallel to the slab, which would divide zero by zero on its faces.
+                if origin < low || origin > high {
+                    return None;
+                }
+                continue;
+            }
+            let t_0 = (low - origin) / direction;
+            let t_1 = (high - origin) / direction;
+            near = near.max(t_0.min(t_1));
+            far = far.min(t_0.max(t_1));
+        }
+        (near <= far).then_some((near, far))
+    }
+
+    /**
+     * Möller–Trumbore intersection with a triangle facing either way, the distance
+     * and the barycentric coordinate of the hit, weighting `p_0`, `p_1` and `p_2`.
+     */
+    pub fn intersect_triangle(
+        &self,
+        p_0: &Vector3<f32>,
+        p_1: &Vector3<f32>,
+        p_2: &Vector3<f32>,
+    ) -> Option<(f32, Vector3<f32>)> {
+        let e_1 = p_1 - p_0;
+        let e_2 = p_2 - p_0;
+        let p = self.direction.cross(&e_2);
+        let det = e_1.dot(&p);
+        if det.abs() <= f32::EPSILON * e_1.cross(&e_2).norm() * self.direction.norm() {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+        let s = self.origin.coords - p_0;
+        let u = s.dot(&p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(&e_1);
+        let v = self.direction.dot(&q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = e_2.dot(&q) * inv_det;
+        (t >= 0.0).then(|| (t, Vector3::new(1.0 - u - v, u, v)))
+    }
+}
+
+/**
+ * A traced hit with the varyings of the vertex shader interpolated at it.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct TraceHit<V: Interpolate> {
+    pub hit: TriangleHit,
+
+    pub varying: V,
+}
+
+/**
+ * The nearest triangle of `triangles` the ray hits before `max_t`, with the varyings
+ * `shader` outputs for its corners blended by the barycentric coordinate of the hit.
+ * The shader is indexed like in `RenderPass::draw`, corner `k` of triangle `i` being
+ * `3 * i + k`, so the programs of the rasterizer serve reflection and shadow rays too.
+ */
+pub fn trace<S: Shader>(
+    ray: &Ray,
+    triangles: &TriangleBvh,
+    shader: &S,
+    max_t: f32,
+) -> Option<TraceHit<S::Varying>> {
+    let hit = triangles.nearest_hit(ray, max_t)?;
+    let [v_0, v_1, v_2] = [0, 1, 2].map(|k| shader.vertex_shader(3 * hit.triangle + k).varying);
+    Some(TraceHit {
+        hit,
+        varying: S::Varying::barycentric_interpolate(&v_0, &v_1, &v_2, &hit.bary_coord),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::color::Color,
+        rasterizer::{shader::VsOutput, triangle::compute_barycentric_coordinate},
+        scene::mesh::{Primitive, Vertex},
+    };
+
+    #[test]
+    fn test_intersect_bbox() {
+        let bounds = Bbox3::new(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0);
+        let ray = Ray::new(Point3::new(-3.0, 0.5, 0.0), Vector3::new(2.0, 0.0, 0.0));
+        assert_eq!(ray.intersect_bbox(&bounds), Some((1.0, 2.0)));
+
+        // Starting inside, the entry is the origin.
+        let inside = Ray::new(Point3::origin(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(inside.intersect_bbox(&bounds), Some((0.0, 1.0)));
+
+        let away = Ray::new(Point3::new(-3.0, 0.0, 0.0), -Vector3::x());
+        assert_eq!(away.intersect_bbox(&bounds), None);
+        let beside = Ray::new(Point3::new(-3.0, 1.5, 0.0), Vector3::x());
+        assert_eq!(beside.intersect_bbox(&bounds), None);
+        // Along a face.
+        let grazing = Ray::new(Point3::new(-3.0, 1.0, 0.0), Vector3::x());
+        assert_eq!(grazing.intersect_bbox(&bounds), Some((2.0, 4.0)));
+    }
+
+    #[test]
+    fn test_intersect_triangle() {
+        let [p_0, p_1, p_2] = [
+            Vector3::new(0.0, 0.0, -2.0),
+            Vector3::new(4.0, 0.0, -2.0),
+            Vector3::new(0.0, 2.0, -2.0),
+        ];
+        let ray = Ray::new(Point3::new(1.0, 0.5, 0.0), Vector3::new(0.0, 0.0, -0.5));
+        let (t, bary_coord) = ray.intersect_triangle(&p_0, &p_1, &p_2).unwrap();
+        assert!((t - 4.0).abs() < 1e-6);
+        // Same weights as the rasterizer computes in the plane of the triangle.
+        let expected =
+            compute_barycentric_coordinate(&p_0.xy(), &p_1.xy(), &p_2.xy(), &ray.at(t).coords.xy());
+        assert!((bary_coord - expected).norm() < 1e-6);
+        assert!((bary_coord - Vector3::new(0.5, 0.25, 0.25)).norm() < 1e-6);
+
+        // Either facing, but not behind the origin, outside or parallel.
+        assert!(ray.intersect_triangle(&p_0, &p_2, &p_1).is_some());
+        let behind = Ray::new(Point3::new(1.0, 0.5, -3.0), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(behind.intersect_triangle(&p_0, &p_1, &p_2), None);
+        let outside = Ray::new(Point3::new(3.0, 1.5, 0.0), -Vector3::z());
+        assert_eq!(outside.intersect_triangle(&p_0, &p_1, &p_2), None);
+        let parallel = Ray::new(Point3::new(-1.0, 0.5, -2.0), Vector3::x());
+        assert_eq!(parallel.intersect_triangle(&p_0, &p_1, &p_2), None);
+    }
+
+    struct ColorProgram<'a> {
+        primitive: &'a Primitive,
+    }
+
+    impl<'a> Shader for ColorProgram<'a> {
+        type Varying = Color;
+
+        fn vertex_shader(&self, index: usize) -> VsOutput<Self::Varying> {
+            let vertex = self.primitive.vertex(index);
+            VsOutput {
+                position: vertex.position,
+                varying: vertex.color.unwrap(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        // A red, green and blue corner, with indices that do not follow the vertices.
+        let corner = |x: f32, y: f32, color: Color| Vertex {
+            color: Some(color),
+            ..Vertex::new(Vector3::new(x, y, 0.0))
+        };
+        let primitive = Primitive {
+            vertices: vec![
+                corner(0.0, 2.0, Color::BLUE),
+                corner(0.0, 0.0, Color::RED),
+                corner(2.0, 0.0, Color::GREEN),
+            ],
+            indices: vec![1, 2, 0],
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        };
+        let triangles = TriangleBvh::new(&primitive);
+        let program = ColorProgram {
+            primitive: &primitive,
+        };
+
+        let ray = Ray::new(Point3::new(0.5, 0.5, 1.0), -Vector3::z());
+        let traced = trace(&ray, &triangles, &program, f32::INFINITY).unwrap();
+        assert_eq!(traced.hit.triangle, 0);
+        assert!((traced.hit.t - 1.0).abs() < 1e-6);
+        let Color { r, g, b, a } = traced.varying;
+        assert!((Vector3::new(r, g, b) - Vector3::new(0.5, 0.25, 0.25)).norm() < 1e-6);
+        assert!((a - 1.0).abs() < 1e-6);
+
+        // A shadow ray stopping short of the triangle.
+        assert!(trace(&ray, &triangles, &program, 0.5).is_none());
+    }
+}
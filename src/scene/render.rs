@@ -0,0 +1,387 @@
+use crate::{
+    common::basetype::Bbox3,
+    rasterizer::{
+        pass::RenderPass,
+        pipeline::{CullMode, DepthCompare, Pipeline},
+        shader::Shader,
+    },
+    scene::{frustum::Frustum, geometry::transform_bounds, mesh::Primitive, node::Instance, Scene},
+};
+use nalgebra::Vector3;
+
+/**
+ * How many instances and primitives `draw_scene_culled` considered and skipped.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CullStats {
+    pub instances: usize,
+
+    pub culled_instances: usize,
+
+    pub primitives: usize,
+
+    pub culled_primitives: usize,
+}
+
+/**
+ * Draw every primitive of every instance in `graph` (the default graph when `None`),
+ * with the shader created by `make_shader` for the instance and primitive.
+ * `reversed_z` tells whether the shaders project through a reversed Z view, usually
+ * `View::reversed_z`. It sets `RenderPass::reversed_z`, and the nearest fragments are
+ * kept with `DepthCompare::Greater` when set or `DepthCompare::Less` otherwise.
+ * The object id of an instance is its index in `Scene::instances`.
+ */
+pub fn draw_scene<'a, S, F>(
+    pass: &mut RenderPass,
+    scene: &'a Scene,
+    graph: Option<usize>,
+    reversed_z: bool,
+    make_shader: F,
+) where
+    S: Shader,
+    S::Varying: std::fmt::Debug,
+    F: FnMut(Instance, &'a Primitive) -> S,
+{
+    draw_instances(pass, scene, graph, reversed_z, None, make_shader);
+}
+
+/**
+ * Like `draw_scene`, but skip instances and primitives whose world bounds lie outside
+ * `frustum` before creating their shader. Skinned instances and primitives with morph
+ * targets may move past their bounds and are always drawn.
+ */
+pub fn draw_scene_culled<'a, S, F>(
+    pass: &mut RenderPass,
+    scene: &'a Scene,
+    graph: Option<usize>,
+    reversed_z: bool,
+    frustum: &Frustum,
+    make_shader: F,
+) -> CullStats
+where
+    S: Shader,
+    S::Varying: std::fmt::Debug,
+    F: FnMut(Instance, &'a Primitive) -> S,
+{
+    draw_instances(pass, scene, graph, reversed_z, Some(frustum), make_shader)
+}
+
+fn draw_instances<'a, S, F>(
+    pass: &mut RenderPass,
+    scene: &'a Scene,
+    graph: Option<usize>,
+    reversed_z: bool,
+    frustum: Option<&Frustum>,
+    mut make_shader: F,
+) -> CullStats
+where
+    S: Shader,
+    S::Varying: std::fmt::Debug,
+    F: FnMut(Instance, &'a Primitive) -> S,
+{
+    let mut stats = CullStats::default();
+    pass.reversed_z = reversed_z;
+    let depth_compare = if reversed_z {
+        DepthCompare::Greater
+    } else {
+        DepthCompare::Less
+    };
+    // Local bounds of each primitive, computed once for all instances of a mesh.
+    let mut bounds: Vec<Option<Vec<Option<Bbox3<f32>>>>> = vec![None; scene.meshes.len()];
+
+    for (object_id, instance) in scene.instances(graph).into_iter().enumerate() {
+        let primitives = &scene.meshes[instance.mesh].primitives;
+        stats.instances += 1;
+        stats.primitives += primitives.len();
+
+        // Deformed vertices may leave the bounds of their bind pose.
+        let skinned = instance
+            .node
+            .is_some_and(|node| scene.nodes[node].skin.is_some());
+        let outside = |local: &Bbox3<f32>| {
+            frustum.is_some_and(|frustum| {
+                !frustum.intersects_bbox(&transform_bounds(local, &instance.world))
+            })
+        };
+
+        let local = bounds[instance.mesh]
+            .get_or_insert_with(|| primitives.iter().map(Primitive::bounds).collect());
+        let rigid = !skinned
+            && primitives
+                .iter()
+                .all(|primitive| primitive.targets.is_empty());
+        if rigid && primitives.len() > 1 {
+            let corners: Vec<Vector3<f32>> =
+                local.iter().flatten().flat_map(Bbox3::corners).collect();
+            if !corners.is_empty() && outside(&Bbox3::from_vector3(&corners.iter().collect())) {
+                stats.culled_instances += 1;
+                stats.culled_primitives += primitives.len();
+                continue;
+            }
+        }
+
+        let mut drawn = 0;
+        for (primitive, local) in primitives.iter().zip(local.iter()) {
+            let deformed = skinned || !primitive.targets.is_empty();
+            if !deformed && local.as_ref().is_some_and(outside) {
+                stats.culled_primitives += 1;
+                continue;
+            }
+            drawn += 1;
+            let shader = make_shader(instance, primitive);
+            let mut pipeline = Pipeline {
+                program: &shader,
+                cull_mode: CullMode::None,
+                depth_write_enable: true,
+                depth_compare,
+            };
+            pass.draw_object(&mut pipeline, primitive.vertex_count(), object_id as u32);
+        }
+        if drawn == 0 && !primitives.is_empty() {
+            stats.culled_instances += 1;
+        }
+    }
+
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::{basetype::Viewport, color::Color},
+        rasterizer::shader::{FsPayload, VsOutput},
+        scene::{
+            camera::{Projection, View},
+            mesh::{Mesh, Vertex},
+            node::{Node, SceneGraph, Transform},
+        },
+    };
+    use nalgebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3};
+
+    struct FlatProgram<'a> {
+        primitive: &'a Primitive,
+
+        world: Matrix4<f32>,
+    }
+
+    impl<'a> Shader for FlatProgram<'a> {
+        type Varying = ();
+
+        fn vertex_shader(&self, index: usize) -> VsOutput<Self::Varying> {
+            VsOutput {
+                position: self.world * self.primitive.vertex(index).position,
+                varying: (),
+            }
+        }
+
+        fn fragment_shader(&self, _: FsPayload<Self::Varying>) -> Color {
+            Color::RED
+        }
+    }
+
+    fn translation(x: f32, y: f32) -> Transform {
+        Transform::Trs {
+            translation: Vector3::new(x, y, 0.0),
+            rotation: UnitQuaternion::identity(),
+            scale: Vector3::repeat(1.0),
+        }
+    }
+
+    fn node(transform: Transform, mesh: Option<usize>, children: Vec<usize>) -> Node {
+        Node {
+            name: None,
+            transform,
+            mesh,
+            camera: None,
+            light: None,
+            skin: None,
+            weights: None,
+            children,
+        }
+    }
+
+    /**
+     * A small triangle around the origin, placed by `nodes` under the root node 0.
+     */
+    fn triangle_scene(nodes: Vec<Node>) -> Scene {
+        let triangle = Primitive {
+            vertices: vec![
+                Vertex::new(Vector3::new(-0.1, -0.1, 0.0)),
+                Vertex::new(Vector3::new(0.1, -0.1, 0.0)),
+                Vertex::new(Vector3::new(0.0, 0.1, 0.0)),
+            ],
+            indices: vec![0, 1, 2],
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        };
+        Scene {
+            meshes: vec![Mesh {
+                name: None,
+                primitives: vec![triangle],
+                weights: Vec::new(),
+            }],
+            textures: Vec::new(),
+            materials: Vec::new(),
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            skins: Vec::new(),
+            animations: Vec::new(),
+            nodes,
+            graphs: vec![SceneGraph {
+                name: None,
+                roots: vec![0],
+            }],
+            default_graph: Some(0),
+        }
+    }
+
+    #[test]
+    fn test_draw_instanced_mesh() {
+        // Placed twice through a parent node.
+        let scene = triangle_scene(vec![
+            node(translation(0.0, 0.5), None, vec![1, 2]),
+            node(translation(-0.5, 0.0), Some(0), Vec::new()),
+            node(translation(0.5, 0.0), Some(0), Vec::new()),
+        ]);
+        assert_eq!(scene.instances(None).len(), 2);
+
+        let mut pass = RenderPass::new(Viewport::new(40, 40));
+        pass.clear();
+        draw_scene(&mut pass, &scene, None, false, |instance, primitive| {
+            FlatProgram {
+                primitive,
+                world: instance.world,
+            }
+        });
+
+        // Clip space (-0.5, 0.5) and (0.5, 0.5), the texture's first row is the top.
+        let texel = |x, y| pass.frame_texture.get_texel(&Vector2::new(x, y)).g;
+        assert_eq!(texel(10, 10), 0.0);
+        assert_eq!(texel(30, 10), 0.0);
+        assert_eq!(texel(20, 10), 1.0);
+        assert_eq!(texel(10, 30), 1.0);
+    }
+
+    #[test]
+    fn test_draw_culled() {
+        let scene = triangle_scene(vec![
+            node(Transform::Matrix(Matrix4::identity()), None, vec![1, 2, 3]),
+            node(translation(0.0, 0.0), Some(0), Vec::new()),
+            node(translation(3.0, 0.0), Some(0), Vec::new()),
+            node(translation(0.0, -1.05), Some(0), Vec::new()),
+        ]);
+        // The clip volume itself, the second instance is far right and the third
+        // straddles the bottom edge.
+        let frustum = Frustum::from_matrix(&Matrix4::identity());
+
+        let mut pass = RenderPass::new(Viewport::new(40, 40));
+        pass.clear();
+        let mut shaded = Vec::new();
+        let stats = draw_scene_culled(
+            &mut pass,
+            &scene,
+            None,
+            false,
+            &frustum,
+            |instance, primitive| {
+                shaded.push(instance.node);
+                FlatProgram {
+                    primitive,
+                    world: instance.world,
+                }
+            },
+        );
+
+        assert_eq!(shaded, [Some(1), Some(3)]);
+        assert_eq!(
+            stats,
+            CullStats {
+                instances: 3,
+                culled_instances: 1,
+                primitives: 3,
+                culled_primitives: 1,
+            }
+        );
+        assert_eq!(pass.frame_texture.get_texel(&Vector2::new(20, 20)).g, 0.0);
+    }
+
+    #[test]
+    fn test_pick() {
+        let scene = triangle_scene(vec![
+            node(translation(0.0, 0.0), None, vec![1, 2]),
+            node(translation(-0.5, 0.0), Some(0), Vec::new()),
+            node(translation(0.5, 0.0), Some(0), Vec::new()),
+        ]);
+        let mut pass = RenderPass::new(Viewport::new(40, 40)).with_id_texture();
+        pass.clear();
+        draw_scene(&mut pass, &scene, None, false, |instance, primitive| {
+            FlatProgram {
+                primitive,
+                world: instance.world,
+            }
+        });
+
+        assert_eq!(pass.pick(10, 20), Some((0, 0, 0.0)));
+        assert_eq!(pass.pick(30, 20), Some((1, 0, 0.0)));
+        assert_eq!(pass.pick(20, 20), None);
+        assert_eq!(pass.pick(40, 0), None);
+
+        pass.clear();
+        assert_eq!(pass.pick(10, 20), None);
+        assert_eq!(RenderPass::new(Viewport::new(4, 4)).pick(0, 0), None);
+    }
+
+    #[test]
+    fn test_draw_reversed_z() {
+        // The same triangle ahead of the eye at two distances, scaled to the same size
+        // on screen, the near one drawn first.
+        let at = |distance: f32| Transform::Trs {
+            translation: Vector3::new(0.0, 0.0, -distance),
+            rotation: UnitQuaternion::identity(),
+            scale: Vector3::repeat(distance),
+        };
+        let scene = triangle_scene(vec![
+            node(Transform::identity(), None, vec![1, 2]),
+            node(at(1.0), Some(0), Vec::new()),
+            node(at(3.0), Some(0), Vec::new()),
+        ]);
+        let view = View::look_at(
+            Point3::origin(),
+            Point3::new(0.0, 0.0, -1.0),
+            Vector3::y(),
+            &Projection::Perspective {
+                yfov: std::f32::consts::FRAC_PI_2,
+                aspect_ratio: None,
+                znear: 0.5,
+                zfar: Some(10.0),
+            },
+            1.0,
+        );
+
+        let front = |view: View| {
+            let mut pass = RenderPass::new(Viewport::new(40, 40)).with_id_texture();
+            pass.clear();
+            let view_projection = view.view_projection();
+            draw_scene(
+                &mut pass,
+                &scene,
+                None,
+                view.reversed_z,
+                |instance, primitive| FlatProgram {
+                    primitive,
+                    world: view_projection * instance.world,
+                },
+            );
+            assert_eq!(pass.reversed_z, view.reversed_z);
+            pass.pick(20, 20).map(|(object, _, depth)| (object, depth))
+        };
+
+        let (object, depth) = front(view).unwrap();
+        assert_eq!(object, 0);
+        let (reversed_object, reversed_depth) = front(view.with_reversed_z()).unwrap();
+        assert_eq!(reversed_object, 0);
+        assert!((reversed_depth - (1.0 - depth) / 2.0).abs() < 1e-5);
+    }
+}
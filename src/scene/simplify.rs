@@ -0,0 +1,713 @@
+use crate::{
+    common::basetype::{Bbox3, Viewport},
+    scene::mesh::{Mesh, Primitive},
+};
+use nalgebra::{Matrix4, Vector3};
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+/**
+ * Weight of the planes keeping borders and attribute seams in place, relative to the
+ * planes of the faces.
+ */
+const FEATURE_WEIGHT: f64 = 10.0;
+
+/**
+ * Smallest cosine between the normals of a triangle before and after a collapse,
+ * rejecting collapses that flip or fold triangles.
+ */
+const MIN_NORMAL_COSINE: f64 = 0.25;
+
+/**
+ * Sum of squared distances to a set of weighted planes, `p^T A p` for homogeneous points.
+ */
+#[derive(Debug, Clone, Copy)]
+struct Quadric {
+    matrix: Matrix4<f64>,
+
+    /**
+     * Total area of the face planes, to turn the sum into a mean.
+     */
+    area: f64,
+}
+
+impl Quadric {
+    fn zero() -> Self {
+        Self {
+            matrix: Matrix4::zeros(),
+            area: 0.0,
+        }
+    }
+
+    fn plane(normal: &Vector3<f64>, point: &Vector3<f64>, weight: f64) -> Matrix4<f64> {
+        let plane = normal.push(-normal.dot(point));
+        plane * plane.transpose() * weight
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        self.matrix += other.matrix;
+        self.area += other.area;
+    }
+
+    /**
+     * Mean squared distance of `point` to the planes.
+     */
+    fn error(&self, point: &Vector3<f64>) -> f64 {
+        let p = point.push(1.0);
+        let error = (p.transpose() * self.matrix * p)[0].max(0.0);
+        if self.area > 0.0 {
+            error / self.area
+        } else {
+            error
+        }
+    }
+}
+
+/**
+ * A half-edge collapse moving position `from` onto position `to`.
+ */
+#[derive(Debug)]
+struct Candidate {
+    cost: f64,
+
+    from: usize,
+
+    to: usize,
+
+    /**
+     * Versions of both positions when the cost was computed.
+     */
+    versions: (u32, u32),
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    /**
+     * Reversed, so the max-heap pops the cheapest collapse first.
+     */
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/**
+ * Simplification state. Vertices of the primitive are wedges: vertices sharing a
+ * position but split by their attributes. Collapses work on positions and move every
+ * wedge of the removed position onto a wedge of the kept one.
+ */
+struct Simplifier {
+    /**
+     * The position of each wedge.
+     */
+    wedge_positions: Vec<usize>,
+
+    positions: Vec<Vector3<f64>>,
+
+    /**
+     * Wedges of each triangle.
+     */
+    triangles: Vec<[u32; 3]>,
+
+    alive: Vec<bool>,
+
+    alive_count: usize,
+
+    /**
+     * Triangles around each position, including dead ones.
+     */
+    around: Vec<Vec<usize>>,
+
+    quadrics: Vec<Quadric>,
+
+    removed: Vec<bool>,
+
+    versions: Vec<u32>,
+}
+
+impl Simplifier {
+    fn new(primitive: &Primitive) -> Self {
+        let mut lookup = HashMap::new();
+        let mut positions = Vec::new();
+        let wedge_positions: Vec<usize> = primitive
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let key: [u32; 3] = vertex.position.xyz().map(f32::to_bits).into();
+                *lookup.entry(key).or_insert_with(|| {
+                    positions.push(vertex.position.xyz().cast::<f64>());
+                    positions.len() - 1
+                })
+            })
+            .collect();
+
+        // Triangles collapsed to a line in position are dropped up front.
+        let triangles: Vec<[u32; 3]> = primitive
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .filter(|triangle| {
+                let [a, b, c] = triangle.map(|wedge| wedge_positions[wedge as usize]);
+                a != b && b != c && c != a
+            })
+            .collect();
+
+        let mut around = vec![Vec::new(); positions.len()];
+        for (t, triangle) in triangles.iter().enumerate() {
+            for &wedge in triangle {
+                around[wedge_positions[wedge as usize]].push(t);
+            }
+        }
+
+        let mut simplifier = Self {
+            alive: vec![true; triangles.len()],
+            alive_count: triangles.len(),
+            quadrics: vec![Quadric::zero(); positions.len()],
+            removed: vec![false; positions.len()],
+            versions: vec![0; positions.len()],
+            wedge_positions,
+            positions,
+            triangles,
+            around,
+        };
+        simplifier.init_quadrics();
+        simplifier
+    }
+
+    fn corners(&self, triangle: usize) -> [usize; 3] {
+        self.triangles[triangle].map(|wedge| self.wedge_positions[wedge as usize])
+    }
+
+    /**
+     * Unnormalized normal, twice the area long.
+     */
+    fn normal(&self, [a, b, c]: [usize; 3]) -> Vector3<f64> {
+        let [a, b, c] = [a, b, c].map(|p| self.positions[p]);
+        (b - a).cross(&(c - a))
+    }
+
+    fn live_around(&self, position: usize) -> impl Iterator<Item = usize> + '_ {
+        self.around[position]
+            .iter()
+            .copied()
+            .filter(|&t| self.alive[t])
+    }
+
+    fn neighbors(&self, position: usize) -> Vec<usize> {
+        let mut neighbors: Vec<usize> = self
+            .live_around(position)
+            .flat_map(|t| self.corners(t))
+            .filter(|&p| p != position)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+
+    /**
+     * Whether the edge between two positions is a border, a seam where the wedges of
+     * the triangles on either side differ, or shared by more than two triangles.
+     */
+    fn is_feature(&self, p: usize, q: usize) -> bool {
+        let wedges: Vec<[u32; 2]> = self
+            .live_around(p)
+            .filter_map(|t| {
+                let corners = self.corners(t);
+                let i = corners.iter().position(|&c| c == p)?;
+                let j = corners.iter().position(|&c| c == q)?;
+                Some([self.triangles[t][i], self.triangles[t][j]])
+            })
+            .collect();
+        wedges.len() != 2 || wedges[0] != wedges[1]
+    }
+
+    fn init_quadrics(&mut self) {
+        for t in 0..self.triangles.len() {
+            let corners = self.corners(t);
+            let normal = self.normal(corners);
+            let Some(unit) = normal.try_normalize(0.0) else {
+                continue;
+            };
+            let area = normal.norm() / 2.0;
+            let face = Quadric {
+                matrix: Quadric::plane(&unit, &self.positions[corners[0]], area),
+                area,
+            };
+            for &p in &corners {
+                self.quadrics[p].add(&face);
+            }
+
+            // Planes through feature edges, perpendicular to the face, pin them in place.
+            for i in 0..3 {
+                let (p, q) = (corners[i], corners[(i + 1) % 3]);
+                if !self.is_feature(p, q) {
+                    continue;
+                }
+                let edge = self.positions[q] - self.positions[p];
+                let Some(side) = edge.cross(&unit).try_normalize(0.0) else {
+                    continue;
+                };
+                let weight = edge.norm_squared() * FEATURE_WEIGHT;
+                let matrix = Quadric::plane(&side, &self.positions[p], weight);
+                for end in [p, q] {
+                    self.quadrics[end].matrix += matrix;
+                }
+            }
+        }
+    }
+
+    fn cost(&self, from: usize, to: usize) -> f64 {
+        let mut quadric = self.quadrics[from];
+        quadric.add(&self.quadrics[to]);
+        quadric.error(&self.positions[to])
+    }
+
+    fn push_candidates(&self, heap: &mut BinaryHeap<Candidate>, position: usize) {
+        for neighbor in self.neighbors(position) {
+            for (from, to) in [(position, neighbor), (neighbor, position)] {
+                heap.push(Candidate {
+                    cost: self.cost(from, to),
+                    from,
+                    to,
+                    versions: (self.versions[from], self.versions[to]),
+                });
+            }
+        }
+    }
+
+    /**
+     * The wedge of `to` replacing each wedge of `from`, or `None` when the collapse
+     * would tear a seam, move a border or seam sideways, make the mesh non-manifold
+     * or fold a triangle over.
+     */
+    fn collapse_wedges(&self, from: usize, to: usize) -> Option<HashMap<u32, u32>> {
+        let shared: Vec<usize> = self
+            .live_around(from)
+            .filter(|&t| self.corners(t).contains(&to))
+            .collect();
+        if shared.is_empty() {
+            return None;
+        }
+
+        // Every wedge of `from` must map to one wedge of `to` across the shared triangles.
+        let mut wedges = HashMap::new();
+        for &t in &shared {
+            let corners = self.corners(t);
+            let wedge = |p: usize| self.triangles[t][corners.iter().position(|&c| c == p).unwrap()];
+            if *wedges.entry(wedge(from)).or_insert(wedge(to)) != wedge(to) {
+                return None;
+            }
+        }
+        for t in self.live_around(from) {
+            let corners = self.corners(t);
+            let wedge = self.triangles[t][corners.iter().position(|&c| c == from).unwrap()];
+            if !wedges.contains_key(&wedge) {
+                return None;
+            }
+        }
+
+        // Positions on borders or seams only slide along them, corners stay.
+        let neighbors = self.neighbors(from);
+        let features: Vec<usize> = neighbors
+            .iter()
+            .copied()
+            .filter(|&q| self.is_feature(from, q))
+            .collect();
+        match features.len() {
+            0 => {}
+            2 if features.contains(&to) => {}
+            _ => return None,
+        }
+
+        // The link condition: only the triangles of the edge may have both ends as neighbors.
+        let to_neighbors = self.neighbors(to);
+        let common = neighbors
+            .iter()
+            .filter(|q| to_neighbors.binary_search(q).is_ok())
+            .count();
+        if common != shared.len() {
+            return None;
+        }
+
+        for t in self.live_around(from).filter(|t| !shared.contains(t)) {
+            let corners = self.corners(t);
+            let before = self.normal(corners);
+            let after = self.normal(corners.map(|c| if c == from { to } else { c }));
+            if before.dot(&after) < MIN_NORMAL_COSINE * before.norm() * after.norm() {
+                return None;
+            }
+        }
+        Some(wedges)
+    }
+
+    fn collapse(&mut self, from: usize, to: usize, wedges: &HashMap<u32, u32>) {
+        let triangles = std::mem::take(&mut self.around[from]);
+        for &t in &triangles {
+            if !self.alive[t] {
+                continue;
+            }
+            if self.corners(t).contains(&to) {
+                self.alive[t] = false;
+                self.alive_count -= 1;
+                continue;
+            }
+            for wedge in &mut self.triangles[t] {
+                if let Some(&replacement) = wedges.get(wedge) {
+                    *wedge = replacement;
+                }
+            }
+            self.around[to].push(t);
+        }
+        let quadric = self.quadrics[from];
+        self.quadrics[to].add(&quadric);
+        self.removed[from] = true;
+    }
+
+    fn run(&mut self, target_triangles: usize, max_error: f64) {
+        let mut heap = BinaryHeap::new();
+        for position in 0..self.positions.len() {
+            for neighbor in self.neighbors(position) {
+                heap.push(Candidate {
+                    cost: self.cost(position, neighbor),
+                    from: position,
+                    to: neighbor,
+                    versions: (0, 0),
+                });
+            }
+        }
+
+        let max_cost = max_error * max_error;
+        while self.alive_count > target_triangles {
+            let Some(candidate) = heap.pop() else {
+                break;
+            };
+            let Candidate {
+                cost,
+                from,
+                to,
+                versions,
+            } = candidate;
+            if self.removed[from]
+                || self.removed[to]
+                || versions != (self.versions[from], self.versions[to])
+            {
+                continue;
+            }
+            if cost > max_cost {
+                break;
+            }
+            let Some(wedges) = self.collapse_wedges(from, to) else {
+                continue;
+            };
+            self.collapse(from, to, &wedges);
+
+            // Costs around `to` changed, and collapses rejected near it may now be valid.
+            let neighbors = self.neighbors(to);
+            for &position in std::iter::once(&to).chain(&neighbors) {
+                self.versions[position] += 1;
+            }
+            for &position in std::iter::once(&to).chain(&neighbors) {
+                self.push_candidates(&mut heap, position);
+            }
+        }
+    }
+}
+
+impl Primitive {
+    /**
+     * A copy with about `target_triangles` triangles, by collapsing the edges of least
+     * quadric error first (Garland and Heckbert). Collapses move one end onto the other,
+     * so the remaining vertices keep their exact attributes, and stop early once the
+     * root mean squared distance to the planes of the merged faces exceeds `max_error`.
+     *
+     * Borders and seams between vertices split by their attributes, like UV seams,
+     * only collapse along themselves, and their corners are kept.
+     */
+    pub fn simplify(&self, target_triangles: usize, max_error: f32) -> Primitive {
+        let mut simplifier = Simplifier::new(self);
+        simplifier.run(target_triangles, max_error as f64);
+
+        let mut simplified = self.clone();
+        let mut lookup = HashMap::new();
+        let mut sources = Vec::new();
+        simplified.indices = simplifier
+            .triangles
+            .iter()
+            .zip(&simplifier.alive)
+            .filter(|(_, &alive)| alive)
+            .flat_map(|(triangle, _)| triangle)
+            .map(|&wedge| {
+                *lookup.entry(wedge).or_insert_with(|| {
+                    sources.push(wedge as usize);
+                    (sources.len() - 1) as u32
+                })
+            })
+            .collect();
+        simplified.remap_vertices(&sources);
+        simplified
+    }
+}
+
+impl Mesh {
+    /**
+     * Simplify every primitive to about `ratio` of its triangles, see `Primitive::simplify`.
+     */
+    pub fn simplify(&self, ratio: f32, max_error: f32) -> Mesh {
+        Mesh {
+            name: self.name.clone(),
+            primitives: self
+                .primitives
+                .iter()
+                .map(|primitive| {
+                    let target = (primitive.triangle_count() as f32 * ratio).round() as usize;
+                    primitive.simplify(target, max_error)
+                })
+                .collect(),
+            weights: self.weights.clone(),
+        }
+    }
+
+    /**
+     * `levels` meshes, the first being a copy and each next one simplified from the
+     * previous to `ratio` of its triangles. A ratio of 1/4 keeps the triangle density
+     * on screen when `select_lod` steps down a level.
+     */
+    pub fn lod_chain(&self, levels: usize, ratio: f32, max_error: f32) -> Vec<Mesh> {
+        let mut chain: Vec<Mesh> = Vec::with_capacity(levels);
+        for level in 0..levels {
+            let mesh = match chain.last() {
+                Some(previous) => previous.simplify(ratio, max_error),
+                None => self.clone(),
+            };
+            if level > 0
+                && mesh
+                    .primitives
+                    .iter()
+                    .map(Primitive::triangle_count)
+                    .sum::<usize>()
+                    == 0
+            {
+                break;
+            }
+            chain.push(mesh);
+        }
+        chain
+    }
+}
+
+/**
+ * The larger side in pixels of the screen rectangle enclosing `bounds` projected by
+ * `model_view_projection`, `None` when they reach behind the camera.
+ */
+pub fn projected_size(
+    bounds: &Bbox3<f32>,
+    model_view_projection: &Matrix4<f32>,
+    viewport: &Viewport,
+) -> Option<f32> {
+    let mut min = nalgebra::Vector2::repeat(f32::INFINITY);
+    let mut max = nalgebra::Vector2::repeat(f32::NEG_INFINITY);
+    for corner in bounds.corners() {
+        let clip = model_view_projection * corner.push(1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        let ndc = clip.xy() / clip.w;
+        min = min.inf(&ndc);
+        max = max.sup(&ndc);
+    }
+    let extent = max - min;
+    Some((extent.x * viewport.width as f32).max(extent.y * viewport.height as f32) / 2.0)
+}
+
+/**
+ * The level of a chain of `levels` to draw, stepping down one level each time the
+ * projected size of `bounds` halves below `full_detail_size` pixels.
+ * Bounds reaching behind the camera get full detail.
+ */
+pub fn select_lod(
+    bounds: &Bbox3<f32>,
+    model_view_projection: &Matrix4<f32>,
+    viewport: &Viewport,
+    full_detail_size: f32,
+    levels: usize,
+) -> usize {
+    let Some(size) = projected_size(bounds, model_view_projection, viewport) else {
+        return 0;
+    };
+    let level = (full_detail_size / size.max(f32::MIN_POSITIVE))
+        .log2()
+        .floor();
+    (level.max(0.0) as usize).min(levels.saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Vertex;
+    use nalgebra::{Point3, Vector2};
+
+    /**
+     * A grid of `n` by `n` quads over the unit square, lifted by `height`, with texture
+     * coordinates offset by one on the right of `seam`, where the vertices are split.
+     */
+    fn grid(n: usize, height: impl Fn(f32) -> f32, seam: Option<usize>) -> Primitive {
+        let mut vertices = Vec::new();
+        let mut ids = HashMap::new();
+        let mut id = |i: usize, j: usize, right: bool| {
+            let right = right && seam.is_some_and(|seam| i >= seam);
+            *ids.entry((i, j, right)).or_insert_with(|| {
+                let (x, y) = (i as f32 / n as f32, j as f32 / n as f32);
+                vertices.push(Vertex {
+                    tex_coord: Some(Vector2::new(x + right as u8 as f32, y)),
+                    ..Vertex::new(Vector3::new(x, y, height(x)))
+                });
+                (vertices.len() - 1) as u32
+            })
+        };
+        let mut indices = Vec::new();
+        for i in 0..n {
+            for j in 0..n {
+                let right = seam.is_some_and(|seam| i >= seam);
+                let [a, b, c, d] =
+                    [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)].map(|(i, j)| id(i, j, right));
+                indices.extend([a, b, c, a, c, d]);
+            }
+        }
+        Primitive {
+            vertices,
+            indices,
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        }
+    }
+
+    fn area(primitive: &Primitive) -> f32 {
+        primitive
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let [a, b, c] =
+                    [0, 1, 2].map(|i| primitive.vertices[triangle[i] as usize].position.xyz());
+                (b - a).cross(&(c - a)).norm() / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_simplify_plane() {
+        let primitive = grid(8, |_| 0.0, None);
+        let simplified = primitive.simplify(0, 1e-4);
+        assert!(
+            simplified.triangle_count() <= 4,
+            "{}",
+            simplified.triangle_count()
+        );
+        assert!(simplified.vertices.len() <= 6);
+        assert!((area(&simplified) - 1.0).abs() < 1e-5);
+        // The corners remain, with their texture coordinates.
+        for corner in [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]] {
+            let corner = Vector2::from(corner);
+            assert!(simplified
+                .vertices
+                .iter()
+                .any(|v| v.position.xy() == corner && v.tex_coord == Some(corner)));
+        }
+
+        // Border collapses remove a single triangle, so the target may be undershot by one.
+        let halved = primitive.simplify(64, 1.0).triangle_count();
+        assert!(halved == 64 || halved == 63, "{}", halved);
+    }
+
+    #[test]
+    fn test_simplify_seam() {
+        let primitive = grid(8, |_| 0.0, Some(4));
+        let simplified = primitive.simplify(0, 1e-4);
+        assert!(
+            simplified.triangle_count() < 16,
+            "{}",
+            simplified.triangle_count()
+        );
+        assert!((area(&simplified) - 1.0).abs() < 1e-5);
+        // No triangle mixes the texture coordinates of both sides of the seam.
+        for triangle in simplified.indices.chunks_exact(3) {
+            let sides = [0, 1, 2].map(|i| {
+                let vertex = &simplified.vertices[triangle[i] as usize];
+                vertex.tex_coord.unwrap().x - vertex.position.x
+            });
+            assert!(sides.iter().all(|&side| (side - sides[0]).abs() < 1e-6));
+        }
+    }
+
+    #[test]
+    fn test_simplify_error_bound() {
+        // Folded along x = 1/2, the fold must survive a small error bound.
+        let primitive = grid(8, |x| (x - 0.5).abs(), None);
+        let simplified = primitive.simplify(0, 1e-4);
+        assert!(simplified.triangle_count() < primitive.triangle_count() / 4);
+        assert!((area(&simplified) - 2.0_f32.sqrt()).abs() < 1e-4);
+
+        let flattened = primitive.simplify(0, 10.0);
+        assert!(flattened.triangle_count() < simplified.triangle_count());
+        assert!(area(&flattened) < area(&simplified) - 1e-3);
+    }
+
+    #[test]
+    fn test_lod_chain() {
+        let mesh = Mesh {
+            name: None,
+            primitives: vec![grid(16, |x| (x * 6.0).sin() * 0.1, None)],
+            weights: Vec::new(),
+        };
+        let chain = mesh.lod_chain(3, 0.25, 1.0);
+        let counts: Vec<usize> = chain
+            .iter()
+            .map(|m| m.primitives[0].triangle_count())
+            .collect();
+        assert_eq!(counts[0], 512);
+        assert!(
+            counts[1].abs_diff(128) <= 1 && counts[2].abs_diff(32) <= 1,
+            "{:?}",
+            counts
+        );
+    }
+
+    #[test]
+    fn test_select_lod() {
+        let bounds = Bbox3::new(-0.5, 0.5, -0.5, 0.5, -0.5, 0.5);
+        let viewport = Viewport::new(200, 100);
+        let projection = Matrix4::new_perspective(2.0, 0.5, 0.1, 100.0);
+        let at = |distance: f32| {
+            projection
+                * Matrix4::look_at_rh(
+                    &Point3::new(0.0, 0.0, distance),
+                    &Point3::origin(),
+                    &Vector3::y(),
+                )
+        };
+
+        let size = projected_size(&bounds, &at(5.0), &viewport).unwrap();
+        let far_size = projected_size(&bounds, &at(10.0), &viewport).unwrap();
+        assert!(far_size < size * 0.6 && far_size > size * 0.4);
+
+        assert_eq!(select_lod(&bounds, &at(5.0), &viewport, size, 4), 0);
+        assert_eq!(select_lod(&bounds, &at(5.0), &viewport, size * 2.5, 4), 1);
+        assert_eq!(select_lod(&bounds, &at(5.0), &viewport, size * 100.0, 4), 3);
+        // From inside the bounds, some corners are behind the camera.
+        assert_eq!(select_lod(&bounds, &at(0.3), &viewport, size * 100.0, 4), 0);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::scene::mesh::Vertex;
+use nalgebra::{Matrix3, Matrix4, Vector3, Vector4};
+
+/**
+ * Joints deforming the meshes of the nodes referencing the skin.
+ */
+#[derive(Debug, Clone)]
+pub struct Skin {
+    pub name: Option<String>,
+
+    /**
+     * Nodes acting as joints, `Vertex::joints` index into this.
+     */
+    pub joints: Vec<usize>,
+
+    /**
+     * Bring each joint from mesh space to its local space, identity when not given.
+     */
+    pub inverse_bind_matrices: Vec<Matrix4<f32>>,
+
+    /**
+     * The common root of the joint hierarchy.
+     */
+    pub skeleton: Option<usize>,
+}
+
+impl Skin {
+    /**
+     * The matrix of every joint, from bind pose mesh space to world space, given the
+     * world matrices of the nodes (see `Scene::world_transforms`).
+     *
+     * Skinned vertices come out in world space: the transform of the skinned mesh's
+     * own node doesn't apply, draw them with an identity model matrix.
+     */
+    pub fn joint_matrices(&self, worlds: &[Option<Matrix4<f32>>]) -> Vec<Matrix4<f32>> {
+        self.joints
+            .iter()
+            .enumerate()
+            .map(|(i, joint)| {
+                let world = worlds
+                    .get(*joint)
+                    .copied()
+                    .flatten()
+                    .unwrap_or_else(Matrix4::identity);
+                let inverse_bind = self
+                    .inverse_bind_matrices
+                    .get(i)
+                    .copied()
+                    .unwrap_or_else(Matrix4::identity);
+                world * inverse_bind
+            })
+            .collect()
+    }
+}
+
+/**
+ * The linear blend of the joint matrices influencing a vertex, for vertex shaders.
+ * Out of range joints are skipped.
+ */
+pub fn skin_matrix(
+    joint_matrices: &[Matrix4<f32>],
+    joints: &[u16; 4],
+    weights: &Vector4<f32>,
+) -> Matrix4<f32> {
+    joints
+        .iter()
+        .zip(weights.iter())
+        .filter(|(_, weight)| **weight != 0.0)
+        .filter_map(|(joint, weight)| Some(joint_matrices.get(*joint as usize)? * *weight))
+        .fold(Matrix4::zeros(), |sum, matrix| sum + matrix)
+}
+
+/**
+ * The skinned position and normal of `vertex`, unchanged without joints and weights.
+ */
+pub fn skin_vertex(
+    vertex: &Vertex,
+    joint_matrices: &[Matrix4<f32>],
+) -> (Vector4<f32>, Option<Vector3<f32>>) {
+    let (Some(joints), Some(weights)) = (vertex.joints, vertex.weights) else {
+        return (vertex.position, vertex.normal);
+    };
+    let matrix = skin_matrix(joint_matrices, &joints, &weights);
+    let normal_matrix = matrix
+        .fixed_view::<3, 3>(0, 0)
+        .try_inverse()
+        .map(|inverse| inverse.transpose())
+        .unwrap_or_else(Matrix3::identity);
+    (
+        matrix * vertex.position,
+        vertex.normal.map(|normal| normal_matrix * normal),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_blend_skinning() {
+        let skin = Skin {
+            name: None,
+            joints: vec![1, 2],
+            inverse_bind_matrices: vec![
+                Matrix4::identity(),
+                Matrix4::new_translation(&Vector3::new(0.0, -1.0, 0.0)),
+            ],
+            skeleton: None,
+        };
+        // The second joint sits at y = 1 in the bind pose and moves to x = 1, y = 1.
+        let worlds = [
+            None,
+            Some(Matrix4::identity()),
+            Some(Matrix4::new_translation(&Vector3::new(1.0, 1.0, 0.0))),
+        ];
+        let joint_matrices = skin.joint_matrices(&worlds);
+
+        let mut vertex = Vertex::new(Vector3::new(0.0, 1.0, 0.0));
+        vertex.normal = Some(Vector3::x());
+        vertex.joints = Some([0, 1, 0, 0]);
+        vertex.weights = Some(Vector4::new(0.5, 0.5, 0.0, 0.0));
+        let (position, normal) = skin_vertex(&vertex, &joint_matrices);
+        assert_eq!(position, Vector4::new(0.5, 1.0, 0.0, 1.0));
+        assert_eq!(normal, Some(Vector3::x()));
+
+        // Vertices without weights stay put.
+        let still = Vertex::new(Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(skin_vertex(&still, &joint_matrices).0, still.position);
+    }
+}
@@ -0,0 +1,326 @@
+use crate::scene::mesh::{Mesh, Primitive, Vertex};
+use nalgebra::Vector3;
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StlFormat {
+    Ascii,
+    Binary,
+}
+
+#[derive(Debug)]
+pub enum StlError {
+    Io(io::Error),
+    Malformed(String),
+}
+
+impl fmt::Display for StlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StlError::Io(err) => write!(f, "io error: {}", err),
+            StlError::Malformed(reason) => write!(f, "malformed STL: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for StlError {}
+
+impl From<io::Error> for StlError {
+    fn from(err: io::Error) -> Self {
+        StlError::Io(err)
+    }
+}
+
+const HEADER_SIZE: usize = 80;
+
+const FACET_SIZE: usize = 50;
+
+/**
+ * Read an ASCII or binary STL file.
+ */
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Mesh, StlError> {
+    read_from(BufReader::new(File::open(path)?))
+}
+
+/**
+ * Decode an STL file into a mesh of a single primitive, without shared vertices.
+ * Vertices get the flat normal of their facet, computed from the counter-clockwise
+ * winding, since the stored facet normals are often unreliable.
+ */
+pub fn read_from<R: Read>(mut reader: R) -> Result<Mesh, StlError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    // Binary files may start with `solid` too, so trust the facet count first.
+    let binary_count = bytes
+        .get(HEADER_SIZE..HEADER_SIZE + 4)
+        .map(|count| u32::from_le_bytes(count.try_into().unwrap()) as usize);
+    let (name, triangles) = match binary_count {
+        Some(count) if bytes.len() == HEADER_SIZE + 4 + count * FACET_SIZE => {
+            (None, read_binary(&bytes[HEADER_SIZE + 4..]))
+        }
+        _ if bytes.starts_with(b"solid") => read_ascii(&bytes)?,
+        Some(count) => {
+            return Err(StlError::Malformed(format!(
+                "{} bytes for {} facets",
+                bytes.len(),
+                count
+            )))
+        }
+        None => return Err(StlError::Malformed(String::from("truncated header"))),
+    };
+
+    let mut vertices = Vec::with_capacity(triangles.len() * 3);
+    for (triangle, stored_normal) in triangles {
+        let [a, b, c] = triangle;
+        let normal = (b - a)
+            .cross(&(c - a))
+            .try_normalize(0.0)
+            .or_else(|| stored_normal.try_normalize(0.0))
+            .unwrap_or_else(Vector3::zeros);
+        vertices.extend(triangle.map(|position| Vertex {
+            normal: Some(normal),
+            ..Vertex::new(position)
+        }));
+    }
+
+    Ok(Mesh {
+        name,
+        primitives: vec![Primitive {
+            indices: (0..vertices.len() as u32).collect(),
+            vertices,
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        }],
+        weights: Vec::new(),
+    })
+}
+
+type Facet = ([Vector3<f32>; 3], Vector3<f32>);
+
+fn read_binary(facets: &[u8]) -> Vec<Facet> {
+    let vector = |bytes: &[u8]| {
+        let float = |i: usize| f32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap());
+        Vector3::new(float(0), float(1), float(2))
+    };
+    facets
+        .chunks_exact(FACET_SIZE)
+        .map(|facet| {
+            (
+                [
+                    vector(&facet[12..]),
+                    vector(&facet[24..]),
+                    vector(&facet[36..]),
+                ],
+                vector(facet),
+            )
+        })
+        .collect()
+}
+
+fn read_ascii(bytes: &[u8]) -> Result<(Option<String>, Vec<Facet>), StlError> {
+    let text = std::str::from_utf8(bytes)
+        .map_err(|_| StlError::Malformed(String::from("ASCII STL is not valid UTF-8")))?;
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+
+    let header = lines.next().unwrap_or("");
+    let name = header
+        .strip_prefix("solid")
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from);
+
+    let vector = |line: &str, keyword: &str| -> Result<Vector3<f32>, StlError> {
+        let values: Vec<f32> = line
+            .strip_prefix(keyword)
+            .ok_or_else(|| StlError::Malformed(format!("expected `{}`, got `{}`", keyword, line)))?
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map_err(|_| StlError::Malformed(format!("invalid numbers in `{}`", line)))?;
+        match values[..] {
+            [x, y, z] => Ok(Vector3::new(x, y, z)),
+            _ => Err(StlError::Malformed(format!(
+                "expected 3 numbers in `{}`",
+                line
+            ))),
+        }
+    };
+    let mut expect = |keyword: &str| -> Result<&str, StlError> {
+        match lines.next() {
+            Some(line) if line.starts_with(keyword) => Ok(line),
+            Some(line) => Err(StlError::Malformed(format!(
+                "expected `{}`, got `{}`",
+                keyword, line
+            ))),
+            None => Err(StlError::Malformed(format!(
+                "expected `{}`, got end of file",
+                keyword
+            ))),
+        }
+    };
+
+    let mut facets = Vec::new();
+    loop {
+        let line = expect("")?;
+        if line.starts_with("endsolid") {
+            break;
+        }
+        let normal = vector(line, "facet normal")?;
+        expect("outer loop")?;
+        let triangle = [
+            vector(expect("vertex")?, "vertex")?,
+            vector(expect("vertex")?, "vertex")?,
+            vector(expect("vertex")?, "vertex")?,
+        ];
+        expect("endloop")?;
+        expect("endfacet")?;
+        facets.push((triangle, normal));
+    }
+    Ok((name, facets))
+}
+
+/**
+ * Write the triangles of every primitive of `mesh` to `path`.
+ */
+pub fn save<P: AsRef<Path>>(path: P, mesh: &Mesh, format: StlFormat) -> Result<(), StlError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_to(&mut writer, mesh, format)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/**
+ * Encode the triangles of every primitive of `mesh`, with facet normals from their winding.
+ */
+pub fn write_to<W: Write>(mut writer: W, mesh: &Mesh, format: StlFormat) -> Result<(), StlError> {
+    let triangles: Vec<[Vector3<f32>; 3]> = mesh
+        .primitives
+        .iter()
+        .flat_map(|primitive| {
+            primitive.indices.chunks_exact(3).map(|triangle| {
+                [0, 1, 2].map(|i| primitive.vertices[triangle[i] as usize].position.xyz())
+            })
+        })
+        .collect();
+    let normal = |[a, b, c]: &[Vector3<f32>; 3]| {
+        (b - a)
+            .cross(&(c - a))
+            .try_normalize(0.0)
+            .unwrap_or_else(Vector3::zeros)
+    };
+
+    match format {
+        StlFormat::Ascii => {
+            let name = mesh.name.as_deref().unwrap_or("");
+            writeln!(writer, "solid {}", name)?;
+            for triangle in &triangles {
+                let n = normal(triangle);
+                writeln!(writer, "  facet normal {:e} {:e} {:e}", n.x, n.y, n.z)?;
+                writeln!(writer, "    outer loop")?;
+                for p in triangle {
+                    writeln!(writer, "      vertex {:e} {:e} {:e}", p.x, p.y, p.z)?;
+                }
+                writeln!(writer, "    endloop")?;
+                writeln!(writer, "  endfacet")?;
+            }
+            writeln!(writer, "endsolid {}", name)?;
+        }
+
+        StlFormat::Binary => {
+            let count = u32::try_from(triangles.len())
+                .map_err(|_| StlError::Malformed(String::from("too many triangles")))?;
+            let mut header = [0u8; HEADER_SIZE];
+            // Headers starting with `solid` confuse readers into parsing ASCII.
+            let tag = b"binary STL";
+            header[..tag.len()].copy_from_slice(tag);
+            writer.write_all(&header)?;
+            writer.write_all(&count.to_le_bytes())?;
+            for triangle in &triangles {
+                for v in std::iter::once(normal(triangle)).chain(triangle.iter().copied()) {
+                    for value in v.iter() {
+                        writer.write_all(&value.to_le_bytes())?;
+                    }
+                }
+                writer.write_all(&[0, 0])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tetrahedron() -> Mesh {
+        let positions = [
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        ];
+        Mesh {
+            name: Some(String::from("tetrahedron")),
+            primitives: vec![Primitive {
+                vertices: positions.into_iter().map(Vertex::new).collect(),
+                indices: vec![0, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3],
+                texture_index: None,
+                material: None,
+                targets: Vec::new(),
+            }],
+            weights: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mesh = tetrahedron();
+        for format in [StlFormat::Ascii, StlFormat::Binary] {
+            let mut bytes = Vec::new();
+            write_to(&mut bytes, &mesh, format).unwrap();
+            let read = read_from(bytes.as_slice()).unwrap();
+
+            let expected_name = (format == StlFormat::Ascii).then_some("tetrahedron");
+            assert_eq!(read.name.as_deref(), expected_name);
+            let primitive = &read.primitives[0];
+            assert_eq!(primitive.vertices.len(), 12);
+            for i in 0..12 {
+                assert_eq!(
+                    primitive.vertex(i).position,
+                    mesh.primitives[0].vertex(i).position
+                );
+            }
+            // The bottom facet faces down, flat normals are not shared between facets.
+            assert_eq!(primitive.vertex(0).normal, Some(-Vector3::z()));
+            assert_eq!(primitive.vertex(3).normal, Some(-Vector3::y()));
+            let slanted = Vector3::repeat(1.0).normalize();
+            assert!((primitive.vertex(9).normal.unwrap() - slanted).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_read_errors() {
+        assert!(matches!(
+            read_from(&b"solid\nfacet normal 0 0\n"[..]),
+            Err(StlError::Malformed(_))
+        ));
+        assert!(matches!(
+            read_from(&b"solid x\nfacet normal 0 0 1\n"[..]),
+            Err(StlError::Malformed(_))
+        ));
+        let mut binary = vec![0u8; HEADER_SIZE];
+        binary.extend(2u32.to_le_bytes());
+        binary.extend([0u8; FACET_SIZE]);
+        assert!(matches!(
+            read_from(binary.as_slice()),
+            Err(StlError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{
+    common::color::Color,
+    scene::mesh::{Primitive, Vertex},
+};
+use nalgebra::{Vector3, Vector4};
+use std::collections::HashMap;
+
+impl Primitive {
+    /**
+     * Fill in per-vertex tangents from the positions, normals and texture coordinates:
+     * per-corner tangents from the texture coordinate gradients, projected onto the
+     * tangent plane and weighted by the corner angle, with the bitangent sign in `w`.
+     *
+     * Vertices shared by triangles of opposite handedness, as on the seam of mirrored
+     * texture coordinates, are split so that each side keeps its sign. Other shared
+     * vertices average their tangents. Returns false, leaving the primitive untouched,
+     * without normals or texture coordinates.
+     */
+    pub fn generate_tangents(&mut self) -> bool {
+        let complete = |vertex: &Vertex| vertex.normal.is_some() && vertex.tex_coord.is_some();
+        if !self.vertices.iter().all(complete) {
+            return false;
+        }
+
+        // The weighted tangent and bitangent of each corner, if its triangle has any.
+        let mut corners: Vec<Option<(Vector3<f32>, Vector3<f32>)>> = vec![None; self.indices.len()];
+        for (triangle, corners) in self
+            .indices
+            .chunks_exact(3)
+            .zip(corners.chunks_exact_mut(3))
+        {
+            let indices = [
+                triangle[0] as usize,
+                triangle[1] as usize,
+                triangle[2] as usize,
+            ];
+            let p = indices.map(|i| self.vertices[i].position.xyz());
+            let uv = indices.map(|i| self.vertices[i].tex_coord.unwrap());
+
+            let (e1, e2) = (p[1] - p[0], p[2] - p[0]);
+            let (d1, d2) = (uv[1] - uv[0], uv[2] - uv[0]);
+            let det = d1.x * d2.y - d2.x * d1.y;
+            if det.abs() <= f32::EPSILON {
+                continue;
+            }
+            let tangent = (e1 * d2.y - e2 * d1.y) / det;
+            let bitangent = (e2 * d1.x - e1 * d2.x) / det;
+
+            for (k, &i) in indices.iter().enumerate() {
+                let to_next = p[(k + 1) % 3] - p[k];
+                let to_prev = p[(k + 2) % 3] - p[k];
+                let angle = match (
+                    to_next.try_normalize(f32::EPSILON),
+                    to_prev.try_normalize(f32::EPSILON),
+                ) {
+                    (Some(a), Some(b)) => a.dot(&b).clamp(-1.0, 1.0).acos(),
+                    _ => continue,
+                };
+
+                let n = self.vertices[i].normal.unwrap();
+                let project = |v: Vector3<f32>| {
+                    (v - n * n.dot(&v))
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::zeros)
+                };
+                corners[k] = Some((project(tangent) * angle, project(bitangent) * angle));
+            }
+        }
+
+        // The first handedness met keeps the vertex, the other one gets a copy.
+        let mut handedness: Vec<Option<bool>> = vec![None; self.vertices.len()];
+        let mut mirrored: HashMap<u32, u32> = HashMap::new();
+        let mut sources: Vec<usize> = (0..self.vertices.len()).collect();
+        for (index, corner) in self.indices.iter_mut().zip(&corners) {
+            let Some((tangent, bitangent)) = corner else {
+                continue;
+            };
+            let i = *index;
+            let n = self.vertices[i as usize].normal.unwrap();
+            let positive = n.cross(tangent).dot(bitangent) >= 0.0;
+            match handedness[i as usize] {
+                None => handedness[i as usize] = Some(positive),
+                Some(first) if first == positive => {}
+                Some(_) => {
+                    *index = *mirrored.entry(i).or_insert_with(|| {
+                        sources.push(i as usize);
+                        (sources.len() - 1) as u32
+                    });
+                }
+            }
+        }
+        if !mirrored.is_empty() {
+            self.remap_vertices(&sources);
+        }
+
+        let mut tangents = vec![Vector3::<f32>::zeros(); self.vertices.len()];
+        let mut bitangents = vec![Vector3::<f32>::zeros(); self.vertices.len()];
+        for (&index, corner) in self.indices.iter().zip(&corners) {
+            if let Some((tangent, bitangent)) = corner {
+                tangents[index as usize] += tangent;
+                bitangents[index as usize] += bitangent;
+            }
+        }
+
+        for ((vertex, tangent), bitangent) in self.vertices.iter_mut().zip(tangents).zip(bitangents)
+        {
+            let n = vertex.normal.unwrap();
+            let t = (tangent - n * n.dot(&tangent))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(|| any_perpendicular(&n));
+            let w = if n.cross(&t).dot(&bitangent) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            vertex.tangent = Some(t.push(w));
+        }
+        true
+    }
+}
+
+fn any_perpendicular(n: &Vector3<f32>) -> Vector3<f32> {
+    let axis = if n.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    (axis - n * n.dot(&axis))
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(axis)
+}
+
+/**
+ * The shading normal from a tangent space normal map texel, for fragment shaders.
+ *
+ * `normal` and `tangent` are the interpolated varyings, with the bitangent sign in the
+ * tangent's `w`; the TBN basis is rebuilt orthonormal from them. `scale` multiplies the
+ * X and Y of the texel, as `Material::normal_scale`.
+ */
+pub fn perturb_normal(
+    normal: &Vector3<f32>,
+    tangent: &Vector4<f32>,
+    texel: &Color,
+    scale: f32,
+) -> Vector3<f32> {
+    let Some(n) = normal.try_normalize(f32::EPSILON) else {
+        return *normal;
+    };
+    let t = tangent.xyz();
+    let Some(t) = (t - n * n.dot(&t)).try_normalize(f32::EPSILON) else {
+        return n;
+    };
+    let b = n.cross(&t) * if tangent.w < 0.0 { -1.0 } else { 1.0 };
+
+    let m = Vector3::new(
+        (texel.r * 2.0 - 1.0) * scale,
+        (texel.g * 2.0 - 1.0) * scale,
+        texel.b * 2.0 - 1.0,
+    );
+    (t * m.x + b * m.y + n * m.z)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector2;
+
+    fn quad(mirrored: bool) -> Primitive {
+        let corners = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)];
+        let vertices = corners
+            .iter()
+            .map(|&(x, y)| {
+                let mut vertex = Vertex::new(Vector3::new(x, y, 0.0));
+                vertex.normal = Some(Vector3::z());
+                vertex.tex_coord = Some(Vector2::new(if mirrored { 1.0 - x } else { x }, y));
+                vertex
+            })
+            .collect();
+        Primitive {
+            vertices,
+            indices: vec![0, 1, 2, 2, 1, 3],
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_generate_tangents() {
+        let mut primitive = quad(false);
+        assert!(primitive.generate_tangents());
+        for vertex in &primitive.vertices {
+            assert_eq!(vertex.tangent, Some(Vector4::new(1.0, 0.0, 0.0, 1.0)));
+        }
+
+        // U running against X flips the tangent, V still follows Y.
+        let mut primitive = quad(true);
+        assert!(primitive.generate_tangents());
+        for vertex in &primitive.vertices {
+            assert_eq!(vertex.tangent, Some(Vector4::new(-1.0, 0.0, 0.0, -1.0)));
+        }
+
+        let mut primitive = quad(false);
+        primitive.vertices[0].tex_coord = None;
+        assert!(!primitive.generate_tangents());
+        assert!(primitive.vertices[1].tangent.is_none());
+    }
+
+    #[test]
+    fn test_generate_tangents_mirrored() {
+        // Two unit squares side by side, with U mirrored about their shared edge x = 1.
+        let mut vertices = Vec::new();
+        for y in [0.0, 1.0] {
+            for x in [0.0, 1.0, 2.0] {
+                let mut vertex = Vertex::new(Vector3::new(x, y, 0.0));
+                vertex.normal = Some(Vector3::z());
+                vertex.tex_coord = Some(Vector2::new(1.0 - (x - 1.0f32).abs(), y));
+                vertices.push(vertex);
+            }
+        }
+        let mut primitive = Primitive {
+            vertices,
+            indices: vec![0, 1, 3, 3, 1, 4, 1, 2, 4, 4, 2, 5],
+            texture_index: None,
+            material: None,
+            targets: Vec::new(),
+        };
+        assert!(primitive.generate_tangents());
+
+        // The shared edge is split, each side keeping its own tangent and sign.
+        assert_eq!(primitive.vertices.len(), 8);
+        for (triangle, expected) in primitive.indices.chunks_exact(3).zip([
+            Vector4::new(1.0, 0.0, 0.0, 1.0),
+            Vector4::new(1.0, 0.0, 0.0, 1.0),
+            Vector4::new(-1.0, 0.0, 0.0, -1.0),
+            Vector4::new(-1.0, 0.0, 0.0, -1.0),
+        ]) {
+            for &i in triangle {
+                assert_eq!(primitive.vertices[i as usize].tangent, Some(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_perturb_normal() {
+        let n = Vector3::new(0.0, 0.0, 2.0);
+        let t = Vector4::new(1.0, 0.0, 0.0, 1.0);
+        let flat = Color::new(0.5, 0.5, 1.0, 1.0);
+        assert!((perturb_normal(&n, &t, &flat, 1.0) - Vector3::z()).norm() < 1e-6);
+
+        // +X in tangent space follows the tangent, +Y the bitangent and its sign.
+        let tilted = Color::new(1.0, 0.5, 0.5, 1.0);
+        assert!((perturb_normal(&n, &t, &tilted, 1.0) - Vector3::x()).norm() < 1e-6);
+        let up = Color::new(0.5, 1.0, 0.5, 1.0);
+        assert!((perturb_normal(&n, &t, &up, 1.0) - Vector3::y()).norm() < 1e-6);
+        let mirrored = Vector4::new(1.0, 0.0, 0.0, -1.0);
+        assert!((perturb_normal(&n, &mirrored, &up, 1.0) + Vector3::y()).norm() < 1e-6);
+
+        // A zero scale ignores the map.
+        assert!((perturb_normal(&n, &t, &tilted, 0.0) - Vector3::z()).norm() < 1e-6);
+    }
+}
@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+use tinyrenderer::{common::color::Color, metrics, rasterizer::texture::Texture};
+
+/**
+ * Set this environment variable to `1` or `true` to overwrite the golden images with
+ * the current output. Other values, such as `0` or an empty one, compare as usual.
+ */
+pub const BLESS_ENV: &str = "TINYRENDERER_BLESS";
+
+fn is_blessing() -> bool {
+    std::env::var(BLESS_ENV).is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Tolerance {
+    /**
+     * Largest per-channel difference, in 8-bit steps, before a pixel counts as different.
+     */
+    pub channel: u8,
+
+    /**
+     * Fraction of pixels allowed to exceed `channel`.
+     */
+    pub max_outlier_ratio: f64,
+
+    pub min_psnr: f64,
+
+    pub min_ssim: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            channel: 2,
+            max_outlier_ratio: 0.001,
+            min_psnr: 40.0,
+            min_ssim: 0.99,
+        }
+    }
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden/images")
+        .join(format!("{}.png", name))
+}
+
+fn output_path(name: &str, suffix: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("golden")
+        .join(format!("{}.{}.png", name, suffix))
+}
+
+/**
+ * Compare `actual` with the golden image `name`.
+ * On failure the actual output and a diff image are written next to the test binary.
+ */
+pub fn check(name: &str, actual: &Texture<Color>, tolerance: Tolerance) {
+    let golden_path = golden_path(name);
+
+    if is_blessing() {
+        std::fs::create_dir_all(golden_path.parent().unwrap()).unwrap();
+        actual.save(&golden_path).unwrap();
+        return;
+    }
+
+    let expected = Texture::<Color>::load(&golden_path).unwrap_or_else(|err| {
+        panic!(
+            "failed to load golden image {}: {}, run with {}=1 to create it",
+            golden_path.display(),
+            err,
+            BLESS_ENV
+        )
+    });
+    compare(name, &expected, actual, tolerance);
+}
+
+/**
+ * Compare `actual` with `expected`, another rendering rather than a golden image,
+ * failing like `check`.
+ */
+pub fn compare(
+    name: &str,
+    expected: &Texture<Color>,
+    actual: &Texture<Color>,
+    tolerance: Tolerance,
+) {
+    assert_eq!(
+        (expected.width, expected.height),
+        (actual.width, actual.height),
+        "size mismatch for {}",
+        name
+    );
+
+    let threshold = tolerance.channel as f32 / 255.0;
+    let outliers = metrics::count_over_threshold(expected, actual, threshold);
+    let outlier_ratio = outliers as f64 / expected.data.len() as f64;
+    let psnr = metrics::psnr(expected, actual);
+    let ssim = metrics::ssim(expected, actual);
+
+    if outlier_ratio > tolerance.max_outlier_ratio
+        || psnr < tolerance.min_psnr
+        || ssim < tolerance.min_ssim
+    {
+        let actual_path = output_path(name, "actual");
+        let diff_path = output_path(name, "diff");
+        std::fs::create_dir_all(actual_path.parent().unwrap()).unwrap();
+        actual.save(&actual_path).unwrap();
+        metrics::heatmap(&metrics::max_channel_error(expected, actual))
+            .save(&diff_path)
+            .unwrap();
+        panic!(
+            "{} differs from its reference: {} pixels over tolerance ({:.4}%), \
+             PSNR {:.2} dB, SSIM {:.4}\n  actual: {}\n  diff: {}",
+            name,
+            outliers,
+            outlier_ratio * 100.0,
+            psnr,
+            ssim,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
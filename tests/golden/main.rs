@@ -0,0 +1,103 @@
+//! Golden image regression tests.
+//!
+//! Each test renders a canonical scene and compares it with `tests/golden/images/<name>.png`.
+//! Run with `TINYRENDERER_BLESS=1 cargo test --test golden` to accept the current output.
+
+mod harness;
+mod scenes;
+
+use harness::{check, compare, Tolerance};
+
+const SIZE: u32 = 128;
+
+#[test]
+fn golden_line_fan() {
+    check("line_fan", &scenes::line_fan(SIZE), Tolerance::default());
+}
+
+#[test]
+fn golden_triangle_grid() {
+    check(
+        "triangle_grid",
+        &scenes::triangle_grid(SIZE),
+        Tolerance::default(),
+    );
+}
+
+#[test]
+fn golden_duck() {
+    check(
+        "duck",
+        &scenes::model("models/Duck/glTF/Duck.gltf", SIZE),
+        Tolerance::default(),
+    );
+}
+
+#[test]
+fn golden_avocado() {
+    check(
+        "avocado",
+        &scenes::model("models/Avocado/glTF/Avocado.gltf", SIZE),
+        Tolerance::default(),
+    );
+}
+
+#[test]
+fn golden_avocado_pbr() {
+    check(
+        "avocado_pbr",
+        &scenes::model_pbr("models/Avocado/glTF/Avocado.gltf", SIZE),
+        Tolerance::default(),
+    );
+}
+
+/**
+ * Dequantization rounds positions and texture coordinates slightly differently,
+ * moving some edge pixels. Draco meshes are quantized as well.
+ */
+const QUANTIZED: Tolerance = Tolerance {
+    channel: 8,
+    max_outlier_ratio: 0.002,
+    min_psnr: 38.0,
+    min_ssim: 0.99,
+};
+
+#[test]
+fn golden_duck_quantized() {
+    compare(
+        "duck_quantized",
+        &scenes::model("models/Duck/glTF/Duck.gltf", SIZE),
+        &scenes::model("models/Duck/glTF-Quantized/Duck.gltf", SIZE),
+        QUANTIZED,
+    );
+}
+
+#[test]
+fn golden_avocado_quantized() {
+    compare(
+        "avocado_quantized",
+        &scenes::model_pbr("models/Avocado/glTF/Avocado.gltf", SIZE),
+        &scenes::model_pbr("models/Avocado/glTF-Quantized/Avocado.gltf", SIZE),
+        QUANTIZED,
+    );
+}
+
+#[test]
+fn golden_duck_draco() {
+    compare(
+        "duck_draco",
+        &scenes::model("models/Duck/glTF/Duck.gltf", SIZE),
+        &scenes::model("models/Duck/glTF-Draco/Duck.gltf", SIZE),
+        QUANTIZED,
+    );
+}
+
+#[test]
+fn golden_avocado_draco() {
+    compare(
+        "avocado_draco",
+        &scenes::model_pbr("models/Avocado/glTF/Avocado.gltf", SIZE),
+        &scenes::model_pbr("models/Avocado/glTF-Draco/Avocado.gltf", SIZE),
+        QUANTIZED,
+    );
+}
@@ -0,0 +1,246 @@
+use nalgebra::{Matrix3, Matrix4, Vector2, Vector3, Vector4};
+use std::f32::consts::PI;
+use tinyrenderer::{
+    common::{
+        basetype::{Bbox2, Viewport},
+        color::Color,
+        random,
+    },
+    interpolate::Interpolate,
+    rasterizer::{
+        pass::RenderPass,
+        pipeline::{CullMode, DepthCompare, Pipeline},
+        shader::{FsPayload, Shader, VsOutput},
+        texture::{EdgeBehavior, SamplingMethod, Texture},
+    },
+    scene::{
+        self,
+        camera::View,
+        light::{Lighting, PunctualLight},
+        material::{Material, TextureInfo},
+        mesh::Primitive,
+        pbr::PbrProgram,
+        render::draw_scene,
+    },
+};
+
+pub fn line_fan(size: u32) -> Texture<Color> {
+    let mut rng = random::seeded_rng(random::DEFAULT_SEED);
+    let mut pass = RenderPass::new(Viewport::new(size, size));
+    pass.clear();
+
+    let center = size as f32 / 2.0;
+    let r_0 = size as f32 / 8.0;
+    let r_1 = 3.0 * r_0;
+    for i in 0..48 {
+        let angle = i as f32 * std::f32::consts::PI / 24.0;
+        let (sin, cos) = angle.sin_cos();
+        pass.draw_line(
+            &Vector2::new(center + r_0 * cos, center + r_0 * sin),
+            &Vector2::new(center + r_1 * cos, center + r_1 * sin),
+            &Color::random(&mut rng),
+        );
+    }
+
+    pass.frame_texture
+}
+
+struct FlatProgram {
+    positions: Vec<Vector4<f32>>,
+
+    colors: Vec<Color>,
+}
+
+impl Shader for FlatProgram {
+    type Varying = Color;
+
+    fn vertex_shader(&self, index: usize) -> VsOutput<Self::Varying> {
+        VsOutput {
+            position: self.positions[index],
+            varying: self.colors[index / 3],
+        }
+    }
+
+    fn fragment_shader(&self, payload: FsPayload<Self::Varying>) -> Color {
+        payload.varying
+    }
+}
+
+/**
+ * A 4x4 grid of cells, each holding one random triangle in a random color.
+ */
+pub fn triangle_grid(size: u32) -> Texture<Color> {
+    let mut rng = random::seeded_rng(random::DEFAULT_SEED);
+    let mut pass = RenderPass::new(Viewport::new(size, size));
+    pass.clear();
+
+    let mut positions = Vec::new();
+    let mut colors = Vec::new();
+    for i in 0..16 {
+        // Cells span a quarter of the [-1, 1] clip space in each direction.
+        let l = -1.0 + (i % 4) as f32 * 0.5;
+        let b = -1.0 + (i / 4) as f32 * 0.5;
+        let cell = Bbox2::new(l, l + 0.5, b, b + 0.5);
+        for p in random::triangle_in_bbox2(&mut rng, &cell) {
+            positions.push(Vector4::new(p.x, p.y, 0.0, 1.0));
+        }
+        colors.push(Color::random(&mut rng));
+    }
+
+    let program = FlatProgram { positions, colors };
+    let mut pipeline = Pipeline {
+        program: &program,
+        cull_mode: CullMode::None,
+        depth_write_enable: false,
+        depth_compare: DepthCompare::Less,
+    };
+    pass.draw(&mut pipeline, program.positions.len());
+
+    pass.frame_texture
+}
+
+#[derive(Debug, Interpolate)]
+struct Varying {
+    pub normal: Vector3<f32>,
+
+    pub tex_coord: Vector2<f32>,
+}
+
+struct ModelProgram<'a> {
+    primitive: &'a Primitive,
+
+    /**
+     * The base color texture of the material, into `textures`.
+     */
+    base_color_texture: Option<TextureInfo>,
+
+    textures: &'a [Texture<Color>],
+
+    matrix: Matrix4<f32>,
+
+    normal_matrix: Matrix3<f32>,
+
+    light_dir: Vector3<f32>,
+}
+
+impl<'a> Shader for ModelProgram<'a> {
+    type Varying = Varying;
+
+    fn vertex_shader(&self, index: usize) -> VsOutput<Self::Varying> {
+        let vertex = self.primitive.vertex(index);
+        let tex_coord = self
+            .base_color_texture
+            .and_then(|info| vertex.tex_coord_set(info.tex_coord));
+        VsOutput {
+            position: self.matrix * vertex.position,
+            varying: Self::Varying {
+                normal: self.normal_matrix * vertex.normal.unwrap_or_else(Vector3::zeros),
+                tex_coord: tex_coord.unwrap_or_else(Vector2::zeros),
+            },
+        }
+    }
+
+    fn fragment_shader(&self, payload: FsPayload<Self::Varying>) -> Color {
+        let texture = self
+            .base_color_texture
+            .and_then(|info| Some((info, self.textures.get(info.texture)?)));
+        let color = match texture {
+            Some((info, texture)) => texture.sample(
+                &info.map(&payload.varying.tex_coord),
+                SamplingMethod::Bilinear,
+                EdgeBehavior::Wrap,
+            ),
+            None => Color::WHITE,
+        };
+        let normal = payload.varying.normal.normalize();
+        color * (0.1 + 0.9 * normal.dot(&self.light_dir).max(0.0))
+    }
+}
+
+/**
+ * Render the default scene of the glTF file at `path`, relative to the crate root,
+ * from a camera framing its bounds.
+ */
+pub fn model(path: &str, size: u32) -> Texture<Color> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+    let scene = scene::gltf::load(path).unwrap();
+
+    let direction = Vector3::new(1.0, 1.0, 1.0).normalize();
+    let view = View::framing(
+        &scene.bounds(None).unwrap(),
+        &direction,
+        45.0_f32.to_radians(),
+        1.0,
+    );
+    let view_projection = view.view_projection();
+
+    let mut pass = RenderPass::new(Viewport::new(size, size));
+    pass.clear();
+
+    draw_scene(
+        &mut pass,
+        &scene,
+        None,
+        view.reversed_z,
+        |instance, primitive| ModelProgram {
+            primitive,
+            base_color_texture: primitive
+                .material
+                .and_then(|index| scene.materials.get(index))
+                .and_then(|material| material.base_color_texture),
+            textures: &scene.textures,
+            matrix: view_projection * instance.world,
+            normal_matrix: instance.normal_matrix(),
+            light_dir: direction,
+        },
+    );
+
+    pass.frame_texture
+}
+
+/**
+ * Like `model`, shaded with the metallic-roughness materials of the asset under a
+ * white directional light from the camera.
+ */
+pub fn model_pbr(path: &str, size: u32) -> Texture<Color> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+    let scene = scene::gltf::load(path).unwrap();
+
+    let direction = Vector3::new(1.0, 1.0, 1.0).normalize();
+    let view = View::framing(
+        &scene.bounds(None).unwrap(),
+        &direction,
+        45.0_f32.to_radians(),
+        1.0,
+    );
+    let lighting = Lighting {
+        ambient: Vector3::repeat(0.1),
+        lights: vec![PunctualLight::directional(-direction, Vector3::repeat(PI))],
+    };
+    let default_material = Material::default();
+
+    let mut pass = RenderPass::new(Viewport::new(size, size));
+    pass.clear();
+
+    draw_scene(
+        &mut pass,
+        &scene,
+        None,
+        view.reversed_z,
+        |instance, primitive| PbrProgram {
+            primitive,
+            material: primitive
+                .material
+                .and_then(|index| scene.materials.get(index))
+                .unwrap_or(&default_material),
+            textures: &scene.textures,
+            model: instance.world,
+            normal_matrix: instance.normal_matrix(),
+            view_projection: view.view_projection(),
+            eye: view.eye.coords,
+            lighting: &lighting,
+        },
+    );
+
+    pass.frame_texture
+}